@@ -0,0 +1,318 @@
+use crate::settings;
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::Algorithm;
+use log::{debug, error, warn};
+use serde::Deserialize;
+use std::env;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+/// The maximum time a single request for the JWKS may take.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How usable a cached JWKS is, depending on its age.
+#[derive(Debug, PartialEq)]
+enum Freshness {
+    /// Younger than the TTL, can be used as is.
+    Fresh,
+    /// Older than the TTL, but within the stale period. Only used if it can't be refetched.
+    Stale,
+    /// Too old to be used at all.
+    Expired,
+}
+
+impl Freshness {
+    fn of(age: Duration, ttl: Duration, stale_period: Duration) -> Self {
+        if age < ttl {
+            Freshness::Fresh
+        } else if age < ttl + stale_period {
+            Freshness::Stale
+        } else {
+            Freshness::Expired
+        }
+    }
+}
+
+/// The parts of an OIDC discovery document needed to validate tokens.
+#[derive(Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    jwks_uri: String,
+    #[serde(default)]
+    id_token_signing_alg_values_supported: Vec<String>,
+}
+
+/// Gets the url of the discovery document of the OIDC provider with the given issuer.
+fn discovery_url(issuer_url: &str) -> String {
+    format!("{}/.well-known/openid-configuration", issuer_url.trim_end_matches('/'))
+}
+
+/// Parses the given names of signing algorithms, skipping the ones that aren't supported.
+fn parse_algorithms(names: &[String]) -> Vec<Algorithm> {
+    names
+        .iter()
+        .filter_map(|name| Algorithm::from_str(name).ok())
+        .collect()
+}
+
+/// A JWKS, together with the time it has been fetched.
+struct CachedJwks {
+    jwks: JwkSet,
+    /// The algorithms tokens may be signed with. Empty if unknown, e.g. if the url of the JWKS
+    /// has been configured instead of being discovered.
+    algorithms: Vec<Algorithm>,
+    fetched_at: Instant,
+}
+
+/// Caches the JWKS of the identity provider, so tokens can be validated without a request to
+/// the identity provider each. The url of the JWKS is taken from the OIDC discovery document of
+/// the issuer, unless it is configured explicitly. The JWKS is refreshed in the background and
+/// refetched right away if a token is signed with an unknown key, e.g. after the keys have been
+/// rotated. If the identity provider can't be reached, the last JWKS is used for the configured
+/// stale period.
+pub struct JwksCache {
+    issuer_url: String,
+    /// Overrides the url of the JWKS from the discovery document.
+    jwks_url: Option<String>,
+    client: reqwest::Client,
+    ttl: Duration,
+    stale_period: Duration,
+    min_refetch_interval: Duration,
+    cached: RwLock<Option<CachedJwks>>,
+    /// The time and result of the last fetch. Held while fetching, so concurrent requests don't
+    /// fetch the JWKS multiple times.
+    last_fetch: Mutex<Option<(Instant, Result<(), String>)>>,
+}
+
+impl JwksCache {
+    /// Creates an empty cache for the JWKS of the identity provider in the given settings.
+    pub fn new(oidc_settings: &settings::Oidc) -> Result<Self, reqwest::Error> {
+        let ignore_tls = env::var("EXPENSE_TRACKER_IGNORE_TLS").is_ok();
+
+        let mut client_builder = reqwest::Client::builder().timeout(FETCH_TIMEOUT);
+
+        if ignore_tls {
+            warn!("TLS has been disabled! Please enable for production use!");
+            client_builder = client_builder.danger_accept_invalid_certs(true);
+        }
+
+        Ok(Self {
+            issuer_url: oidc_settings.issuer_url().to_string(),
+            jwks_url: oidc_settings.jwks_uri().map(str::to_string),
+            client: client_builder.build()?,
+            ttl: Duration::from_secs(oidc_settings.jwks_ttl()),
+            stale_period: Duration::from_secs(oidc_settings.jwks_stale_period()),
+            min_refetch_interval: Duration::from_secs(oidc_settings.jwks_min_refetch_interval()),
+            cached: RwLock::new(None),
+            last_fetch: Mutex::new(None),
+        })
+    }
+
+    /// Gets the key with the given kid, together with the algorithms tokens may be signed with.
+    /// The algorithms are empty if unknown. The JWKS is fetched if the cache is not fresh or
+    /// does not contain the key.
+    pub async fn get_key(&self, kid: &str) -> Result<(Jwk, Vec<Algorithm>), String> {
+        if let Some(key) = self.find_cached(kid, false).await {
+            return Ok(key);
+        }
+
+        // either the cache is not fresh or the key is unknown, e.g. after a key rotation
+        let refreshed = self.refresh(true).await;
+
+        if let Some(key) = self.find_cached(kid, true).await {
+            return Ok(key);
+        }
+
+        match refreshed {
+            Ok(()) => {
+                error!("Key not found in JWKS");
+                Err("Key not found in JWKS".to_string())
+            }
+            Err(e) => {
+                error!("Failed to fetch JWKS: {}", e);
+                Err("Failed to fetch JWKS".to_string())
+            }
+        }
+    }
+
+    /// Looks up the key with the given kid in the cache. Stale keys are only used if `allow_stale`.
+    async fn find_cached(&self, kid: &str, allow_stale: bool) -> Option<(Jwk, Vec<Algorithm>)> {
+        let cached = self.cached.read().await;
+        let cached = cached.as_ref()?;
+
+        let key = match Freshness::of(cached.fetched_at.elapsed(), self.ttl, self.stale_period) {
+            Freshness::Fresh => cached.jwks.find(kid).cloned(),
+            Freshness::Stale if allow_stale => {
+                let key = cached.jwks.find(kid).cloned();
+
+                if key.is_some() {
+                    warn!("Using a stale JWKS, as it could not be refreshed");
+                }
+
+                key
+            }
+            _ => None,
+        };
+
+        key.map(|key| (key, cached.algorithms.clone()))
+    }
+
+    /// Fetches the JWKS and replaces the cached one. If `on_demand`, the JWKS is not fetched
+    /// again if the last fetch was less than the minimum refetch interval ago, so tokens with
+    /// unknown keys can't be used to flood the identity provider with requests. The result of the
+    /// last fetch is returned instead.
+    async fn refresh(&self, on_demand: bool) -> Result<(), String> {
+        let requested_at = Instant::now();
+        let mut last_fetch = self.last_fetch.lock().await;
+
+        if let Some((fetched_at, result)) = last_fetch.as_ref() {
+            // another request might have fetched the JWKS while this one waited for the lock
+            if on_demand
+                && (*fetched_at > requested_at
+                    || fetched_at.elapsed() < self.min_refetch_interval)
+            {
+                debug!("Skipping JWKS refetch, as it has just been fetched");
+                return result.clone();
+            }
+        }
+
+        let result = match self.fetch().await {
+            Ok((jwks, algorithms)) => {
+                *self.cached.write().await = Some(CachedJwks {
+                    jwks,
+                    algorithms,
+                    fetched_at: Instant::now(),
+                });
+                Ok(())
+            }
+            Err(e) => Err(e),
+        };
+
+        *last_fetch = Some((Instant::now(), result.clone()));
+
+        result
+    }
+
+    /// Fetches the jwks used for validation of the token, together with the algorithms tokens may
+    /// be signed with. Unless the url of the JWKS is configured, both are discovered first.
+    async fn fetch(&self) -> Result<(JwkSet, Vec<Algorithm>), String> {
+        let (jwks_url, algorithms) = match &self.jwks_url {
+            Some(jwks_url) => (jwks_url.clone(), vec![]),
+            None => {
+                let metadata = self.discover().await?;
+                let algorithms = parse_algorithms(&metadata.id_token_signing_alg_values_supported);
+                (metadata.jwks_uri, algorithms)
+            }
+        };
+
+        debug!("Fetching JWKS from {}", jwks_url);
+        let response = self
+            .client
+            .get(&jwks_url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+        debug!("Received JWKS response with status {}", response.status());
+        let jwks = response.json::<JwkSet>().await.map_err(|e| e.to_string())?;
+
+        Ok((jwks, algorithms))
+    }
+
+    /// Fetches the OIDC discovery document of the issuer.
+    async fn discover(&self) -> Result<ProviderMetadata, String> {
+        let url = discovery_url(&self.issuer_url);
+
+        debug!("Fetching OIDC discovery document from {}", url);
+        let metadata = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?
+            .json::<ProviderMetadata>()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        // as required by OIDC discovery, so a document of another issuer isn't trusted
+        if metadata.issuer.trim_end_matches('/') != self.issuer_url.trim_end_matches('/') {
+            return Err(format!(
+                "The discovery document is issued by {}, expected {}",
+                metadata.issuer, self.issuer_url
+            ));
+        }
+
+        Ok(metadata)
+    }
+
+    /// Refreshes the JWKS in the background, each time the TTL has passed. Failed refreshes are
+    /// retried after the minimum refetch interval.
+    pub fn spawn_refresh(self: Arc<Self>) {
+        tokio::spawn(async move {
+            loop {
+                let next_refresh = match self.refresh(false).await {
+                    Ok(()) => self.ttl,
+                    Err(e) => {
+                        warn!("Failed to refresh JWKS in the background: {}", e);
+                        self.min_refetch_interval
+                    }
+                };
+
+                tokio::time::sleep(next_refresh).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::jwks::{discovery_url, parse_algorithms, Freshness};
+    use jsonwebtoken::Algorithm;
+    use std::time::Duration;
+
+    #[test]
+    fn test_freshness() {
+        let ttl = Duration::from_secs(300);
+        let stale_period = Duration::from_secs(3600);
+        let freshness = |age| Freshness::of(Duration::from_secs(age), ttl, stale_period);
+
+        assert_eq!(freshness(0), Freshness::Fresh);
+        assert_eq!(freshness(299), Freshness::Fresh);
+        assert_eq!(freshness(300), Freshness::Stale);
+        assert_eq!(freshness(3899), Freshness::Stale);
+        assert_eq!(freshness(3900), Freshness::Expired);
+    }
+
+    #[test]
+    fn test_freshness_without_stale_period() {
+        let ttl = Duration::from_secs(300);
+        let freshness = Freshness::of(ttl, ttl, Duration::ZERO);
+
+        assert_eq!(freshness, Freshness::Expired);
+    }
+
+    #[test]
+    fn test_discovery_url() {
+        assert_eq!(
+            discovery_url("https://auth.example.com/realms/local"),
+            "https://auth.example.com/realms/local/.well-known/openid-configuration"
+        );
+        assert_eq!(
+            discovery_url("https://auth.example.com/"),
+            "https://auth.example.com/.well-known/openid-configuration"
+        );
+    }
+
+    #[test]
+    fn test_parse_algorithms() {
+        let names = ["RS256", "ES384", "none", "HS256"].map(str::to_string);
+
+        assert_eq!(
+            parse_algorithms(&names),
+            vec![Algorithm::RS256, Algorithm::ES384, Algorithm::HS256]
+        );
+    }
+}
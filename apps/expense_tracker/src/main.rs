@@ -1,21 +1,25 @@
 extern crate core;
+mod jwks;
 mod settings;
 
-use crate::settings::Settings;
+use crate::jwks::JwksCache;
+use crate::settings::{AuthMode, Settings};
 use axum::body::Body;
+use axum::extract::State;
 use axum::http::{HeaderValue, Request, StatusCode};
 use axum::middleware::Next;
 use axum::response::Response;
 use expense_tracker_api::api;
-use expense_tracker_db::setup::setup_db;
-use jsonwebtoken::jwk::JwkSet;
+use expense_tracker_api::api_token_api::api_token_api::ApiTokenAuth;
+use expense_tracker_api::auth::auth::{ClaimNames, LocalTokens, TokenUse};
+use expense_tracker_db::setup::{setup_db, DbPool};
+use expense_tracker_services::local_account_service::local_account_service;
 use jsonwebtoken::{decode, DecodingKey, Validation};
-use log::{debug, error, info, warn};
-use std::env;
+use log::{debug, error, info};
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufRead, Write};
 use std::net::SocketAddr;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 use clap::Parser;
 use tower::ServiceBuilder;
@@ -32,12 +36,31 @@ use utoipa_swagger_ui::SwaggerUi;
 const SETTINGS_FILE: &str = "config/settings.toml";
 static APP_SETTINGS: LazyLock<Settings> =
     LazyLock::new(|| Settings::new(SETTINGS_FILE).expect("Settings file must exist"));
+static JWKS_CACHE: LazyLock<Arc<JwksCache>> = LazyLock::new(|| {
+    let oidc = APP_SETTINGS.oidc().expect("The JWKS is only used with an OIDC provider");
+    Arc::new(JwksCache::new(oidc).expect("Failed to create HTTP client"))
+});
+static LOCAL_TOKENS: LazyLock<LocalTokens> = LazyLock::new(|| match APP_SETTINGS.auth_mode() {
+    AuthMode::Local(local_auth) => LocalTokens::new(
+        local_auth.secret().as_bytes(),
+        local_auth.access_token_lifetime(),
+        local_auth.refresh_token_lifetime(),
+    ),
+    AuthMode::Oidc(_) => panic!("Local tokens are only used with the built-in authentication"),
+});
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
     #[arg(short, long)]
-    export_openapi : bool
+    export_openapi : bool,
+    /// Creates a local account with the given username for the built-in authentication and
+    /// exits. The password is read from stdin.
+    #[arg(long, value_name = "USERNAME")]
+    add_user: Option<String>,
+    /// Grants admin rights to the account created with --add-user.
+    #[arg(long, requires = "add_user")]
+    admin: bool,
 }
 
 #[derive(OpenApi)]
@@ -59,47 +82,9 @@ impl Modify for SecurityAddon {
     }
 }
 
-/// Fetches the jwks used for validation of the token
-async fn fetch_jwks(jwks_url: &str) -> Result<JwkSet, reqwest::Error> {
-    let ignore_tls = env::var("EXPENSE_TRACKER_IGNORE_TLS").is_ok();
-
-    let mut client_builder = reqwest::Client::builder();
-
-    if ignore_tls {
-        warn!("TLS has been disabled! Please enable for production use!");
-        client_builder = client_builder.danger_accept_invalid_certs(true);
-    }
-
-    let client = client_builder.build()?;
-
-    debug!("Fetching JWKS from {}", jwks_url);
-    let response = client.get(jwks_url).send().await?;
-    debug!("Received JWKS response with status {}", response.status());
-    let jwks = response.json::<JwkSet>().await?;
-    Ok(jwks)
-}
-
 async fn validate_token(token: &str, oidc_settings: &settings::Oidc) -> Result<Value, String> {
     debug!("Starting token validation");
 
-    // prefer optional jwks_uri, but fallback to issuer_url if not set
-    // the latter should be the default in most cases, e.g. in production where
-    // the jwks_uri is the same as the issuer_url
-    let jwks_uri = oidc_settings.jwks_uri().unwrap_or(oidc_settings.issuer_url());
-
-    let jwks_url = format!(
-        "{}/protocol/openid-connect/certs",
-        jwks_uri
-    );
-
-    let jwks = match fetch_jwks(jwks_url.as_str()).await {
-        Ok(jwks) => jwks,
-        Err(e) => {
-            error!("Failed to fetch JWKS: {}", e);
-            return Err("Failed to fetch JWKS".to_string());
-        }
-    };
-
     let header = match jsonwebtoken::decode_header(token) {
         Ok(header) => header,
         Err(e) => {
@@ -116,15 +101,14 @@ async fn validate_token(token: &str, oidc_settings: &settings::Oidc) -> Result<V
         }
     };
 
-    let key = match jwks.find(&kid) {
-        Some(key) => key,
-        None => {
-            error!("Key not found in JWKS");
-            return Err("Key not found in JWKS".to_string());
-        }
-    };
+    let (key, algorithms) = JWKS_CACHE.get_key(&kid).await?;
 
-    let decoding_key = match DecodingKey::from_jwk(key) {
+    if !algorithms.is_empty() && !algorithms.contains(&header.alg) {
+        error!("Token is signed with unsupported algorithm {:?}", header.alg);
+        return Err("Token is signed with an unsupported algorithm".to_string());
+    }
+
+    let decoding_key = match DecodingKey::from_jwk(&key) {
         Ok(key) => key,
         Err(e) => {
             error!("Failed to create decoding key: {}", e);
@@ -147,7 +131,11 @@ async fn validate_token(token: &str, oidc_settings: &settings::Oidc) -> Result<V
     }
 }
 
-async fn auth_middleware(request: Request<Body>, next: Next) -> Result<Response, Response<String>> {
+async fn auth_middleware(
+    State(api_token_auth): State<ApiTokenAuth>,
+    request: Request<Body>,
+    next: Next,
+) -> Result<Response, Response<String>> {
     debug!("Auth middleware entered!");
     let (parts, body) = request.into_parts();
 
@@ -165,12 +153,24 @@ async fn auth_middleware(request: Request<Body>, next: Next) -> Result<Response,
 
     debug!("Token extracted!");
 
-    let claims = validate_token(token, APP_SETTINGS.oidc())
-        .await
-        .map_err(|e| {
+    // API tokens are accepted regardless of how users log in
+    let claims = if ApiTokenAuth::is_api_token(token) {
+        api_token_auth
+            .authenticate(token, &parts.method, parts.uri.path())
+            .await
+    } else {
+        match APP_SETTINGS.auth_mode() {
+            AuthMode::Oidc(oidc) => validate_token(token, oidc).await,
+            AuthMode::Local(_) => LOCAL_TOKENS.validate(token, TokenUse::Access),
+        }
+        .map_err(|e| (StatusCode::UNAUTHORIZED, format!("Unauthorized, {e}")))
+    };
+
+    let claims = claims
+        .map_err(|(status, message)| {
             Response::builder()
-                .status(StatusCode::UNAUTHORIZED)
-                .body(format!("Unauthorized, {e}"))
+                .status(status)
+                .body(message)
                 .unwrap()
         })?;
 
@@ -182,6 +182,26 @@ async fn auth_middleware(request: Request<Body>, next: Next) -> Result<Response,
     Ok(next.run(request).await)
 }
 
+/// Creates a local account with the given username, reading its password from the first line
+/// of stdin.
+async fn add_local_account(pool: DbPool, username: &str, is_admin: bool) -> Result<(), String> {
+    eprint!("Password for {username}: ");
+
+    let mut password = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut password)
+        .map_err(|e| format!("Failed to read the password: {e}"))?;
+
+    let (_, user) = local_account_service::new_service(pool)
+        .create_account(username, password.trim_end_matches(['\r', '\n']), is_admin)
+        .await
+        .map_err(|e| format!("Failed to create the account: {e}"))?;
+
+    println!("Created account {} for user {}", username, user.id());
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
@@ -201,6 +221,14 @@ async fn main() {
         .await
         .expect("Failed to create pool");
 
+    if let Some(username) = args.add_user {
+        if let Err(e) = add_local_account(pool, &username, args.admin).await {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // To get a JWT: curl -X POST 'http://localhost:8080/realms/expense-tracker-dev/protocol/openid-connect/token' -H 'Content-Type: application/x-www-form-urlencoded' -d 'client_id=<CLIENT_ID>' -d 'username=<USER>' -d 'password=<PASSWORD>' -d 'grant_type=password' -d 'scope=email profile' -d 'client_secret=<CLIENT_SECRET>'
 
     let origins = [
@@ -221,27 +249,50 @@ async fn main() {
     let cors = ServiceBuilder::new()
         .layer(cors_layer);
 
+    let claim_names = match APP_SETTINGS.auth_mode() {
+        AuthMode::Oidc(oidc) => ClaimNames::new(
+            oidc.user_id_claim().to_string(),
+            oidc.user_name_claim().to_string(),
+            oidc.user_email_claim().to_string(),
+            oidc.user_avatar_claim().to_string(),
+            oidc.roles_claim().to_string(),
+            oidc.admin_role().to_string(),
+        ),
+        AuthMode::Local(_) => LocalTokens::claim_names(),
+    };
+
     let oauth_validator = ServiceBuilder::new()
         .layer(cors.clone())
-        .layer(axum::middleware::from_fn(auth_middleware));
+        .layer(axum::middleware::from_fn_with_state(
+            ApiTokenAuth::new(pool.clone(), claim_names.clone()),
+            auth_middleware,
+        ));
 
-    let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-        .nest("/api", api::router(pool).await)
+    let mut open_api_router = OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .nest("/api", api::router(pool.clone(), claim_names).await)
         .layer(oauth_validator)
-        .nest("/api", api::add_health_api().await)
+        .nest("/api", api::add_health_api().await);
+
+    if let AuthMode::Local(_) = APP_SETTINGS.auth_mode() {
+        open_api_router = open_api_router
+            .nest("/api", api::add_local_auth_api(pool, LOCAL_TOKENS.clone()).await);
+    }
+
+    let (router, api) = open_api_router
         .layer(cors)
         // 3. Add a TraceLayer to automatically create and enter spans
         .layer(TraceLayer::new_for_http())
         .split_for_parts();
 
+    let swagger_ui = SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", api.clone());
+
     // setup oAuth with utoipa swagger ui
-    let oauth_config = oauth::Config::new().client_id(APP_SETTINGS.oidc().audience());
+    let swagger_ui = match APP_SETTINGS.auth_mode() {
+        AuthMode::Oidc(oidc) => swagger_ui.oauth(oauth::Config::new().client_id(oidc.audience())),
+        AuthMode::Local(_) => swagger_ui,
+    };
 
-    let router = router.merge(
-        SwaggerUi::new("/swagger-ui")
-            .url("/api-docs/openapi.json", api.clone())
-            .oauth(oauth_config),
-    );
+    let router = router.merge(swagger_ui);
 
     if args.export_openapi {
         let mut file = File::create("./openapi/expense_tracker_openapi.json").expect("Failed to create file");
@@ -250,6 +301,10 @@ async fn main() {
         return;
     }
 
+    if let AuthMode::Oidc(_) = APP_SETTINGS.auth_mode() {
+        JWKS_CACHE.clone().spawn_refresh();
+    }
+
     let addr = SocketAddr::from(([0, 0, 0, 0], APP_SETTINGS.expense_tracker().port()));
 
     info!("listening on {}", addr);
@@ -7,6 +7,35 @@ pub struct Oidc {
     issuer_url: String,
     jwks_uri: Option<String>,
     audience: String,
+    jwks_ttl: Option<u64>,
+    jwks_stale_period: Option<u64>,
+    jwks_min_refetch_interval: Option<u64>,
+    user_id_claim: Option<String>,
+    user_name_claim: Option<String>,
+    user_email_claim: Option<String>,
+    user_avatar_claim: Option<String>,
+    roles_claim: Option<String>,
+    admin_role: Option<String>,
+}
+
+/// The settings of the built-in authentication with local accounts, used instead of an OIDC
+/// provider.
+#[derive(Deserialize, Debug)]
+pub struct LocalAuth {
+    secret: String,
+    access_token_lifetime: Option<u64>,
+    refresh_token_lifetime: Option<u64>,
+}
+
+/// The minimum length of the secret the tokens of the built-in authentication are signed with.
+const MIN_SECRET_LENGTH: usize = 32;
+
+/// How the users of the API are authenticated.
+pub enum AuthMode<'a> {
+    /// With the tokens of an external OIDC provider, e.g. Keycloak.
+    Oidc(&'a Oidc),
+    /// With local accounts and tokens issued by the API itself.
+    Local(&'a LocalAuth),
 }
 
 #[derive(Deserialize, Debug)]
@@ -39,6 +68,8 @@ impl Oidc {
         &self.issuer_url
     }
 
+    /// The url of the JWKS. If not set, it is taken from the OIDC discovery document of the
+    /// issuer.
     pub fn jwks_uri(&self) -> Option<&str> {
         self.jwks_uri.as_deref()
     }
@@ -46,11 +77,78 @@ impl Oidc {
     pub fn audience(&self) -> &str {
         &self.audience
     }
+
+    /// The number of seconds the JWKS is cached and refreshed after. Defaults to 5 minutes.
+    pub fn jwks_ttl(&self) -> u64 {
+        self.jwks_ttl.unwrap_or(300)
+    }
+
+    /// The number of seconds an expired JWKS is still used, if it can't be refreshed, e.g. because
+    /// the identity provider is down. Defaults to 1 hour.
+    pub fn jwks_stale_period(&self) -> u64 {
+        self.jwks_stale_period.unwrap_or(3600)
+    }
+
+    /// The minimum number of seconds between two fetches of the JWKS caused by tokens signed with
+    /// an unknown key. Defaults to 10 seconds.
+    pub fn jwks_min_refetch_interval(&self) -> u64 {
+        self.jwks_min_refetch_interval.unwrap_or(10)
+    }
+
+    /// The claim of a token holding the id of the user, which must be a UUID. Defaults to `sub`.
+    pub fn user_id_claim(&self) -> &str {
+        self.user_id_claim.as_deref().unwrap_or("sub")
+    }
+
+    /// The claim of a token holding the name of the user. Defaults to `preferred_username`.
+    pub fn user_name_claim(&self) -> &str {
+        self.user_name_claim.as_deref().unwrap_or("preferred_username")
+    }
+
+    /// The claim of a token holding the email address of the user. Defaults to `email`.
+    pub fn user_email_claim(&self) -> &str {
+        self.user_email_claim.as_deref().unwrap_or("email")
+    }
+
+    /// The claim of a token holding the url of the avatar of the user. Defaults to `picture`.
+    pub fn user_avatar_claim(&self) -> &str {
+        self.user_avatar_claim.as_deref().unwrap_or("picture")
+    }
+
+    /// The path of the claim of a token holding the roles of the user, with nested claims
+    /// separated by dots. Defaults to `realm_access.roles`, where Keycloak puts the realm roles.
+    pub fn roles_claim(&self) -> &str {
+        self.roles_claim.as_deref().unwrap_or("realm_access.roles")
+    }
+
+    /// The role granting admin rights. Defaults to `admin`.
+    pub fn admin_role(&self) -> &str {
+        self.admin_role.as_deref().unwrap_or("admin")
+    }
+}
+
+impl LocalAuth {
+    /// The secret the tokens are signed with. Must be kept secret, since anyone knowing it can
+    /// issue tokens for any user.
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    /// The number of seconds access tokens are valid for. Defaults to 15 minutes.
+    pub fn access_token_lifetime(&self) -> u64 {
+        self.access_token_lifetime.unwrap_or(900)
+    }
+
+    /// The number of seconds refresh tokens are valid for. Defaults to 30 days.
+    pub fn refresh_token_lifetime(&self) -> u64 {
+        self.refresh_token_lifetime.unwrap_or(2_592_000)
+    }
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Settings {
-    oidc: Oidc,
+    oidc: Option<Oidc>,
+    local_auth: Option<LocalAuth>,
     expense_tracker: ExpenseTracker,
 }
 
@@ -61,12 +159,35 @@ impl Settings {
             .add_source(config::File::with_name(file_name))
             .build()?;
 
-        s.try_deserialize()
+        let settings: Self = s.try_deserialize()?;
+
+        match (&settings.oidc, &settings.local_auth) {
+            (Some(_), Some(_)) | (None, None) => Err(ConfigError::Message(
+                "Exactly one of [oidc] and [local_auth] must be configured".to_string(),
+            )),
+            (None, Some(local_auth)) if local_auth.secret.len() < MIN_SECRET_LENGTH => {
+                Err(ConfigError::Message(format!(
+                    "The secret of [local_auth] must have at least {} characters",
+                    MIN_SECRET_LENGTH
+                )))
+            }
+            _ => Ok(settings),
+        }
+    }
+
+    /// Gets the OIDC settings stored in your settings file, if an OIDC provider is used.
+    pub fn oidc(&self) -> Option<&Oidc> {
+        self.oidc.as_ref()
     }
 
-    /// Gets the OIDC settings stored in your settings file.
-    pub fn oidc(&self) -> &Oidc {
-        &self.oidc
+    /// Gets how users are authenticated, depending on whether [oidc] or [local_auth] is
+    /// configured.
+    pub fn auth_mode(&self) -> AuthMode<'_> {
+        match (&self.oidc, &self.local_auth) {
+            (_, Some(local_auth)) => AuthMode::Local(local_auth),
+            (Some(oidc), None) => AuthMode::Oidc(oidc),
+            (None, None) => unreachable!("checked when the settings are loaded"),
+        }
     }
 
     /// Gets the expense_tracker settings stored in your settings file.
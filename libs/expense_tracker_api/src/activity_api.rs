@@ -0,0 +1,206 @@
+pub mod activity_api {
+    use crate::api::{check_error, ApiResponse};
+    use crate::auth::auth::AuthUser;
+    use crate::user_api::user_api::UserDTO;
+    use axum::extract::{Path, Query, State};
+    use axum::http::StatusCode;
+    use axum::Json;
+    use chrono::{DateTime, Utc};
+    use expense_tracker_db::pot_events::pot_events::{PotEvent, PotEventKind};
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::users::users::User;
+    use expense_tracker_services::expense_service::expense_service::Pagination;
+    use expense_tracker_services::pot_event_service::pot_event_service;
+    use expense_tracker_services::pot_event_service::pot_event_service::PotEventService;
+    use serde::{Deserialize, Serialize};
+    use std::sync::Arc;
+    use utoipa::{IntoParams, ToSchema};
+    use utoipa_axum::router::OpenApiRouter;
+    use utoipa_axum::routes;
+    use uuid::Uuid;
+
+    /// Holds the App State for the ActivityAPI.
+    pub struct ActivityApiState {
+        pot_event_service: PotEventService,
+    }
+
+    /// Registers all functions of the Activity API.
+    pub fn register(pool: DbPool) -> OpenApiRouter {
+        let shared_state = Arc::new(ActivityApiState {
+            pot_event_service: pot_event_service::new_service(pool),
+        });
+
+        OpenApiRouter::new()
+            .routes(routes!(get_pot_activity))
+            .with_state(shared_state)
+    }
+
+    /// The kinds of events in the activity of a pot.
+    #[derive(ToSchema, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum PotEventKindDTO {
+        PotCreated,
+        PotUpdated,
+        PotArchived,
+        PotUnarchived,
+        /// All expenses of the pot have been marked as paid at once.
+        PotPaid,
+        MemberAdded,
+        /// A user joined the pot by accepting an invitation or with an invite code.
+        MemberJoined,
+        MemberRemoved,
+        MemberLeft,
+        RoleChanged,
+        OwnershipTransferred,
+        GuestAdded,
+        /// A user claimed a guest, taking over their splits, expenses and settlements.
+        GuestClaimed,
+        ExpenseAdded,
+        ExpenseUpdated,
+        ExpenseDeleted,
+        ExpensePaid,
+        SettlementRecorded,
+    }
+
+    impl PotEventKindDTO {
+        /// Creates a new PotEventKindDTO from a db PotEventKind.
+        pub fn from(kind: PotEventKind) -> Self {
+            match kind {
+                PotEventKind::PotCreated => PotEventKindDTO::PotCreated,
+                PotEventKind::PotUpdated => PotEventKindDTO::PotUpdated,
+                PotEventKind::PotArchived => PotEventKindDTO::PotArchived,
+                PotEventKind::PotUnarchived => PotEventKindDTO::PotUnarchived,
+                PotEventKind::PotPaid => PotEventKindDTO::PotPaid,
+                PotEventKind::MemberAdded => PotEventKindDTO::MemberAdded,
+                PotEventKind::MemberJoined => PotEventKindDTO::MemberJoined,
+                PotEventKind::MemberRemoved => PotEventKindDTO::MemberRemoved,
+                PotEventKind::MemberLeft => PotEventKindDTO::MemberLeft,
+                PotEventKind::RoleChanged => PotEventKindDTO::RoleChanged,
+                PotEventKind::OwnershipTransferred => PotEventKindDTO::OwnershipTransferred,
+                PotEventKind::GuestAdded => PotEventKindDTO::GuestAdded,
+                PotEventKind::GuestClaimed => PotEventKindDTO::GuestClaimed,
+                PotEventKind::ExpenseAdded => PotEventKindDTO::ExpenseAdded,
+                PotEventKind::ExpenseUpdated => PotEventKindDTO::ExpenseUpdated,
+                PotEventKind::ExpenseDeleted => PotEventKindDTO::ExpenseDeleted,
+                PotEventKind::ExpensePaid => PotEventKindDTO::ExpensePaid,
+                PotEventKind::SettlementRecorded => PotEventKindDTO::SettlementRecorded,
+            }
+        }
+    }
+
+    /// DTO of a single event in the activity of a pot.
+    #[derive(ToSchema, Serialize)]
+    pub struct PotEventDTO {
+        id: i32,
+        kind: PotEventKindDTO,
+        /// The user that triggered the event. Null for admins of the application and deleted
+        /// users.
+        actor: Option<UserDTO>,
+        /// The user the event is about, e.g. the member that has been added.
+        subject_id: Option<Uuid>,
+        /// The expense the event is about. The expense may have been deleted since.
+        expense_id: Option<i32>,
+        /// A short description of what happened, e.g. the description and amount of an expense.
+        details: Option<String>,
+        created_at: DateTime<Utc>,
+    }
+
+    impl PotEventDTO {
+        /// Creates a new PotEventDTO from a db PotEvent and the user that triggered it.
+        pub fn from(event: &PotEvent, actor: Option<User>) -> Self {
+            Self {
+                id: event.id(),
+                kind: PotEventKindDTO::from(event.kind()),
+                actor: actor.map(UserDTO::from),
+                subject_id: event.subject_id(),
+                expense_id: event.expense_id(),
+                details: event.details().map(str::to_string),
+                created_at: event.created_at(),
+            }
+        }
+    }
+
+    /// DTO for a single page of the activity of a pot.
+    #[derive(ToSchema, Serialize)]
+    pub struct PotActivityDTO {
+        /// The events, newest first.
+        events: Vec<PotEventDTO>,
+        page: i64,
+        per_page: i64,
+        /// The number of events on all pages.
+        total_count: i64,
+    }
+
+    /// The parameters of a listing of the activity of a pot.
+    #[derive(IntoParams, Deserialize)]
+    pub struct ActivityParams {
+        /// The number of the page, starting at 1. Defaults to 1.
+        #[serde(default = "default_page")]
+        page: i64,
+        /// The number of events per page, at most 200. Defaults to 50.
+        #[serde(default = "default_per_page")]
+        per_page: i64,
+    }
+
+    fn default_page() -> i64 {
+        1
+    }
+
+    fn default_per_page() -> i64 {
+        50
+    }
+
+    /// Gets the activity of the given pot, meaning everything that happened in it, newest first.
+    /// Events are recorded for changes of the pot, its members, expenses and settlements.
+    #[utoipa::path(
+        get,
+        path = "/pots/{pot_id}/activity",
+        tag = "Activity",
+        responses(
+            (status = 200, description = "A single page of the activity of the pot.", body = PotActivityDTO),
+            (status = 400, description = "The page or the page size is out of bounds."),
+            (status = 404, description = "Indicates that the desired pot does not exists."),
+            (status = 500, description = "An internal server error occurred")
+        ),
+        params(
+            ("pot_id" = i32, Path, description = "Pot database id for the pot."),
+            ActivityParams
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn get_pot_activity(
+        State(activity_api_state): State<Arc<ActivityApiState>>,
+        Path(pot_id): Path<i32>,
+        Query(params): Query<ActivityParams>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<PotActivityDTO>, ApiResponse<String>> {
+        let result = activity_api_state
+            .pot_event_service
+            .get_events(
+                pot_id,
+                auth_user.id(),
+                &Pagination::new(params.page, params.per_page),
+            )
+            .await
+            .map_err(check_error)?;
+
+        let total_count = result.total_count();
+        let events = result
+            .into_events()
+            .into_iter()
+            .map(|(event, actor)| PotEventDTO::from(&event, actor))
+            .collect();
+
+        Ok((
+            StatusCode::OK,
+            Json(PotActivityDTO {
+                events,
+                page: params.page,
+                per_page: params.per_page,
+                total_count,
+            }),
+        ))
+    }
+}
@@ -0,0 +1,263 @@
+pub mod admin_api {
+    use crate::api::{check_error, ApiResponse};
+    use crate::auth::auth::AuthUser;
+    use crate::currency_api::currency_api::CurrencyDTO;
+    use crate::pot_api::pot_api::PotDTO;
+    use crate::template_api::template_api::PotTemplateDTO;
+    use crate::user_api::user_api::UserDTO;
+    use axum::extract::{Path, State};
+    use axum::http::StatusCode;
+    use axum::Json;
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_services::currency_service::currency_service;
+    use expense_tracker_services::currency_service::currency_service::CurrencyService;
+    use expense_tracker_services::expense_service::expense_service::PotBalance;
+    use expense_tracker_services::pot_service::pot_service;
+    use expense_tracker_services::pot_service::pot_service::PotService;
+    use expense_tracker_services::template_service::pot_template_service::PotTemplateService;
+    use expense_tracker_services::user_service::user_service;
+    use expense_tracker_services::user_service::user_service::UserService;
+    use serde::Deserialize;
+    use std::sync::Arc;
+    use utoipa::ToSchema;
+    use utoipa_axum::router::OpenApiRouter;
+    use utoipa_axum::routes;
+    use uuid::Uuid;
+
+    /// Holds the App State for the AdminAPI.
+    pub struct AdminApiState {
+        pot_service: PotService,
+        pot_template_service: PotTemplateService,
+        currency_service: CurrencyService,
+        user_service: UserService,
+    }
+
+    /// Registers all functions of the Admin API. Every function requires the admin role.
+    pub fn register(pool: DbPool) -> OpenApiRouter {
+        let shared_state = Arc::new(AdminApiState {
+            pot_service: pot_service::new_service(pool.clone()),
+            pot_template_service: PotTemplateService::new_service(pool.clone()),
+            currency_service: currency_service::new_service(pool.clone()),
+            user_service: user_service::new_service(pool.clone()),
+        });
+
+        OpenApiRouter::new()
+            .routes(routes!(get_all_pots))
+            .routes(routes!(get_all_templates))
+            .routes(routes!(reassign_pot_owner))
+            .routes(routes!(reassign_template_owner))
+            .routes(routes!(delete_user))
+            .with_state(shared_state)
+    }
+
+    /// DTO used to make another user the owner of a pot or template.
+    #[derive(ToSchema, Deserialize)]
+    pub struct NewOwnerDTO {
+        owner_id: Uuid,
+    }
+
+    /// Gets all pots of all users. The balances are not calculated, since the admin is usually
+    /// not part of the pots.
+    #[utoipa::path(
+        get,
+        path = "/admin/pots",
+        tag = "Admin",
+        responses(
+            (status = 200, description = "All pots", body = Vec<PotDTO>),
+            (status = 403, description = "The user is not an admin"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn get_all_pots(
+        State(admin_api_state): State<Arc<AdminApiState>>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<Vec<PotDTO>>, ApiResponse<String>> {
+        auth_user.require_admin()?;
+
+        let loaded_pots = admin_api_state
+            .pot_service
+            .get_all_pots()
+            .await
+            .map_err(check_error)?;
+
+        let all_currencies = admin_api_state
+            .currency_service
+            .get_currencies()
+            .await
+            .map_err(check_error)?;
+
+        let loaded_pots = loaded_pots
+            .into_iter()
+            .map(|(pot, users)| (pot, users, PotBalance::new(vec![], None)))
+            .collect();
+
+        Ok((
+            StatusCode::OK,
+            Json(PotDTO::from_vec(loaded_pots, CurrencyDTO::from_vec(all_currencies))),
+        ))
+    }
+
+    /// Gets all pot templates of all users.
+    #[utoipa::path(
+        get,
+        path = "/admin/templates",
+        tag = "Admin",
+        responses(
+            (status = 200, description = "All pot templates", body = Vec<PotTemplateDTO>),
+            (status = 403, description = "The user is not an admin"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn get_all_templates(
+        State(admin_api_state): State<Arc<AdminApiState>>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<Vec<PotTemplateDTO>>, ApiResponse<String>> {
+        auth_user.require_admin()?;
+
+        let result = admin_api_state
+            .pot_template_service
+            .get_all_templates()
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(result.into_iter().map(PotTemplateDTO::from).collect()),
+        ))
+    }
+
+    /// Makes the given user the owner of the pot. The user is added to the pot, if they are not
+    /// part of it yet.
+    #[utoipa::path(
+        put,
+        path = "/admin/pots/{pot_id}/owner",
+        tag = "Admin",
+        params(
+            ("pot_id" = i32, Path, description = "The id of the pot")
+        ),
+        request_body = NewOwnerDTO,
+        responses(
+            (status = 200, description = "The pot with its new owner", body = PotDTO),
+            (status = 403, description = "The user is not an admin"),
+            (status = 404, description = "The pot or the new owner could not be found"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn reassign_pot_owner(
+        State(admin_api_state): State<Arc<AdminApiState>>,
+        auth_user: AuthUser,
+        Path(pot_id): Path<i32>,
+        Json(new_owner): Json<NewOwnerDTO>,
+    ) -> Result<ApiResponse<PotDTO>, ApiResponse<String>> {
+        auth_user.require_admin()?;
+
+        let (pot, users) = admin_api_state
+            .pot_service
+            .reassign_owner(pot_id, new_owner.owner_id)
+            .await
+            .map_err(check_error)?;
+
+        let currency = admin_api_state
+            .currency_service
+            .get_currency_by_id(pot.default_currency_id())
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(PotDTO::from(
+                pot,
+                CurrencyDTO::from(currency),
+                UserDTO::from_vec(users),
+                PotBalance::new(vec![], None),
+            )),
+        ))
+    }
+
+    /// Makes the given user the owner of the pot template. The user is added to the users of the
+    /// template, if they are not part of it yet. Pots created from the template afterwards are
+    /// owned by the new owner.
+    #[utoipa::path(
+        put,
+        path = "/admin/templates/{template_id}/owner",
+        tag = "Admin",
+        params(
+            ("template_id" = i32, Path, description = "The id of the pot template")
+        ),
+        request_body = NewOwnerDTO,
+        responses(
+            (status = 200, description = "The pot template with its new owner", body = PotTemplateDTO),
+            (status = 403, description = "The user is not an admin"),
+            (status = 404, description = "The pot template or the new owner could not be found"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn reassign_template_owner(
+        State(admin_api_state): State<Arc<AdminApiState>>,
+        auth_user: AuthUser,
+        Path(template_id): Path<i32>,
+        Json(new_owner): Json<NewOwnerDTO>,
+    ) -> Result<ApiResponse<PotTemplateDTO>, ApiResponse<String>> {
+        auth_user.require_admin()?;
+
+        let result = admin_api_state
+            .pot_template_service
+            .reassign_owner(template_id, new_owner.owner_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(PotTemplateDTO::from(result))))
+    }
+
+    /// Deletes the given user and removes them from all pots and templates. Users that still own
+    /// pots, templates or expenses, or that are part of expenses or settlements, can't be
+    /// deleted. Reassign their pots and templates first.
+    #[utoipa::path(
+        delete,
+        path = "/admin/users/{user_id}",
+        tag = "Admin",
+        params(
+            ("user_id" = Uuid, Path, description = "The id of the user")
+        ),
+        responses(
+            (status = 204, description = "The user has been deleted"),
+            (status = 403, description = "The user is not an admin"),
+            (status = 404, description = "The user could not be found"),
+            (status = 409, description = "The user still owns pots, templates or expenses, or is part of expenses or settlements"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn delete_user(
+        State(admin_api_state): State<Arc<AdminApiState>>,
+        auth_user: AuthUser,
+        Path(user_id): Path<Uuid>,
+    ) -> Result<ApiResponse<String>, ApiResponse<String>> {
+        auth_user.require_admin()?;
+
+        admin_api_state
+            .user_service
+            .delete_user(user_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::NO_CONTENT,
+            Json(format!("User with id {} has been deleted.", user_id))
+        ))
+    }
+}
@@ -0,0 +1,351 @@
+pub mod api_token_api {
+    use crate::api::{check_error, ApiResponse};
+    use crate::auth::auth::{AuthUser, ClaimNames};
+    use axum::extract::{Path, State};
+    use axum::http::{Method, StatusCode};
+    use axum::Json;
+    use chrono::{DateTime, Utc};
+    use expense_tracker_db::api_tokens::api_tokens::ApiToken;
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_services::api_token_service::api_token_service;
+    use expense_tracker_services::api_token_service::api_token_service::{
+        ApiTokenService, API_TOKEN_PREFIX,
+    };
+    use serde::{Deserialize, Serialize};
+    use std::sync::Arc;
+    use utoipa::r#gen::serde_json::Value;
+    use utoipa::ToSchema;
+    use utoipa_axum::router::OpenApiRouter;
+    use utoipa_axum::routes;
+
+    /// Holds the App State for the ApiTokenAPI.
+    pub struct ApiTokenApiState {
+        api_token_service: ApiTokenService,
+    }
+
+    /// Registers all functions of the API Token API.
+    pub fn register(pool: DbPool) -> OpenApiRouter {
+        let shared_state = Arc::new(ApiTokenApiState {
+            api_token_service: api_token_service::new_service(pool),
+        });
+
+        OpenApiRouter::new()
+            .routes(routes!(get_api_tokens, create_api_token))
+            .routes(routes!(revoke_api_token))
+            .with_state(shared_state)
+    }
+
+    /// What an API token can be used for.
+    #[derive(ToSchema, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ApiTokenScope {
+        /// Allows all requests reading data, e.g. getting pots and expenses.
+        Read,
+        /// Allows adding expenses to pots.
+        AddExpense,
+        /// Allows everything the user can do, except managing API tokens.
+        Full,
+    }
+
+    impl ApiTokenScope {
+        /// The name the scope is stored with.
+        fn as_str(&self) -> &'static str {
+            match self {
+                ApiTokenScope::Read => "read",
+                ApiTokenScope::AddExpense => "add_expense",
+                ApiTokenScope::Full => "full",
+            }
+        }
+
+        /// Gets the scope stored with the given name.
+        fn parse(name: &str) -> Option<Self> {
+            [ApiTokenScope::Read, ApiTokenScope::AddExpense, ApiTokenScope::Full]
+                .into_iter()
+                .find(|scope| scope.as_str() == name)
+        }
+
+        /// Checks if a request with the given method to the given path is allowed by the scope.
+        /// API tokens can never be used to manage API tokens, so a leaked token can't be used to
+        /// create new ones.
+        pub(crate) fn allows(&self, method: &Method, path: &str) -> bool {
+            let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+            let route = segments
+                .iter()
+                .position(|segment| *segment == "v1")
+                .map(|version| &segments[version + 1..])
+                .unwrap_or_default();
+
+            if route.first() == Some(&"api_tokens") {
+                return false;
+            }
+
+            match self {
+                ApiTokenScope::Read => method == Method::GET || method == Method::HEAD,
+                ApiTokenScope::AddExpense => {
+                    method == Method::POST
+                        && matches!(route, ["pots", pot_id] if pot_id.parse::<i32>().is_ok())
+                }
+                ApiTokenScope::Full => true,
+            }
+        }
+    }
+
+    /// Authenticates requests made with API tokens instead of tokens of the OIDC provider.
+    #[derive(Clone)]
+    pub struct ApiTokenAuth {
+        api_token_service: ApiTokenService,
+        claim_names: ClaimNames,
+    }
+
+    impl ApiTokenAuth {
+        /// Constructor for ApiTokenAuth. The given claim names are the ones the `AuthUser` is
+        /// read from.
+        pub fn new(pool: DbPool, claim_names: ClaimNames) -> Self {
+            Self {
+                api_token_service: api_token_service::new_service(pool),
+                claim_names,
+            }
+        }
+
+        /// Checks if the given bearer token is an API token.
+        pub fn is_api_token(token: &str) -> bool {
+            token.starts_with(API_TOKEN_PREFIX)
+        }
+
+        /// Validates the given API token and checks if its scopes allow a request with the given
+        /// method to the given path. Returns the claims of the user of the token, which never
+        /// grant the admin role, or the status and message to reject the request with.
+        pub async fn authenticate(
+            &self,
+            token: &str,
+            method: &Method,
+            path: &str,
+        ) -> Result<Value, (StatusCode, String)> {
+            let api_token = self
+                .api_token_service
+                .resolve(token)
+                .await
+                .map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to check API token: {e}"),
+                    )
+                })?
+                .ok_or_else(|| {
+                    (
+                        StatusCode::UNAUTHORIZED,
+                        "Unauthorized, the API token is invalid or expired".to_string(),
+                    )
+                })?;
+
+            let is_allowed = api_token
+                .scopes()
+                .iter()
+                .filter_map(|scope| ApiTokenScope::parse(scope))
+                .any(|scope| scope.allows(method, path));
+
+            if !is_allowed {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    "The scopes of the API token don't allow this request".to_string(),
+                ));
+            }
+
+            Ok(self.claim_names.claims_of(api_token.user_id()))
+        }
+    }
+
+    /// DTO used when working with existing API tokens. The token itself is only shown once,
+    /// when it is created.
+    #[derive(ToSchema, Serialize)]
+    pub struct ApiTokenDTO {
+        id: i32,
+        name: String,
+        /// The start of the token, to tell the tokens apart.
+        #[schema(example = "et_3f9a1c2b")]
+        token_prefix: String,
+        scopes: Vec<ApiTokenScope>,
+        created_at: DateTime<Utc>,
+        /// The token can't be used anymore after this date. Null if it never expires.
+        expires_at: Option<DateTime<Utc>>,
+        last_used_at: Option<DateTime<Utc>>,
+    }
+
+    impl ApiTokenDTO {
+        /// Creates a new ApiTokenDTO from a db ApiToken.
+        pub fn from(api_token: ApiToken) -> Self {
+            Self {
+                id: api_token.id(),
+                name: api_token.name().to_string(),
+                token_prefix: api_token.token_prefix().to_string(),
+                scopes: api_token
+                    .scopes()
+                    .iter()
+                    .filter_map(|scope| ApiTokenScope::parse(scope))
+                    .collect(),
+                created_at: api_token.created_at(),
+                expires_at: api_token.expires_at(),
+                last_used_at: api_token.last_used_at(),
+            }
+        }
+    }
+
+    /// DTO used when creating a new API token.
+    #[derive(ToSchema, Deserialize)]
+    pub struct NewApiTokenDTO {
+        #[schema(max_length = 24)]
+        #[schema(example = "Home automation")]
+        name: String,
+        scopes: Vec<ApiTokenScope>,
+        /// The token can't be used anymore after this date. Never expires, if not set.
+        expires_at: Option<DateTime<Utc>>,
+    }
+
+    /// DTO returned when creating a new API token.
+    #[derive(ToSchema, Serialize)]
+    pub struct CreatedApiTokenDTO {
+        /// The token to send as bearer token. It is not stored and can't be shown again.
+        token: String,
+        api_token: ApiTokenDTO,
+    }
+
+    /// Gets the API tokens of the bearer.
+    #[utoipa::path(
+        get,
+        path = "/api_tokens",
+        tag = "API Tokens",
+        responses(
+            (status = 200, description = "The API tokens of the bearer", body = Vec<ApiTokenDTO>),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn get_api_tokens(
+        State(api_token_api_state): State<Arc<ApiTokenApiState>>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<Vec<ApiTokenDTO>>, ApiResponse<String>> {
+        let api_tokens = api_token_api_state
+            .api_token_service
+            .get_tokens(auth_user.id())
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(api_tokens.into_iter().map(ApiTokenDTO::from).collect()),
+        ))
+    }
+
+    /// Creates a new API token for the bearer, e.g. for scripts and integrations. Requests made
+    /// with it are made on behalf of the bearer, limited to the given scopes.
+    #[utoipa::path(
+        post,
+        path = "/api_tokens",
+        tag = "API Tokens",
+        request_body = NewApiTokenDTO,
+        responses(
+            (status = 201, description = "The created API token", body = CreatedApiTokenDTO),
+            (status = 400, description = "The name is invalid, no scopes are given or the expiry is in the past"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn create_api_token(
+        State(api_token_api_state): State<Arc<ApiTokenApiState>>,
+        auth_user: AuthUser,
+        Json(new_api_token): Json<NewApiTokenDTO>,
+    ) -> Result<ApiResponse<CreatedApiTokenDTO>, ApiResponse<String>> {
+        let scopes = new_api_token
+            .scopes
+            .iter()
+            .map(|scope| scope.as_str().to_string())
+            .collect();
+
+        let (api_token, token) = api_token_api_state
+            .api_token_service
+            .create_token(
+                auth_user.id(),
+                &new_api_token.name,
+                scopes,
+                new_api_token.expires_at,
+            )
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::CREATED,
+            Json(CreatedApiTokenDTO {
+                token,
+                api_token: ApiTokenDTO::from(api_token),
+            }),
+        ))
+    }
+
+    /// Revokes the given API token of the bearer, so it can't be used anymore.
+    #[utoipa::path(
+        delete,
+        path = "/api_tokens/{token_id}",
+        tag = "API Tokens",
+        params(
+            ("token_id" = i32, Path, description = "The id of the API token")
+        ),
+        responses(
+            (status = 204, description = "The API token has been revoked"),
+            (status = 404, description = "The bearer has no API token with the given id"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn revoke_api_token(
+        State(api_token_api_state): State<Arc<ApiTokenApiState>>,
+        auth_user: AuthUser,
+        Path(token_id): Path<i32>,
+    ) -> Result<ApiResponse<String>, ApiResponse<String>> {
+        api_token_api_state
+            .api_token_service
+            .revoke_token(auth_user.id(), token_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::NO_CONTENT,
+            Json(format!("API token with id {} has been revoked.", token_id)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api_token_api::api_token_api::ApiTokenScope;
+    use axum::http::Method;
+
+    #[test]
+    fn test_scope_allows() {
+        let read = ApiTokenScope::Read;
+        assert!(read.allows(&Method::GET, "/api/v1/pots/1"));
+        assert!(!read.allows(&Method::POST, "/api/v1/pots/1"));
+
+        let add_expense = ApiTokenScope::AddExpense;
+        assert!(add_expense.allows(&Method::POST, "/api/v1/pots/1"));
+        assert!(!add_expense.allows(&Method::POST, "/api/v1/pots"));
+        assert!(!add_expense.allows(&Method::POST, "/api/v1/pots/1/settlements"));
+        assert!(!add_expense.allows(&Method::PUT, "/api/v1/pots/1"));
+        assert!(!add_expense.allows(&Method::GET, "/api/v1/pots/1"));
+
+        let full = ApiTokenScope::Full;
+        assert!(full.allows(&Method::DELETE, "/api/v1/pots/1"));
+
+        // API tokens can't manage API tokens
+        for scope in [read, add_expense, full] {
+            assert!(!scope.allows(&Method::GET, "/api/v1/api_tokens"));
+            assert!(!scope.allows(&Method::POST, "/api/v1/api_tokens"));
+            assert!(!scope.allows(&Method::DELETE, "/api/v1/api_tokens/1"));
+        }
+    }
+}
@@ -0,0 +1,456 @@
+pub mod auth {
+    use crate::api::ApiResponse;
+    use axum::extract::FromRequestParts;
+    use axum::http::request::Parts;
+    use axum::http::StatusCode;
+    use axum::Json;
+    use expense_tracker_db::users::users::UserProfile;
+    use jsonwebtoken::{
+        decode, encode, get_current_timestamp, Algorithm, DecodingKey, EncodingKey, Header,
+        Validation,
+    };
+    use serde::Serialize;
+    use utoipa::r#gen::serde_json::Value;
+    use uuid::Uuid;
+
+    const DEFAULT_USER_ID_CLAIM: &str = "sub";
+    const DEFAULT_USER_NAME_CLAIM: &str = "preferred_username";
+    const DEFAULT_USER_EMAIL_CLAIM: &str = "email";
+    const DEFAULT_USER_AVATAR_CLAIM: &str = "picture";
+    const DEFAULT_ROLES_CLAIM: &str = "realm_access.roles";
+    const DEFAULT_ADMIN_ROLE: &str = "admin";
+
+    /// The names of the claims of a token the `AuthUser` is built from.
+    #[derive(Clone)]
+    pub struct ClaimNames {
+        /// The claim holding the id of the user, which must be a UUID.
+        user_id: String,
+        /// The claim holding the name of the user, e.g. `email` or `name`.
+        user_name: String,
+        /// The claim holding the email address of the user.
+        user_email: String,
+        /// The claim holding the url of the avatar of the user.
+        user_avatar: String,
+        /// The path of the claim holding the roles of the user, with nested claims separated by
+        /// dots, e.g. `realm_access.roles` or `groups`.
+        roles: String,
+        /// The role granting admin rights.
+        admin_role: String,
+    }
+
+    impl ClaimNames {
+        /// Constructor for ClaimNames.
+        pub fn new(
+            user_id: String,
+            user_name: String,
+            user_email: String,
+            user_avatar: String,
+            roles: String,
+            admin_role: String,
+        ) -> Self {
+            Self {
+                user_id,
+                user_name,
+                user_email,
+                user_avatar,
+                roles,
+                admin_role,
+            }
+        }
+    }
+
+    impl ClaimNames {
+        /// Creates claims only holding the given user id, as if a token of the user had them.
+        pub(crate) fn claims_of(&self, user_id: Uuid) -> Value {
+            let mut claims = utoipa::r#gen::serde_json::Map::new();
+            claims.insert(self.user_id.clone(), Value::String(user_id.to_string()));
+            Value::Object(claims)
+        }
+    }
+
+    impl Default for ClaimNames {
+        fn default() -> Self {
+            Self::new(
+                DEFAULT_USER_ID_CLAIM.to_string(),
+                DEFAULT_USER_NAME_CLAIM.to_string(),
+                DEFAULT_USER_EMAIL_CLAIM.to_string(),
+                DEFAULT_USER_AVATAR_CLAIM.to_string(),
+                DEFAULT_ROLES_CLAIM.to_string(),
+                DEFAULT_ADMIN_ROLE.to_string(),
+            )
+        }
+    }
+
+    /// The claims of a validated token, which have been put into the request by the auth
+    /// middleware.
+    struct Claims {
+        user_id: Option<String>,
+        user_name: Option<String>,
+        user_email: Option<String>,
+        user_avatar: Option<String>,
+        roles: Vec<String>,
+    }
+
+    impl Claims {
+        /// Reads the claims with the given names from the given claims of a token.
+        fn from(claims: &Value, names: &ClaimNames) -> Self {
+            let get = |name: &str| claims.get(name).and_then(Value::as_str).map(str::to_string);
+
+            let roles = names
+                .roles
+                .split('.')
+                .try_fold(claims, |claim, name| claim.get(name));
+
+            // a single role may be given as a string instead of an array
+            let roles = match roles {
+                Some(Value::Array(roles)) => roles
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect(),
+                Some(Value::String(role)) => vec![role.clone()],
+                _ => vec![],
+            };
+
+            Self {
+                user_id: get(&names.user_id),
+                user_name: get(&names.user_name),
+                user_email: get(&names.user_email),
+                user_avatar: get(&names.user_avatar),
+                roles,
+            }
+        }
+    }
+
+    /// The user a request has been made by, as given by the bearer token. Rejects requests
+    /// without a valid user id with 401.
+    pub struct AuthUser {
+        id: Uuid,
+        name: Option<String>,
+        email: Option<String>,
+        avatar_url: Option<String>,
+        is_admin: bool,
+    }
+
+    impl AuthUser {
+        /// Getter for id.
+        pub fn id(&self) -> Uuid {
+            self.id
+        }
+
+        /// The display name of the user, if the token contains it.
+        pub fn name(&self) -> Option<&str> {
+            self.name.as_deref()
+        }
+
+        /// The email address of the user, if the token contains it.
+        pub fn email(&self) -> Option<&str> {
+            self.email.as_deref()
+        }
+
+        /// The url of the avatar of the user, if the token contains it.
+        pub fn avatar_url(&self) -> Option<&str> {
+            self.avatar_url.as_deref()
+        }
+
+        /// The profile of the user as given by the token.
+        pub fn profile(&self) -> UserProfile {
+            UserProfile::new(self.name.clone(), self.email.clone(), self.avatar_url.clone())
+        }
+
+        /// Whether the token grants the admin role.
+        pub fn is_admin(&self) -> bool {
+            self.is_admin
+        }
+
+        /// Returns 403, unless the user is an admin.
+        pub fn require_admin(&self) -> Result<(), ApiResponse<String>> {
+            if self.is_admin {
+                Ok(())
+            } else {
+                Err((
+                    StatusCode::FORBIDDEN,
+                    Json("Only admins are allowed to do this".to_string()),
+                ))
+            }
+        }
+
+        /// Builds the user from the given claims of a token.
+        pub(crate) fn from_claims(
+            claims: &Value,
+            names: &ClaimNames,
+        ) -> Result<Self, ApiResponse<String>> {
+            let claims = Claims::from(claims, names);
+
+            let user_id = claims.user_id.ok_or_else(|| {
+                unauthorized(format!("the token has no {} claim", names.user_id))
+            })?;
+
+            let id = Uuid::parse_str(&user_id).map_err(|_| {
+                unauthorized(format!("the {} claim of the token is not a UUID", names.user_id))
+            })?;
+
+            Ok(Self {
+                id,
+                name: claims.user_name,
+                email: claims.user_email,
+                avatar_url: claims.user_avatar,
+                is_admin: claims.roles.contains(&names.admin_role),
+            })
+        }
+    }
+
+    impl<S> FromRequestParts<S> for AuthUser
+    where
+        S: Send + Sync,
+    {
+        type Rejection = ApiResponse<String>;
+
+        async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+            let claims = parts
+                .extensions
+                .get::<Value>()
+                .ok_or_else(|| unauthorized("the request has no valid token".to_string()))?;
+
+            let default_names = ClaimNames::default();
+            let names = parts.extensions.get::<ClaimNames>().unwrap_or(&default_names);
+
+            Self::from_claims(claims, names)
+        }
+    }
+
+    /// The issuer and audience of the tokens issued by the built-in authentication.
+    const LOCAL_ISSUER: &str = "expense-tracker";
+    /// The claim holding the roles of a user in the tokens issued by the built-in authentication.
+    const LOCAL_ROLES_CLAIM: &str = "roles";
+    /// The claim telling what a token issued by the built-in authentication can be used for.
+    const TOKEN_USE_CLAIM: &str = "token_use";
+
+    /// What a token issued by the built-in authentication can be used for.
+    #[derive(Serialize, Clone, Copy)]
+    #[serde(rename_all = "snake_case")]
+    pub enum TokenUse {
+        /// Authorizes requests to the API.
+        Access,
+        /// Gets new tokens, once the access token expired.
+        Refresh,
+    }
+
+    /// The claims of a token issued by the built-in authentication.
+    #[derive(Serialize)]
+    struct LocalClaims<'a> {
+        iss: &'a str,
+        aud: &'a str,
+        sub: Uuid,
+        preferred_username: &'a str,
+        roles: Vec<&'a str>,
+        token_use: TokenUse,
+        iat: u64,
+        exp: u64,
+    }
+
+    /// Issues and validates the tokens of the built-in authentication, which is used instead of
+    /// an OIDC provider. The tokens are signed with HS256 using a secret shared by all
+    /// instances of the API.
+    #[derive(Clone)]
+    pub struct LocalTokens {
+        encoding_key: EncodingKey,
+        decoding_key: DecodingKey,
+        /// The number of seconds access tokens are valid for.
+        access_token_lifetime: u64,
+        /// The number of seconds refresh tokens are valid for.
+        refresh_token_lifetime: u64,
+    }
+
+    impl LocalTokens {
+        /// Constructor for LocalTokens.
+        pub fn new(secret: &[u8], access_token_lifetime: u64, refresh_token_lifetime: u64) -> Self {
+            Self {
+                encoding_key: EncodingKey::from_secret(secret),
+                decoding_key: DecodingKey::from_secret(secret),
+                access_token_lifetime,
+                refresh_token_lifetime,
+            }
+        }
+
+        /// The names of the claims of the issued tokens.
+        pub fn claim_names() -> ClaimNames {
+            ClaimNames {
+                roles: LOCAL_ROLES_CLAIM.to_string(),
+                ..ClaimNames::default()
+            }
+        }
+
+        /// Getter for access_token_lifetime.
+        pub fn access_token_lifetime(&self) -> u64 {
+            self.access_token_lifetime
+        }
+
+        /// Issues a token for the given use to the given user.
+        pub(crate) fn issue(
+            &self,
+            token_use: TokenUse,
+            user_id: Uuid,
+            user_name: &str,
+            is_admin: bool,
+        ) -> Result<String, String> {
+            let lifetime = match token_use {
+                TokenUse::Access => self.access_token_lifetime,
+                TokenUse::Refresh => self.refresh_token_lifetime,
+            };
+            let now = get_current_timestamp();
+
+            let claims = LocalClaims {
+                iss: LOCAL_ISSUER,
+                aud: LOCAL_ISSUER,
+                sub: user_id,
+                preferred_username: user_name,
+                roles: if is_admin { vec![DEFAULT_ADMIN_ROLE] } else { vec![] },
+                token_use,
+                iat: now,
+                exp: now + lifetime,
+            };
+
+            encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+                .map_err(|e| format!("Failed to sign token: {e}"))
+        }
+
+        /// Validates the given token and returns its claims, if it has been issued by the
+        /// built-in authentication for the given use.
+        pub fn validate(&self, token: &str, token_use: TokenUse) -> Result<Value, String> {
+            let mut validation = Validation::new(Algorithm::HS256);
+            validation.set_audience(&[LOCAL_ISSUER]);
+            validation.set_issuer(&[LOCAL_ISSUER]);
+
+            let claims = decode::<Value>(token, &self.decoding_key, &validation)
+                .map_err(|e| format!("Failed to decode token: {e}"))?
+                .claims;
+
+            let expected_use = utoipa::r#gen::serde_json::to_value(token_use)
+                .map_err(|e| format!("Failed to check token use: {e}"))?;
+
+            if claims.get(TOKEN_USE_CLAIM) != Some(&expected_use) {
+                return Err(format!("The token is not a valid {} token", expected_use));
+            }
+
+            Ok(claims)
+        }
+    }
+
+    /// Creates a 401 response with the given message.
+    pub(crate) fn unauthorized(message: String) -> ApiResponse<String> {
+        (StatusCode::UNAUTHORIZED, Json(format!("Unauthorized, {}", message)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::auth::{AuthUser, ClaimNames, LocalTokens, TokenUse};
+    use axum::http::StatusCode;
+    use utoipa::r#gen::serde_json::json;
+    use uuid::uuid;
+
+    #[test]
+    fn test_from_claims() {
+        let claims = json!({
+            "sub": "e6be621a-ec2d-48f3-8027-0d34cf5cbe40",
+            "preferred_username": "alice",
+            "email": "alice@example.com",
+            "picture": "https://example.com/alice.png",
+        });
+
+        let user = AuthUser::from_claims(&claims, &ClaimNames::default()).unwrap();
+        assert_eq!(user.id(), uuid!("e6be621a-ec2d-48f3-8027-0d34cf5cbe40"));
+        assert_eq!(user.name(), Some("alice"));
+        assert_eq!(user.email(), Some("alice@example.com"));
+        assert_eq!(user.avatar_url(), Some("https://example.com/alice.png"));
+
+        let names = ClaimNames::new(
+            "sub".to_string(),
+            "email".to_string(),
+            "mail".to_string(),
+            "avatar".to_string(),
+            "groups".to_string(),
+            "admin".to_string(),
+        );
+        let user = AuthUser::from_claims(&claims, &names).unwrap();
+        assert_eq!(user.name(), Some("alice@example.com"));
+        assert_eq!(user.email(), None);
+        assert_eq!(user.avatar_url(), None);
+        assert!(!user.is_admin());
+    }
+
+    #[test]
+    fn test_from_claims_roles() {
+        let user_id = "e6be621a-ec2d-48f3-8027-0d34cf5cbe40";
+        let is_admin = |claims, roles: &str| {
+            let names = ClaimNames::new(
+                "sub".to_string(),
+                "preferred_username".to_string(),
+                "email".to_string(),
+                "picture".to_string(),
+                roles.to_string(),
+                "admin".to_string(),
+            );
+            AuthUser::from_claims(&claims, &names).unwrap().is_admin()
+        };
+
+        let keycloak = json!({ "sub": user_id, "realm_access": { "roles": ["user", "admin"] } });
+        let groups = json!({ "sub": user_id, "groups": ["admin"] });
+        let single = json!({ "sub": user_id, "groups": "admin" });
+        let other_role = json!({ "sub": user_id, "groups": ["administrators"] });
+
+        assert!(is_admin(keycloak.clone(), "realm_access.roles"));
+        assert!(!is_admin(keycloak, "groups"));
+        assert!(is_admin(groups, "groups"));
+        assert!(is_admin(single, "groups"));
+        assert!(!is_admin(other_role, "groups"));
+    }
+
+    #[test]
+    fn test_local_tokens() {
+        let tokens = LocalTokens::new(b"0123456789abcdef0123456789abcdef", 60, 3600);
+        let user_id = uuid!("e6be621a-ec2d-48f3-8027-0d34cf5cbe40");
+
+        let access = tokens.issue(TokenUse::Access, user_id, "alice", true).unwrap();
+        let refresh = tokens.issue(TokenUse::Refresh, user_id, "alice", false).unwrap();
+
+        let claims = tokens.validate(&access, TokenUse::Access).unwrap();
+        let user = AuthUser::from_claims(&claims, &LocalTokens::claim_names()).unwrap();
+        assert_eq!(user.id(), user_id);
+        assert_eq!(user.name(), Some("alice"));
+        assert!(user.is_admin());
+
+        let claims = tokens.validate(&refresh, TokenUse::Refresh).unwrap();
+        let user = AuthUser::from_claims(&claims, &LocalTokens::claim_names()).unwrap();
+        assert!(!user.is_admin());
+
+        // tokens can only be used for what they have been issued for
+        assert!(tokens.validate(&refresh, TokenUse::Access).is_err());
+        assert!(tokens.validate(&access, TokenUse::Refresh).is_err());
+
+        // tokens signed with another secret are rejected
+        let other = LocalTokens::new(b"fedcba9876543210fedcba9876543210", 60, 3600);
+        assert!(other.validate(&access, TokenUse::Access).is_err());
+    }
+
+    #[test]
+    fn test_from_claims_without_user_name() {
+        let claims = json!({ "sub": "e6be621a-ec2d-48f3-8027-0d34cf5cbe40" });
+
+        let user = AuthUser::from_claims(&claims, &ClaimNames::default()).unwrap();
+        assert_eq!(user.name(), None);
+    }
+
+    #[test]
+    fn test_from_claims_invalid_user_id() {
+        let missing = json!({ "preferred_username": "alice" });
+        let not_a_uuid = json!({ "sub": "alice" });
+        let not_a_string = json!({ "sub": 42 });
+
+        for claims in [missing, not_a_uuid, not_a_string] {
+            let result = AuthUser::from_claims(&claims, &ClaimNames::default());
+            assert!(matches!(result, Err((StatusCode::UNAUTHORIZED, _))));
+        }
+    }
+}
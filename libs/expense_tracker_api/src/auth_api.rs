@@ -0,0 +1,157 @@
+pub mod auth_api {
+    use crate::api::{check_error, ApiResponse};
+    use crate::auth::auth::{unauthorized, LocalTokens, TokenUse};
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::Json;
+    use expense_tracker_db::local_accounts::local_accounts::LocalAccount;
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::users::users::User;
+    use expense_tracker_services::local_account_service::local_account_service;
+    use expense_tracker_services::local_account_service::local_account_service::LocalAccountService;
+    use serde::{Deserialize, Serialize};
+    use std::sync::Arc;
+    use utoipa::ToSchema;
+    use utoipa_axum::router::OpenApiRouter;
+    use utoipa_axum::routes;
+
+    /// Holds the App State for the AuthAPI.
+    pub struct AuthApiState {
+        local_account_service: LocalAccountService,
+        local_tokens: LocalTokens,
+    }
+
+    /// Registers all functions of the Auth API, which issues the tokens of the built-in
+    /// authentication with the given `local_tokens`.
+    pub fn register(pool: DbPool, local_tokens: LocalTokens) -> OpenApiRouter {
+        let shared_state = Arc::new(AuthApiState {
+            local_account_service: local_account_service::new_service(pool),
+            local_tokens,
+        });
+
+        OpenApiRouter::new()
+            .routes(routes!(login))
+            .routes(routes!(refresh))
+            .with_state(shared_state)
+    }
+
+    /// DTO used to log in with a local account.
+    #[derive(ToSchema, Deserialize)]
+    pub struct LoginDTO {
+        username: String,
+        password: String,
+    }
+
+    /// DTO used to get new tokens with a refresh token.
+    #[derive(ToSchema, Deserialize)]
+    pub struct RefreshDTO {
+        refresh_token: String,
+    }
+
+    /// DTO holding the tokens issued to a user.
+    #[derive(ToSchema, Serialize)]
+    pub struct TokenDTO {
+        /// The token to send as bearer token with every request to the API.
+        access_token: String,
+        /// The token used to get new tokens, once the access token expired.
+        refresh_token: String,
+        #[schema(example = "Bearer")]
+        token_type: String,
+        /// The number of seconds the access token is valid for.
+        expires_in: u64,
+    }
+
+    impl TokenDTO {
+        /// Issues new tokens to the given user.
+        fn issue(
+            local_tokens: &LocalTokens,
+            account: &LocalAccount,
+            user: &User,
+        ) -> Result<Self, ApiResponse<String>> {
+            let issue = |token_use| {
+                local_tokens
+                    .issue(token_use, user.id(), user.provider_name(), account.is_admin())
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(e)))
+            };
+
+            Ok(Self {
+                access_token: issue(TokenUse::Access)?,
+                refresh_token: issue(TokenUse::Refresh)?,
+                token_type: "Bearer".to_string(),
+                expires_in: local_tokens.access_token_lifetime(),
+            })
+        }
+    }
+
+    /// Logs in with the username and password of a local account. Only available if the
+    /// built-in authentication is used instead of an OIDC provider.
+    #[utoipa::path(
+        post,
+        path = "/auth/login",
+        tag = "Auth",
+        request_body = LoginDTO,
+        responses(
+            (status = 200, description = "The tokens of the user", body = TokenDTO),
+            (status = 401, description = "The username or password is wrong"),
+            (status = 500, description = "Internal server error")
+        )
+    )]
+    pub async fn login(
+        State(auth_api_state): State<Arc<AuthApiState>>,
+        Json(login): Json<LoginDTO>,
+    ) -> Result<ApiResponse<TokenDTO>, ApiResponse<String>> {
+        let (account, user) = auth_api_state
+            .local_account_service
+            .authenticate(&login.username, &login.password)
+            .await
+            .map_err(check_error)?
+            .ok_or_else(|| unauthorized("wrong username or password".to_string()))?;
+
+        Ok((
+            StatusCode::OK,
+            Json(TokenDTO::issue(&auth_api_state.local_tokens, &account, &user)?),
+        ))
+    }
+
+    /// Gets new tokens with a refresh token. The refresh token is only accepted as long as the
+    /// account of the user exists.
+    #[utoipa::path(
+        post,
+        path = "/auth/refresh",
+        tag = "Auth",
+        request_body = RefreshDTO,
+        responses(
+            (status = 200, description = "The new tokens of the user", body = TokenDTO),
+            (status = 401, description = "The refresh token is invalid or expired, or the account does not exist anymore"),
+            (status = 500, description = "Internal server error")
+        )
+    )]
+    pub async fn refresh(
+        State(auth_api_state): State<Arc<AuthApiState>>,
+        Json(refresh): Json<RefreshDTO>,
+    ) -> Result<ApiResponse<TokenDTO>, ApiResponse<String>> {
+        let claims = auth_api_state
+            .local_tokens
+            .validate(&refresh.refresh_token, TokenUse::Refresh)
+            .map_err(unauthorized)?;
+
+        let user_id = claims
+            .get("sub")
+            .and_then(|sub| sub.as_str())
+            .and_then(|sub| sub.parse().ok())
+            .ok_or_else(|| unauthorized("the refresh token has no valid user id".to_string()))?;
+
+        // the name and roles are read again, so changes apply with the next refresh
+        let (account, user) = auth_api_state
+            .local_account_service
+            .get_account(user_id)
+            .await
+            .map_err(check_error)?
+            .ok_or_else(|| unauthorized("the account does not exist anymore".to_string()))?;
+
+        Ok((
+            StatusCode::OK,
+            Json(TokenDTO::issue(&auth_api_state.local_tokens, &account, &user)?),
+        ))
+    }
+}
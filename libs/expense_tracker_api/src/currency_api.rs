@@ -1,14 +1,18 @@
 pub mod currency_api {
     use crate::api::{check_error, ApiResponse};
-    use axum::extract::State;
+    use crate::auth::auth::AuthUser;
+    use axum::extract::{Query, State};
     use axum::http::StatusCode;
     use axum::Json;
+    use chrono::{NaiveDate, Utc};
     use expense_tracker_db::currencies::currencies::{Currency, NewCurrency};
+    use expense_tracker_db::exchange_rates::exchange_rates::{ExchangeRate, NewExchangeRate};
     use expense_tracker_db::setup::DbPool;
     use expense_tracker_services::currency_service::currency_service;
     use expense_tracker_services::currency_service::currency_service::CurrencyService;
+    use expense_tracker_services::money::money::{from_minor_units, to_minor_units};
     use serde::{Deserialize, Serialize};
-    use utoipa::ToSchema;
+    use utoipa::{IntoParams, ToSchema};
     use utoipa_axum::router::OpenApiRouter;
     use utoipa_axum::routes;
 
@@ -17,15 +21,25 @@ pub mod currency_api {
         OpenApiRouter::new()
             .routes(routes!(create_currency))
             .routes(routes!(get_currencies))
+            .routes(routes!(create_exchange_rate, get_exchange_rates))
+            .routes(routes!(import_ecb_rates))
+            .routes(routes!(convert))
             .with_state(currency_service::new_service(pool))
     }
 
+    /// The minor units of a currency, if none are given on creation.
+    const DEFAULT_MINOR_UNITS: i32 = 2;
+
     /// DTO representing a currency.
     #[derive(ToSchema, Serialize)]
     pub struct CurrencyDTO {
         id: i32,
         name: String,
         symbol: String,
+        /// The number of digits after the decimal separator, e.g. 2 for cents.
+        minor_units: i32,
+        /// The ISO 4217 code of the currency, e.g. EUR. Used to match imported exchange rates.
+        code: Option<String>,
     }
 
     impl Clone for CurrencyDTO {
@@ -34,6 +48,8 @@ pub mod currency_api {
                 id: self.id,
                 name: self.name.clone(),
                 symbol: self.symbol.clone(),
+                minor_units: self.minor_units,
+                code: self.code.clone(),
             }
         }
     }
@@ -45,6 +61,8 @@ pub mod currency_api {
                 id: src.id(),
                 name: src.name().to_string(),
                 symbol: src.symbol().to_string(),
+                minor_units: src.minor_units(),
+                code: src.code().map(str::to_string),
             }
         }
 
@@ -62,6 +80,10 @@ pub mod currency_api {
         pub fn id(&self) -> i32 {
             self.id
         }
+
+        pub fn minor_units(&self) -> i32 {
+            self.minor_units
+        }
     }
 
     /// DTO representing a new currency.
@@ -69,23 +91,119 @@ pub mod currency_api {
     pub struct NewCurrencyDTO {
         name: String,
         symbol: String,
+        /// The number of digits after the decimal separator, e.g. 0 for JPY. Defaults to 2.
+        #[schema(minimum = 0, maximum = 4)]
+        minor_units: Option<i32>,
+        /// The ISO 4217 code of the currency, e.g. EUR. Needed to import ECB exchange rates.
+        #[schema(min_length = 3, max_length = 3)]
+        code: Option<String>,
     }
 
     impl NewCurrencyDTO {
         /// Converts the DTO to the respective db model.
         pub fn to_db(&self) -> NewCurrency {
-            NewCurrency::new(self.name.clone(), self.symbol.clone())
+            NewCurrency::new(
+                self.name.clone(),
+                self.symbol.clone(),
+                self.minor_units.unwrap_or(DEFAULT_MINOR_UNITS),
+                self.code.clone(),
+            )
+        }
+    }
+
+    /// DTO representing the exchange rate between two currencies on a given day.
+    #[derive(ToSchema, Serialize)]
+    pub struct ExchangeRateDTO {
+        id: i32,
+        base_currency_id: i32,
+        quote_currency_id: i32,
+        /// The value of one unit of the base currency in the quote currency.
+        rate: f64,
+        /// The day the rate is valid on. It is used until a more recent rate is known.
+        valid_on: NaiveDate,
+    }
+
+    impl ExchangeRateDTO {
+        /// Converts ExchangeRate to ExchangeRateDTO.
+        pub fn from(src: ExchangeRate) -> Self {
+            Self {
+                id: src.id(),
+                base_currency_id: src.base_currency_id(),
+                quote_currency_id: src.quote_currency_id(),
+                rate: src.rate(),
+                valid_on: src.valid_on(),
+            }
         }
+
+        /// Converts a Vec of ExchangeRate to Vec of ExchangeRateDTO.
+        pub fn from_vec(src: Vec<ExchangeRate>) -> Vec<Self> {
+            src.into_iter().map(ExchangeRateDTO::from).collect()
+        }
+    }
+
+    /// DTO representing a new exchange rate.
+    #[derive(ToSchema, Serialize, Deserialize)]
+    pub struct NewExchangeRateDTO {
+        base_currency_id: i32,
+        quote_currency_id: i32,
+        /// The value of one unit of the base currency in the quote currency.
+        rate: f64,
+        /// The day the rate is valid on. Defaults to today.
+        valid_on: Option<NaiveDate>,
+    }
+
+    impl NewExchangeRateDTO {
+        /// Converts the DTO to the respective db model.
+        pub fn to_db(&self) -> NewExchangeRate {
+            NewExchangeRate::new(
+                self.base_currency_id,
+                self.quote_currency_id,
+                self.rate,
+                self.valid_on.unwrap_or_else(|| Utc::now().date_naive()),
+            )
+        }
+    }
+
+    /// DTO describing the result of an exchange rate import.
+    #[derive(ToSchema, Serialize)]
+    pub struct ExchangeRateImportDTO {
+        /// The number of rates that have been created or updated.
+        imported: usize,
     }
 
-    /// Creates a new currency, if symbol is not yet in use.
+    /// The parameters of a conversion between two currencies.
+    #[derive(IntoParams, Deserialize)]
+    pub struct ConversionParams {
+        /// The id of the currency to convert from.
+        from: i32,
+        /// The id of the currency to convert to.
+        to: i32,
+        /// The amount to convert.
+        amount: f64,
+        /// The day whose rate is used. Defaults to today.
+        date: Option<NaiveDate>,
+    }
+
+    /// DTO describing the result of a conversion between two currencies.
+    #[derive(ToSchema, Serialize)]
+    pub struct ConversionDTO {
+        from: CurrencyDTO,
+        to: CurrencyDTO,
+        amount: f64,
+        converted_amount: f64,
+        date: NaiveDate,
+    }
+
+    /// Creates a new currency, if symbol is not yet in use. Only admins are allowed to do this.
     #[utoipa::path(
         post,
         path = "/currencies",
         tag  = "Currency",
         responses(
             (status = 201, description = "The currency has been created", body = NewCurrencyDTO),
-            (status = 409, description = "Detected a conflict, as the symbol is already known.")
+            (status = 400, description = "The minor units or the code of the currency are invalid."),
+            (status = 403, description = "The bearer is not an admin."),
+            (status = 409, description = "Detected a conflict, as the symbol or code is already known.")
         ),
         request_body = NewCurrencyDTO,
         security(
@@ -94,8 +212,11 @@ pub mod currency_api {
     )]
     pub async fn create_currency(
         State(service): State<CurrencyService>,
+        auth_user: AuthUser,
         Json(new_currency): Json<NewCurrencyDTO>,
     ) -> Result<ApiResponse<CurrencyDTO>, ApiResponse<String>> {
+        auth_user.require_admin()?;
+
         let res = service
             .create_currency(new_currency.to_db())
             .await
@@ -126,4 +247,138 @@ pub mod currency_api {
             Json(CurrencyDTO::from_vec(loaded_currencies)),
         ))
     }
+
+    /// Creates an exchange rate between two currencies. An existing rate of the same currencies
+    /// on the same day is replaced. Only admins are allowed to do this.
+    #[utoipa::path(
+        post,
+        path = "/currencies/exchange_rates",
+        tag = "Currency",
+        responses(
+            (status = 201, description = "The exchange rate has been created", body = ExchangeRateDTO),
+            (status = 400, description = "The rate is not positive or the currencies are the same."),
+            (status = 403, description = "The bearer is not an admin."),
+            (status = 404, description = "One of the currencies could not be found.")
+        ),
+        request_body = NewExchangeRateDTO,
+        security(
+                ("bearer" = [])
+        )
+    )]
+    pub async fn create_exchange_rate(
+        State(service): State<CurrencyService>,
+        auth_user: AuthUser,
+        Json(new_exchange_rate): Json<NewExchangeRateDTO>,
+    ) -> Result<ApiResponse<ExchangeRateDTO>, ApiResponse<String>> {
+        auth_user.require_admin()?;
+
+        let res = service
+            .create_exchange_rate(new_exchange_rate.to_db())
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::CREATED, Json(ExchangeRateDTO::from(res))))
+    }
+
+    /// Gets all known exchange rates, the most recent ones first.
+    #[utoipa::path(
+        get,
+        path = "/currencies/exchange_rates",
+        tag = "Currency",
+        responses(
+            (status = 200, description = "All exchange rates known to the system", body = Vec<ExchangeRateDTO>),
+        ),
+        security(
+                ("bearer" = [])
+        )
+    )]
+    pub async fn get_exchange_rates(
+        State(service): State<CurrencyService>,
+    ) -> Result<ApiResponse<Vec<ExchangeRateDTO>>, ApiResponse<String>> {
+        let loaded_rates = service.get_exchange_rates().await.map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(ExchangeRateDTO::from_vec(loaded_rates))))
+    }
+
+    /// Imports the reference rates of the European Central Bank from the given XML, e.g. the
+    /// daily or historic dumps from the ECB website. Rates are matched to the currencies by their
+    /// code, rates of unknown currencies are skipped. Only admins are allowed to do this.
+    #[utoipa::path(
+        post,
+        path = "/currencies/exchange_rates/ecb",
+        tag = "Currency",
+        responses(
+            (status = 200, description = "The exchange rates have been imported", body = ExchangeRateImportDTO),
+            (status = 400, description = "The XML is invalid or there is no currency with code EUR."),
+            (status = 403, description = "The bearer is not an admin.")
+        ),
+        request_body(content = String, content_type = "application/xml"),
+        security(
+                ("bearer" = [])
+        )
+    )]
+    pub async fn import_ecb_rates(
+        State(service): State<CurrencyService>,
+        auth_user: AuthUser,
+        xml: String,
+    ) -> Result<ApiResponse<ExchangeRateImportDTO>, ApiResponse<String>> {
+        auth_user.require_admin()?;
+
+        let imported = service.import_ecb_rates(&xml).await.map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(ExchangeRateImportDTO { imported })))
+    }
+
+    /// Converts the given amount from one currency into another, using the rate of the given
+    /// day. If there is no rate on that day, the latest rate before it is used.
+    #[utoipa::path(
+        get,
+        path = "/currencies/convert",
+        tag = "Currency",
+        params(ConversionParams),
+        responses(
+            (status = 200, description = "The converted amount", body = ConversionDTO),
+            (status = 404, description = "One of the currencies or an exchange rate between them could not be found.")
+        ),
+        security(
+                ("bearer" = [])
+        )
+    )]
+    pub async fn convert(
+        State(service): State<CurrencyService>,
+        Query(params): Query<ConversionParams>,
+    ) -> Result<ApiResponse<ConversionDTO>, ApiResponse<String>> {
+        let from = service
+            .get_currency_by_id(params.from)
+            .await
+            .map_err(check_error)?;
+
+        let to = service
+            .get_currency_by_id(params.to)
+            .await
+            .map_err(check_error)?;
+
+        let date = params.date.unwrap_or_else(|| Utc::now().date_naive());
+
+        let converted = service
+            .convert(
+                to_minor_units(params.amount, from.minor_units()),
+                &from,
+                &to,
+                date,
+            )
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(ConversionDTO {
+                amount: params.amount,
+                converted_amount: from_minor_units(converted, to.minor_units()),
+                from: CurrencyDTO::from(from),
+                to: CurrencyDTO::from(to),
+                date,
+            }),
+        ))
+    }
 }
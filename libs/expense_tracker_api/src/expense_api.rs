@@ -1,19 +1,22 @@
 pub mod expense_api {
-    use crate::api::{check_error, get_sub_claim, ApiResponse};
+    use crate::api::{check_error, ApiResponse};
+    use crate::auth::auth::AuthUser;
     use crate::currency_api::currency_api::CurrencyDTO;
     use axum::extract::{Path, State};
-    use axum::http::request::Parts;
     use axum::Json;
+    use chrono::{DateTime, Utc};
     use expense_tracker_db::currencies::currencies::Currency;
     use expense_tracker_db::expenses::expenses::{Expense, NewExpense};
     use expense_tracker_db::setup::DbPool;
-    use expense_tracker_db::splits::splits::{NewExpenseSplit, Split};
+    use expense_tracker_db::splits::splits::Split;
     use expense_tracker_services::expense_service;
     use expense_tracker_services::expense_service::expense_service::{
-        ExpenseService, JoinedExpense,
+        ExpenseService, ExpenseUpdate, JoinedExpense, SettledAmounts, SplitMode,
     };
+    use expense_tracker_services::money::money::from_minor_units;
     use hyper::StatusCode;
     use serde::{Deserialize, Serialize};
+    use std::sync::Arc;
     use utoipa::ToSchema;
     use utoipa_axum::router::OpenApiRouter;
     use utoipa_axum::routes;
@@ -29,9 +32,15 @@ pub mod expense_api {
         currency: CurrencyDTO,
         splits: Vec<SplitDTO>,
         /// If negative: you have to pay `owner_id` this amount of money, otherwise
-        /// you can expect others to pay you the given amount.
+        /// you can expect others to pay you the given amount. Settlements that have already
+        /// been made are taken into account.
         sum: f64,
-        total_amount: f64
+        /// The amount the owner has paid in total.
+        total_amount: f64,
+        /// The date the money has been spent.
+        spent_at: DateTime<Utc>,
+        created_at: DateTime<Utc>,
+        updated_at: DateTime<Utc>,
     }
 
     impl ExpenseDTO {
@@ -40,20 +49,38 @@ pub mod expense_api {
             currency: Currency,
             splits: Vec<Split>,
             requester_id: Uuid,
+            settled_amounts: &SettledAmounts,
         ) -> Self {
+            let sum = get_sum(expense.owner_id(), requester_id, &splits)
+                + get_settled_sum(
+                    expense.id(),
+                    expense.owner_id(),
+                    requester_id,
+                    &splits,
+                    settled_amounts,
+                );
+            let minor_units = currency.minor_units();
+
             Self {
                 id: expense.id(),
                 description: expense.description().to_string(),
                 pot_id: expense.pot_id(),
                 currency: CurrencyDTO::from(currency),
                 owner_id: expense.owner_id(),
-                splits: SplitDTO::from_vec_split(splits.clone()),
-                sum: get_sum(expense.owner_id(), requester_id, &splits),
-                total_amount: get_total_amount(&splits),
+                splits: SplitDTO::from_vec_split(splits.clone(), minor_units),
+                sum: from_minor_units(sum, minor_units),
+                total_amount: from_minor_units(expense.total_amount(), minor_units),
+                spent_at: expense.spent_at(),
+                created_at: expense.created_at(),
+                updated_at: expense.updated_at(),
             }
         }
 
-        pub fn from_vec(expenses: Vec<JoinedExpense>, requester_id: Uuid) -> Vec<Self> {
+        pub fn from_vec(
+            expenses: Vec<JoinedExpense>,
+            requester_id: Uuid,
+            settled_amounts: &SettledAmounts,
+        ) -> Vec<Self> {
             let mut dtos: Vec<ExpenseDTO> = vec![];
 
             for joined_expense in expenses {
@@ -61,7 +88,13 @@ pub mod expense_api {
                 let splits = joined_expense.1;
                 let currency = joined_expense.2;
 
-                dtos.push(ExpenseDTO::from(expense, currency, splits, requester_id))
+                dtos.push(ExpenseDTO::from(
+                    expense,
+                    currency,
+                    splits,
+                    requester_id,
+                    settled_amounts,
+                ))
             }
 
             dtos
@@ -69,9 +102,9 @@ pub mod expense_api {
     }
 
     /// Gets the sum the `viewer_id`'s user owes the `expense_owner_id`'s user for the given
-    /// `Vec<Split>`.
-    pub fn get_sum(expense_owner_id: Uuid, viewer_id: Uuid, splits: &Vec<Split>) -> f64 {
-        let mut sum = 0.0;
+    /// `Vec<Split>`, in minor units.
+    pub fn get_sum(expense_owner_id: Uuid, viewer_id: Uuid, splits: &Vec<Split>) -> i64 {
+        let mut sum = 0;
 
         for split in splits {
             if !split.is_paid() && split.user_id().ne(&expense_owner_id) {
@@ -87,15 +120,36 @@ pub mod expense_api {
         sum
     }
 
-    /// Gets the total amount of money that has been paid for the given `Vec<Split>`.
-    fn get_total_amount(splits: &Vec<Split>) -> f64 {
-        let mut total_amount = 0.0;
+    /// Gets the part of `get_sum` that has already been covered by settlements, which must be
+    /// added to it. It is negative for the `expense_owner_id`'s user and positive for the
+    /// debtors.
+    pub fn get_settled_sum(
+        expense_id: i32,
+        expense_owner_id: Uuid,
+        viewer_id: Uuid,
+        splits: &Vec<Split>,
+        settled_amounts: &SettledAmounts,
+    ) -> i64 {
+        let mut settled_sum = 0;
 
         for split in splits {
-            total_amount += split.amount();
+            if split.is_paid() || split.user_id().eq(&expense_owner_id) {
+                continue;
+            }
+
+            let settled = settled_amounts
+                .get(&(expense_id, split.user_id()))
+                .copied()
+                .unwrap_or(0);
+
+            if expense_owner_id == viewer_id {
+                settled_sum -= settled;
+            } else if split.user_id().eq(&viewer_id) {
+                settled_sum += settled;
+            }
         }
 
-        total_amount
+        settled_sum
     }
 
     /// DTO used when working with splits.
@@ -109,46 +163,107 @@ pub mod expense_api {
     }
 
     impl SplitDTO {
-        fn from(split: Split) -> Self {
+        fn from(split: Split, minor_units: i32) -> Self {
             Self {
                 user_id: split.user_id(),
                 is_paid: split.is_paid(),
-                amount: split.amount(),
+                amount: from_minor_units(split.amount(), minor_units),
             }
         }
 
-        fn from_vec_split(splits: Vec<Split>) -> Vec<SplitDTO> {
+        fn from_vec_split(splits: Vec<Split>, minor_units: i32) -> Vec<SplitDTO> {
             let mut dtos: Vec<SplitDTO> = vec![];
 
             for split in splits {
-                dtos.push(SplitDTO::from(split))
+                dtos.push(SplitDTO::from(split, minor_units))
             }
 
             dtos
         }
     }
 
+    /// Defines how the total amount of an expense is split among the users of the splits.
+    #[derive(Clone, Copy, Default, ToSchema, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum SplitModeDTO {
+        /// The total is split equally among the users of the splits.
+        Equal,
+        /// Each split has a `percentage` of the total, all of them adding up to 100.
+        Percentage,
+        /// Each split has a number of `shares`, the total is split proportionally to them.
+        Shares,
+        /// Each split has an exact `amount`. Splits without an `amount` share the remainder of
+        /// the total equally.
+        #[default]
+        Exact,
+    }
+
     /// DTO used when creating new Splits for expenses.
     #[derive(Clone, ToSchema, Serialize, Deserialize)]
     pub struct NewSplitDTO {
         user_id: Uuid,
-        amount: f64
+        /// Used by the `exact` split mode. Rounded to the minor units of the expense's currency.
+        /// If the splits don't add up to whole minor units, the owner of the expense receives
+        /// the leftover.
+        amount: Option<f64>,
+        /// Used by the `percentage` split mode.
+        percentage: Option<f64>,
+        /// Used by the `shares` split mode.
+        shares: Option<f64>,
     }
 
     impl NewSplitDTO {
-        /// Turns this SplitDTO into a db NewExpenseSplit.
-        fn to_new_db(&self) -> NewExpenseSplit {
-            NewExpenseSplit::new(self.user_id, self.amount)
+        /// Turns the given `NewSplitDTO`s into the `SplitMode` for the given `SplitModeDTO`.
+        /// Returns 400 if a split lacks the value needed by the split mode.
+        fn to_split_mode(
+            split_mode: SplitModeDTO,
+            splits: &[Self],
+        ) -> Result<SplitMode, ApiResponse<String>> {
+            let values = |value: fn(&Self) -> Option<f64>, name: &str| {
+                splits
+                    .iter()
+                    .map(|split| match value(split) {
+                        Some(value) => Ok((split.user_id, value)),
+                        None => Err((
+                            StatusCode::BAD_REQUEST,
+                            Json(format!("The split of user {} needs a {}", split.user_id, name)),
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            };
+
+            Ok(match split_mode {
+                SplitModeDTO::Equal => {
+                    SplitMode::Equal(splits.iter().map(|split| split.user_id).collect())
+                }
+                SplitModeDTO::Percentage => {
+                    SplitMode::Percentage(values(|split| split.percentage, "percentage")?)
+                }
+                SplitModeDTO::Shares => SplitMode::Shares(values(|split| split.shares, "share")?),
+                SplitModeDTO::Exact => SplitMode::Exact(
+                    splits
+                        .iter()
+                        .map(|split| (split.user_id, split.amount))
+                        .collect(),
+                ),
+            })
         }
     }
 
     /// DTO used when creating a new expense for the given pot.
-    #[derive(ToSchema, Serialize, Deserialize)]
+    #[derive(Clone, ToSchema, Serialize, Deserialize)]
     pub struct NewExpenseDTO {
         #[schema(max_length=24)]
         description: String,
         currency_id: i32,
+        /// The total amount that has been paid. Required for all split modes except `exact`,
+        /// where it defaults to the sum of the splits.
+        total_amount: Option<f64>,
+        /// Defaults to `exact`.
+        split_mode: Option<SplitModeDTO>,
         splits: Vec<NewSplitDTO>,
+        /// The date the money has been spent. Defaults to now.
+        spent_at: Option<DateTime<Utc>>,
     }
 
     impl NewExpenseDTO {
@@ -159,37 +274,68 @@ pub mod expense_api {
                 owning_pot_id,
                 self.description.clone(),
                 self.currency_id,
+                self.spent_at,
             )
         }
 
-        /// Turns the `Vec<SplitDTO>` into a `Vec<NewExpenseSplit>`.
-        pub(crate) fn splits_to_new_db(&self) -> Vec<NewExpenseSplit> {
-            let mut splits = vec![];
-
-            for split in &self.splits {
-                let db_split = split.to_new_db();
-                splits.push(db_split);
-            }
+        /// Turns the splits into the `SplitMode` used to compute the splits of the expense.
+        pub(crate) fn split_mode(&self) -> Result<SplitMode, ApiResponse<String>> {
+            NewSplitDTO::to_split_mode(self.split_mode.unwrap_or_default(), &self.splits)
+        }
 
-            splits
+        pub fn total_amount(&self) -> Option<f64> {
+            self.total_amount
         }
     }
 
-    impl Clone for NewExpenseDTO {
-        fn clone(&self) -> Self {
-            Self {
-                description: self.description.clone(),
-                currency_id: self.currency_id,
-                splits: self.splits.clone(),
+    /// DTO used when updating an existing expense. Properties set to `null` are left untouched.
+    #[derive(ToSchema, Serialize, Deserialize)]
+    pub struct ExpenseUpdateDTO {
+        #[schema(max_length=24)]
+        description: Option<String>,
+        currency_id: Option<i32>,
+        /// The date the money has been spent.
+        spent_at: Option<DateTime<Utc>>,
+        /// The total amount that has been paid, see `NewExpenseDTO`. Only allowed with `splits`.
+        total_amount: Option<f64>,
+        /// Defaults to `exact`. Only allowed with `splits`.
+        split_mode: Option<SplitModeDTO>,
+        /// Replaces all splits of the expense, if given.
+        splits: Option<Vec<NewSplitDTO>>,
+    }
+
+    impl ExpenseUpdateDTO {
+        /// Return false if all properties are set to None or if `total_amount` or `split_mode`
+        /// are given without `splits`, as they would be ignored.
+        pub(crate) fn is_valid(&self) -> bool {
+            if self.description.is_none()
+                && self.currency_id.is_none()
+                && self.spent_at.is_none()
+                && self.splits.is_none() {
+                return false;
+            }
+
+            if self.splits.is_none() && (self.total_amount.is_some() || self.split_mode.is_some()) {
+                return false;
             }
+            true
         }
     }
 
+    /// The state of the expense API.
+    pub struct ExpenseApiState {
+        expense_service: ExpenseService,
+    }
+
     pub fn register(pool: DbPool) -> OpenApiRouter {
+        let shared_state = Arc::new(ExpenseApiState {
+            expense_service: expense_service::expense_service::new_service(pool),
+        });
+
         OpenApiRouter::new()
-            .routes(routes!(get_expense_by_id))
+            .routes(routes!(get_expense_by_id, update_expense, delete_expense))
             .routes(routes!(pay_expense))
-            .with_state(expense_service::expense_service::new_service(pool))
+            .with_state(shared_state)
     }
 
     /// Gets the expense with the given id. Returns 404 if no expense with the given id exists
@@ -217,30 +363,154 @@ pub mod expense_api {
         )
     )]
     pub async fn get_expense_by_id(
-        State(service): State<ExpenseService>,
+        State(expense_api_state): State<Arc<ExpenseApiState>>,
         Path(expense_id): Path<i32>,
-        parts: Parts,
+        auth_user: AuthUser,
     ) -> Result<ApiResponse<ExpenseDTO>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
+        let service = &expense_api_state.expense_service;
 
         let expense = service
             .get_expense_by_id(expense_id, subject_id)
             .await
             .map_err(check_error)?;
 
+        let settled_amounts = service
+            .get_settled_amounts(expense.0.pot_id(), subject_id)
+            .await
+            .map_err(check_error)?;
+
         Ok((
             StatusCode::OK,
             Json(ExpenseDTO::from(
-                expense.0, expense.2, expense.1, subject_id,
+                expense.0, expense.2, expense.1, subject_id, &settled_amounts,
             )),
         ))
     }
 
-    /// Pays the expense with the given `expense_id` if the user has access to it.
+    /// Updates description, currency and splits of the expense with the given `expense_id`, if
+    /// the bearer is the owner of the expense or an admin of its pot and none of the other users
+    /// has paid their split.
     #[utoipa::path(
         put,
         path = "/expenses/{expense_id}",
         tag = "Expenses",
+        responses(
+            (
+                status = 200,
+                description = "The Expense with the given id has been updated.",
+                body = ExpenseDTO
+            ),
+            (status = 400, description = "The update is invalid, e.g. because it is empty or the splits don't add up to the total amount."),
+            (status = 403, description = "Indicates that the bearer neither owns the Expense nor is an admin of its pot."),
+            (status = 404, description = "Indicates that the desired Expense does not exists."),
+            (status = 409, description = "Some splits of the Expense have already been paid."),
+            (status = 423, description = "The Expense can't be updated, as the pot is archived."),
+        ),
+        request_body = ExpenseUpdateDTO,
+        params(
+            ("expense_id" = i32, Path, description = "Expense database id for the Expense.")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn update_expense(
+        State(expense_api_state): State<Arc<ExpenseApiState>>,
+        Path(expense_id): Path<i32>,
+        auth_user: AuthUser,
+        Json(expense_update_dto): Json<ExpenseUpdateDTO>,
+    ) -> Result<ApiResponse<ExpenseDTO>, ApiResponse<String>> {
+        let subject_id = auth_user.id();
+        let service = &expense_api_state.expense_service;
+
+        if !expense_update_dto.is_valid() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json("Invalid expense update DTO. \
+                     Ensure that it contains at least one property and that total_amount \
+                     and split_mode are only given together with splits".to_string())
+            ))
+        }
+
+        let split_mode = match &expense_update_dto.splits {
+            Some(splits) => Some(NewSplitDTO::to_split_mode(
+                expense_update_dto.split_mode.unwrap_or_default(),
+                splits,
+            )?),
+            None => None,
+        };
+
+        let (expense, splits, currency) = service
+            .update_expense(
+                expense_id,
+                ExpenseUpdate::new(
+                    expense_update_dto.description,
+                    expense_update_dto.currency_id,
+                    expense_update_dto.spent_at,
+                ),
+                expense_update_dto.total_amount,
+                split_mode,
+                subject_id,
+            )
+            .await
+            .map_err(check_error)?;
+
+        let settled_amounts = service
+            .get_settled_amounts(expense.pot_id(), subject_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(ExpenseDTO::from(expense, currency, splits, subject_id, &settled_amounts)),
+        ))
+    }
+
+    /// Deletes the expense with the given `expense_id`, if the bearer is the owner of the expense
+    /// or an admin of its pot and none of the other users has paid their split.
+    #[utoipa::path(
+        delete,
+        path = "/expenses/{expense_id}",
+        tag = "Expenses",
+        responses(
+            (status = 204, description = "The Expense with the given id has been deleted."),
+            (status = 403, description = "Indicates that the bearer neither owns the Expense nor is an admin of its pot."),
+            (status = 404, description = "Indicates that the desired Expense does not exists."),
+            (status = 409, description = "Some splits of the Expense have already been paid."),
+            (status = 423, description = "The Expense can't be deleted, as the pot is archived."),
+        ),
+        params(
+            ("expense_id" = i32, Path, description = "Expense database id for the Expense.")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn delete_expense(
+        State(expense_api_state): State<Arc<ExpenseApiState>>,
+        Path(expense_id): Path<i32>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<String>, ApiResponse<String>> {
+        let subject_id = auth_user.id();
+
+        expense_api_state
+            .expense_service
+            .delete_expense(expense_id, subject_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::NO_CONTENT,
+            Json(format!("Expense with id {} has been deleted.", expense_id))
+        ))
+    }
+
+    /// Pays the expense with the given `expense_id` if the user has access to it.
+    #[utoipa::path(
+        put,
+        path = "/expenses/{expense_id}/pay",
+        tag = "Expenses",
         responses(
             (
                 status = 200,
@@ -264,11 +534,12 @@ pub mod expense_api {
         )
     )]
     pub async fn pay_expense(
-        State(service): State<ExpenseService>,
+        State(expense_api_state): State<Arc<ExpenseApiState>>,
         Path(expense_id): Path<i32>,
-        parts: Parts,
+        auth_user: AuthUser,
     ) -> Result<ApiResponse<ExpenseDTO>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
+        let service = &expense_api_state.expense_service;
 
         service
             .pay_expense(expense_id, subject_id)
@@ -284,7 +555,12 @@ pub mod expense_api {
         let splits = expense.1;
         let expense = expense.0;
 
-        let expense_dto = ExpenseDTO::from(expense, currency, splits, subject_id);
+        let settled_amounts = service
+            .get_settled_amounts(expense.pot_id(), subject_id)
+            .await
+            .map_err(check_error)?;
+
+        let expense_dto = ExpenseDTO::from(expense, currency, splits, subject_id, &settled_amounts);
 
         Ok((StatusCode::OK, Json(expense_dto)))
     }
@@ -292,8 +568,10 @@ pub mod expense_api {
 
 #[cfg(test)]
 mod tests {
-    use crate::expense_api::expense_api::get_sum;
+    use crate::expense_api::expense_api::{get_settled_sum, get_sum, ExpenseUpdateDTO};
     use expense_tracker_db::splits::splits::Split;
+    use expense_tracker_services::expense_service::expense_service::SettledAmounts;
+    use utoipa::r#gen::serde_json::{from_value, json};
     use uuid::{uuid, Uuid};
 
     const USER_ONE: Uuid = uuid!("e6be621a-ec2d-48f3-8027-0d34cf5cbe40");
@@ -306,11 +584,11 @@ mod tests {
     /// First case: Expense_Owner is the viewer, so sum should be 0. Only one split
     #[test]
     fn get_sum_test_expense_owner_is_viewer_one_split() {
-        let splits = vec![Split::new(1, USER_ONE, 42.0, true)];
+        let splits = vec![Split::new(1, USER_ONE, 4200, true)];
 
         let sum = get_sum(USER_ONE, USER_ONE, &splits);
 
-        assert_eq!(sum, 0.0);
+        assert_eq!(sum, 0);
     }
 
     /// Second case: Expense_Owner is the viewer, so sum should be positive sum of
@@ -318,14 +596,14 @@ mod tests {
     #[test]
     fn get_sum_test_expense_owner_is_viewer_multiple_splits_nothing_paid() {
         let splits = vec![
-            Split::new(1, USER_ONE, 42.0, true),
-            Split::new(1, USER_TWO, 42.0, false),
-            Split::new(1, USER_THREE, 42.0, false),
+            Split::new(1, USER_ONE, 4200, true),
+            Split::new(1, USER_TWO, 4200, false),
+            Split::new(1, USER_THREE, 4200, false),
         ];
 
         let sum = get_sum(USER_ONE, USER_ONE, &splits);
 
-        assert_eq!(sum, 84.0);
+        assert_eq!(sum, 8400);
     }
 
     /// Third case: Expense_Owner is the viewer, so sum should be positive sum of
@@ -333,16 +611,16 @@ mod tests {
     #[test]
     fn get_sum_test_expense_owner_is_viewer_multiple_splits_some_paid() {
         let splits = vec![
-            Split::new(1, USER_ONE, 42.0, true),
-            Split::new(1, USER_TWO, 42.0, false),
-            Split::new(1, USER_THREE, 42.0, false),
-            Split::new(1, USER_FOUR, 42.0, true),
-            Split::new(1, USER_FIVE, 42.0, true),
+            Split::new(1, USER_ONE, 4200, true),
+            Split::new(1, USER_TWO, 4200, false),
+            Split::new(1, USER_THREE, 4200, false),
+            Split::new(1, USER_FOUR, 4200, true),
+            Split::new(1, USER_FIVE, 4200, true),
         ];
 
         let sum = get_sum(USER_ONE, USER_ONE, &splits);
 
-        assert_eq!(sum, 84.0);
+        assert_eq!(sum, 8400);
     }
 
     /// Fourth case: Expense_Owner is not the viewer, so sum should be negative sum of
@@ -350,14 +628,14 @@ mod tests {
     #[test]
     fn get_sum_test_expense_owner_is_not_viewer_multiple_splits_nothing_paid() {
         let splits = vec![
-            Split::new(1, USER_ONE, 42.0, true),
-            Split::new(1, USER_TWO, 42.0, false),
-            Split::new(1, USER_THREE, 42.0, false),
+            Split::new(1, USER_ONE, 4200, true),
+            Split::new(1, USER_TWO, 4200, false),
+            Split::new(1, USER_THREE, 4200, false),
         ];
 
         let sum = get_sum(USER_ONE, USER_TWO, &splits);
 
-        assert_eq!(sum, -42.0);
+        assert_eq!(sum, -4200);
     }
 
     /// Fifth case: Expense_Owner is not the viewer, so sum should be negative sum of
@@ -365,14 +643,14 @@ mod tests {
     #[test]
     fn get_sum_test_expense_owner_is_not_viewer_multiple_splits_viewer_paid() {
         let splits = vec![
-            Split::new(1, USER_ONE, 42.0, true),
-            Split::new(1, USER_TWO, 42.0, true),
-            Split::new(1, USER_THREE, 42.0, false),
+            Split::new(1, USER_ONE, 4200, true),
+            Split::new(1, USER_TWO, 4200, true),
+            Split::new(1, USER_THREE, 4200, false),
         ];
 
         let sum = get_sum(USER_ONE, USER_TWO, &splits);
 
-        assert_eq!(sum, 0.0);
+        assert_eq!(sum, 0);
     }
 
     /// Sixth case: Expense_Owner is not the viewer, so sum should be negative sum of
@@ -380,16 +658,16 @@ mod tests {
     #[test]
     fn get_sum_test_expense_owner_is_not_viewer_multiple_splits_viewer_some_paid() {
         let splits = vec![
-            Split::new(1, USER_ONE, 42.0, true),
-            Split::new(1, USER_TWO, 42.0, true),
-            Split::new(1, USER_TWO, 42.0, false),
-            Split::new(1, USER_TWO, 42.0, false),
-            Split::new(1, USER_THREE, 42.0, false),
+            Split::new(1, USER_ONE, 4200, true),
+            Split::new(1, USER_TWO, 4200, true),
+            Split::new(1, USER_TWO, 4200, false),
+            Split::new(1, USER_TWO, 4200, false),
+            Split::new(1, USER_THREE, 4200, false),
         ];
 
         let sum = get_sum(USER_ONE, USER_TWO, &splits);
 
-        assert_eq!(sum, -84.0);
+        assert_eq!(sum, -8400);
     }
 
     /// Eighth case: Expense_Owner is not the viewer, viewer does not owe any money.
@@ -398,15 +676,64 @@ mod tests {
     fn get_sum_test_expense_owner_is_not_viewer_multiple_splits_viewer_some_paid_view_is_not_debtor(
     ) {
         let splits = vec![
-            Split::new(1, USER_ONE, 42.0, true),
-            Split::new(1, USER_TWO, 42.0, true),
-            Split::new(1, USER_TWO, 42.0, false),
-            Split::new(1, USER_TWO, 42.0, false),
-            Split::new(1, USER_THREE, 42.0, false),
+            Split::new(1, USER_ONE, 4200, true),
+            Split::new(1, USER_TWO, 4200, true),
+            Split::new(1, USER_TWO, 4200, false),
+            Split::new(1, USER_TWO, 4200, false),
+            Split::new(1, USER_THREE, 4200, false),
         ];
 
         let sum = get_sum(USER_ONE, OTHER_USER, &splits);
 
-        assert_eq!(sum, 0.0);
+        assert_eq!(sum, 0);
+    }
+
+    /// The viewer owns the expense and USER_TWO already paid a part of their split.
+    #[test]
+    fn get_settled_sum_test_expense_owner_is_viewer() {
+        let splits = vec![
+            Split::new(1, USER_ONE, 4200, true),
+            Split::new(1, USER_TWO, 4200, false),
+            Split::new(1, USER_THREE, 4200, false),
+        ];
+        let settled_amounts = SettledAmounts::from([((1, USER_TWO), 2000)]);
+
+        let sum = get_sum(USER_ONE, USER_ONE, &splits)
+            + get_settled_sum(1, USER_ONE, USER_ONE, &splits, &settled_amounts);
+
+        assert_eq!(sum, 6400);
+    }
+
+    /// The viewer owes the owner and already paid a part of their split.
+    #[test]
+    fn get_settled_sum_test_viewer_is_debtor() {
+        let splits = vec![
+            Split::new(1, USER_ONE, 4200, true),
+            Split::new(1, USER_TWO, 4200, false),
+            Split::new(1, USER_THREE, 4200, false),
+        ];
+        let settled_amounts = SettledAmounts::from([((1, USER_TWO), 2000), ((1, USER_THREE), 200)]);
+
+        let sum = get_sum(USER_ONE, USER_TWO, &splits)
+            + get_settled_sum(1, USER_ONE, USER_TWO, &splits, &settled_amounts);
+
+        assert_eq!(sum, -2200);
+    }
+
+    #[test]
+    fn test_expense_update_is_valid() {
+        let is_valid = |update| from_value::<ExpenseUpdateDTO>(update).unwrap().is_valid();
+
+        assert!(is_valid(json!({ "description": "Dinner" })));
+        assert!(is_valid(json!({
+            "total_amount": 30.0,
+            "split_mode": "equal",
+            "splits": [{ "user_id": USER_ONE }, { "user_id": USER_TWO }]
+        })));
+
+        assert!(!is_valid(json!({})));
+        // the total and the split mode only apply to new splits, so they can't be given alone
+        assert!(!is_valid(json!({ "total_amount": 30.0 })));
+        assert!(!is_valid(json!({ "description": "Dinner", "split_mode": "equal" })));
     }
 }
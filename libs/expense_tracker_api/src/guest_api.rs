@@ -0,0 +1,158 @@
+pub mod guest_api {
+    use crate::api::{check_error, ApiResponse};
+    use crate::auth::auth::AuthUser;
+    use crate::invitation_api::invitation_api::JoinedPotDTO;
+    use crate::user_api::user_api::UserDTO;
+    use axum::extract::{Path, State};
+    use axum::http::StatusCode;
+    use axum::Json;
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_services::guest_service::guest_service;
+    use expense_tracker_services::guest_service::guest_service::GuestService;
+    use serde::{Deserialize, Serialize};
+    use std::sync::Arc;
+    use utoipa::ToSchema;
+    use utoipa_axum::router::OpenApiRouter;
+    use utoipa_axum::routes;
+    use uuid::Uuid;
+
+    /// Holds the App State for the GuestAPI.
+    pub struct GuestApiState {
+        guest_service: GuestService,
+    }
+
+    /// Registers all functions of the Guest API. Guests are listed with the other members of
+    /// their pot and removed like them.
+    pub fn register(pool: DbPool) -> OpenApiRouter {
+        let shared_state = Arc::new(GuestApiState {
+            guest_service: guest_service::new_service(pool),
+        });
+
+        OpenApiRouter::new()
+            .routes(routes!(add_guest))
+            .routes(routes!(create_claim_code))
+            .routes(routes!(claim_guest))
+            .with_state(shared_state)
+    }
+
+    /// DTO used to add a guest to a pot.
+    #[derive(ToSchema, Deserialize)]
+    pub struct NewGuestDTO {
+        #[schema(max_length = 24)]
+        name: String,
+    }
+
+    /// DTO of a code a guest can be claimed with.
+    #[derive(ToSchema, Serialize)]
+    pub struct ClaimCodeDTO {
+        code: String,
+    }
+
+    /// Adds a guest to the pot, for people that take part in expenses but never log in. Guests
+    /// can be part of splits like any other member.
+    #[utoipa::path(
+        post,
+        path = "/pots/{pot_id}/guests",
+        tag = "Guests",
+        params(
+            ("pot_id" = i32, Path, description = "The id of the pot")
+        ),
+        request_body = NewGuestDTO,
+        responses(
+            (status = 201, description = "The added guest", body = UserDTO),
+            (status = 400, description = "The name is empty or too long"),
+            (status = 403, description = "The bearer is not an admin of the pot"),
+            (status = 404, description = "The pot could not be found"),
+            (status = 423, description = "The pot is archived"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn add_guest(
+        State(guest_api_state): State<Arc<GuestApiState>>,
+        auth_user: AuthUser,
+        Path(pot_id): Path<i32>,
+        Json(new_guest): Json<NewGuestDTO>,
+    ) -> Result<ApiResponse<UserDTO>, ApiResponse<String>> {
+        let guest = guest_api_state
+            .guest_service
+            .add_guest(pot_id, &new_guest.name, auth_user.id())
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::CREATED, Json(UserDTO::from(guest))))
+    }
+
+    /// Creates a code the guest can be claimed with, to be handed to the person behind the
+    /// guest once they have an account. A previous code of the guest stops working.
+    #[utoipa::path(
+        post,
+        path = "/pots/{pot_id}/guests/{guest_id}/claim_code",
+        tag = "Guests",
+        params(
+            ("pot_id" = i32, Path, description = "The id of the pot"),
+            ("guest_id" = Uuid, Path, description = "The id of the guest")
+        ),
+        responses(
+            (status = 201, description = "The code the guest can be claimed with", body = ClaimCodeDTO),
+            (status = 403, description = "The bearer is not an admin of the pot"),
+            (status = 404, description = "The pot could not be found or the guest is not part of it"),
+            (status = 423, description = "The pot is archived"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn create_claim_code(
+        State(guest_api_state): State<Arc<GuestApiState>>,
+        auth_user: AuthUser,
+        Path((pot_id, guest_id)): Path<(i32, Uuid)>,
+    ) -> Result<ApiResponse<ClaimCodeDTO>, ApiResponse<String>> {
+        let code = guest_api_state
+            .guest_service
+            .create_claim_code(pot_id, guest_id, auth_user.id())
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::CREATED, Json(ClaimCodeDTO { code })))
+    }
+
+    /// Claims the guest of the given code. The bearer takes over the membership, splits,
+    /// expenses and settlements of the guest, which is deleted afterwards. Splits of the bearer
+    /// and the guest in the same expense are merged, and settlements between the two are
+    /// dropped. The bearer must have called `current_user` before, so their user exists.
+    #[utoipa::path(
+        post,
+        path = "/guests/claim/{code}",
+        tag = "Guests",
+        params(
+            ("code" = String, Path, description = "The claim code")
+        ),
+        responses(
+            (status = 200, description = "The pot of the claimed guest", body = JoinedPotDTO),
+            (status = 403, description = "The bearer is a guest"),
+            (status = 404, description = "The claim code could not be found, or the bearer does not exist yet"),
+            (status = 423, description = "The pot of the guest is archived"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn claim_guest(
+        State(guest_api_state): State<Arc<GuestApiState>>,
+        auth_user: AuthUser,
+        Path(code): Path<String>,
+    ) -> Result<ApiResponse<JoinedPotDTO>, ApiResponse<String>> {
+        let pot = guest_api_state
+            .guest_service
+            .claim_guest(auth_user.id(), &code)
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(JoinedPotDTO::from(pot))))
+    }
+}
@@ -0,0 +1,369 @@
+pub mod invitation_api {
+    use crate::api::{check_error, ApiResponse};
+    use crate::auth::auth::AuthUser;
+    use crate::user_api::user_api::UserDTO;
+    use axum::extract::{Path, State};
+    use axum::http::StatusCode;
+    use axum::Json;
+    use chrono::{DateTime, Utc};
+    use expense_tracker_db::pot_invitations::pot_invitations::PotInvitation;
+    use expense_tracker_db::pots::pots::Pot;
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::users::users::User;
+    use expense_tracker_services::invitation_service::invitation_service;
+    use expense_tracker_services::invitation_service::invitation_service::InvitationService;
+    use serde::{Deserialize, Serialize};
+    use std::sync::Arc;
+    use utoipa::ToSchema;
+    use utoipa_axum::router::OpenApiRouter;
+    use utoipa_axum::routes;
+    use uuid::Uuid;
+
+    /// Holds the App State for the InvitationAPI.
+    pub struct InvitationApiState {
+        invitation_service: InvitationService,
+    }
+
+    /// Registers all functions of the Invitation API. Users are invited to pots with
+    /// `PUT /pots/{pot_id}` of the Pot API.
+    pub fn register(pool: DbPool) -> OpenApiRouter {
+        let shared_state = Arc::new(InvitationApiState {
+            invitation_service: invitation_service::new_service(pool),
+        });
+
+        OpenApiRouter::new()
+            .routes(routes!(get_pot_invitations))
+            .routes(routes!(create_invite_code))
+            .routes(routes!(revoke_invitation))
+            .routes(routes!(get_pending_invitations))
+            .routes(routes!(accept_invitation))
+            .routes(routes!(decline_invitation))
+            .routes(routes!(join_with_code))
+            .with_state(shared_state)
+    }
+
+    /// DTO of an invitation to a pot, as seen by the owner of the pot.
+    #[derive(ToSchema, Serialize)]
+    pub struct PotInvitationDTO {
+        id: i32,
+        pot_id: i32,
+        inviter_id: Uuid,
+        /// The invited user. Null for invitations with a code.
+        invitee_id: Option<Uuid>,
+        /// The code anyone can join the pot with. Null for invitations of a single user.
+        #[schema(example = "k7mq2x9ahf3c")]
+        code: Option<String>,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    }
+
+    impl PotInvitationDTO {
+        /// Creates a new PotInvitationDTO from a db PotInvitation.
+        pub fn from(invitation: PotInvitation) -> Self {
+            Self {
+                id: invitation.id(),
+                pot_id: invitation.pot_id(),
+                inviter_id: invitation.inviter_id(),
+                invitee_id: invitation.invitee_id(),
+                code: invitation.code().map(str::to_string),
+                created_at: invitation.created_at(),
+                expires_at: invitation.expires_at(),
+            }
+        }
+    }
+
+    /// DTO of an invitation to a pot, as seen by the invited user.
+    #[derive(ToSchema, Serialize)]
+    pub struct PendingInvitationDTO {
+        id: i32,
+        pot_id: i32,
+        pot_name: String,
+        inviter: UserDTO,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    }
+
+    impl PendingInvitationDTO {
+        /// Creates a new PendingInvitationDTO from a db PotInvitation, its pot and the user that
+        /// sent it.
+        pub fn from(invitation: PotInvitation, pot: Pot, inviter: User) -> Self {
+            Self {
+                id: invitation.id(),
+                pot_id: pot.id(),
+                pot_name: pot.name().to_string(),
+                inviter: UserDTO::from(inviter),
+                created_at: invitation.created_at(),
+                expires_at: invitation.expires_at(),
+            }
+        }
+    }
+
+    /// DTO used when creating a new invite code.
+    #[derive(ToSchema, Deserialize)]
+    pub struct NewInviteCodeDTO {
+        /// The code can't be used anymore after this date. Defaults to 14 days from now.
+        expires_at: Option<DateTime<Utc>>,
+    }
+
+    /// DTO of the pot a user joined.
+    #[derive(ToSchema, Serialize)]
+    pub struct JoinedPotDTO {
+        pot_id: i32,
+        pot_name: String,
+    }
+
+    impl JoinedPotDTO {
+        pub(crate) fn from(pot: Pot) -> Self {
+            Self {
+                pot_id: pot.id(),
+                pot_name: pot.name().to_string(),
+            }
+        }
+    }
+
+    /// Gets all invitations to the pot that did not expire yet, including invite codes.
+    #[utoipa::path(
+        get,
+        path = "/pots/{pot_id}/invitations",
+        tag = "Invitations",
+        params(
+            ("pot_id" = i32, Path, description = "The id of the pot")
+        ),
+        responses(
+            (status = 200, description = "The invitations to the pot", body = Vec<PotInvitationDTO>),
+            (status = 403, description = "The bearer is not the owner of the pot"),
+            (status = 404, description = "The pot could not be found"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn get_pot_invitations(
+        State(invitation_api_state): State<Arc<InvitationApiState>>,
+        auth_user: AuthUser,
+        Path(pot_id): Path<i32>,
+    ) -> Result<ApiResponse<Vec<PotInvitationDTO>>, ApiResponse<String>> {
+        let invitations = invitation_api_state
+            .invitation_service
+            .get_pot_invitations(pot_id, auth_user.id())
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(invitations.into_iter().map(PotInvitationDTO::from).collect()),
+        ))
+    }
+
+    /// Creates an invite code for the pot, which can be shared with anyone, e.g. as a link for
+    /// people that never logged in before. It can be used by any number of users, until it
+    /// expires or is revoked.
+    #[utoipa::path(
+        post,
+        path = "/pots/{pot_id}/invite_codes",
+        tag = "Invitations",
+        params(
+            ("pot_id" = i32, Path, description = "The id of the pot")
+        ),
+        request_body = NewInviteCodeDTO,
+        responses(
+            (status = 201, description = "The created invite code", body = PotInvitationDTO),
+            (status = 400, description = "The expiry is in the past"),
+            (status = 403, description = "The bearer is not the owner of the pot"),
+            (status = 404, description = "The pot could not be found"),
+            (status = 423, description = "The pot is archived"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn create_invite_code(
+        State(invitation_api_state): State<Arc<InvitationApiState>>,
+        auth_user: AuthUser,
+        Path(pot_id): Path<i32>,
+        Json(new_invite_code): Json<NewInviteCodeDTO>,
+    ) -> Result<ApiResponse<PotInvitationDTO>, ApiResponse<String>> {
+        let invitation = invitation_api_state
+            .invitation_service
+            .create_invite_code(pot_id, auth_user.id(), new_invite_code.expires_at)
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::CREATED, Json(PotInvitationDTO::from(invitation))))
+    }
+
+    /// Revokes the given invitation to the pot or invite code.
+    #[utoipa::path(
+        delete,
+        path = "/pots/{pot_id}/invitations/{invitation_id}",
+        tag = "Invitations",
+        params(
+            ("pot_id" = i32, Path, description = "The id of the pot"),
+            ("invitation_id" = i32, Path, description = "The id of the invitation")
+        ),
+        responses(
+            (status = 204, description = "The invitation has been revoked"),
+            (status = 403, description = "The bearer is not the owner of the pot"),
+            (status = 404, description = "The pot or the invitation could not be found"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn revoke_invitation(
+        State(invitation_api_state): State<Arc<InvitationApiState>>,
+        auth_user: AuthUser,
+        Path((pot_id, invitation_id)): Path<(i32, i32)>,
+    ) -> Result<ApiResponse<String>, ApiResponse<String>> {
+        invitation_api_state
+            .invitation_service
+            .revoke_invitation(pot_id, auth_user.id(), invitation_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::NO_CONTENT,
+            Json(format!("Invitation with id {} has been revoked.", invitation_id)),
+        ))
+    }
+
+    /// Gets the invitations of the bearer that did not expire yet.
+    #[utoipa::path(
+        get,
+        path = "/invitations",
+        tag = "Invitations",
+        responses(
+            (status = 200, description = "The pending invitations of the bearer", body = Vec<PendingInvitationDTO>),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn get_pending_invitations(
+        State(invitation_api_state): State<Arc<InvitationApiState>>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<Vec<PendingInvitationDTO>>, ApiResponse<String>> {
+        let invitations = invitation_api_state
+            .invitation_service
+            .get_pending_invitations(auth_user.id())
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(
+                invitations
+                    .into_iter()
+                    .map(|(invitation, pot, inviter)| {
+                        PendingInvitationDTO::from(invitation, pot, inviter)
+                    })
+                    .collect(),
+            ),
+        ))
+    }
+
+    /// Accepts the given invitation of the bearer, adding them to the pot.
+    #[utoipa::path(
+        post,
+        path = "/invitations/{invitation_id}/accept",
+        tag = "Invitations",
+        params(
+            ("invitation_id" = i32, Path, description = "The id of the invitation")
+        ),
+        responses(
+            (status = 200, description = "The pot the bearer joined", body = JoinedPotDTO),
+            (status = 404, description = "The invitation could not be found or has expired"),
+            (status = 409, description = "The bearer is already part of the pot"),
+            (status = 423, description = "The pot is archived"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn accept_invitation(
+        State(invitation_api_state): State<Arc<InvitationApiState>>,
+        auth_user: AuthUser,
+        Path(invitation_id): Path<i32>,
+    ) -> Result<ApiResponse<JoinedPotDTO>, ApiResponse<String>> {
+        let pot = invitation_api_state
+            .invitation_service
+            .accept_invitation(auth_user.id(), invitation_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(JoinedPotDTO::from(pot))))
+    }
+
+    /// Declines the given invitation of the bearer.
+    #[utoipa::path(
+        post,
+        path = "/invitations/{invitation_id}/decline",
+        tag = "Invitations",
+        params(
+            ("invitation_id" = i32, Path, description = "The id of the invitation")
+        ),
+        responses(
+            (status = 204, description = "The invitation has been declined"),
+            (status = 404, description = "The invitation could not be found"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn decline_invitation(
+        State(invitation_api_state): State<Arc<InvitationApiState>>,
+        auth_user: AuthUser,
+        Path(invitation_id): Path<i32>,
+    ) -> Result<ApiResponse<String>, ApiResponse<String>> {
+        invitation_api_state
+            .invitation_service
+            .decline_invitation(auth_user.id(), invitation_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::NO_CONTENT,
+            Json(format!("Invitation with id {} has been declined.", invitation_id)),
+        ))
+    }
+
+    /// Joins the pot of the given invite code. The bearer must have called `current_user`
+    /// before, so their user exists.
+    #[utoipa::path(
+        post,
+        path = "/invitations/join/{code}",
+        tag = "Invitations",
+        params(
+            ("code" = String, Path, description = "The invite code")
+        ),
+        responses(
+            (status = 200, description = "The pot the bearer joined", body = JoinedPotDTO),
+            (status = 404, description = "The invite code could not be found or has expired, or the bearer does not exist yet"),
+            (status = 409, description = "The bearer is already part of the pot"),
+            (status = 423, description = "The pot is archived"),
+            (status = 500, description = "Internal server error")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn join_with_code(
+        State(invitation_api_state): State<Arc<InvitationApiState>>,
+        auth_user: AuthUser,
+        Path(code): Path<String>,
+    ) -> Result<ApiResponse<JoinedPotDTO>, ApiResponse<String>> {
+        let pot = invitation_api_state
+            .invitation_service
+            .join_with_code(auth_user.id(), &code)
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(JoinedPotDTO::from(pot))))
+    }
+}
@@ -1,42 +1,67 @@
+// Every file wraps its content in a module named like the file itself.
+#![allow(clippy::module_inception)]
+
+pub mod auth;
+mod activity_api;
+mod admin_api;
+pub mod api_token_api;
+mod auth_api;
 mod currency_api;
 mod expense_api;
+mod guest_api;
 mod health_api;
+mod invitation_api;
 mod pot_api;
 mod user_api;
 mod generate_openapi;
 mod template_api;
 
 pub mod api {
+    use crate::activity_api::activity_api;
+    use crate::admin_api::admin_api;
+    use crate::api_token_api::api_token_api;
+    use crate::auth_api::auth_api;
     use crate::currency_api::currency_api;
     use crate::expense_api::expense_api;
+    use crate::guest_api::guest_api;
     use crate::health_api::health_api;
+    use crate::invitation_api::invitation_api;
     use crate::pot_api::pot_api;
     use crate::user_api::user_api;
-    use axum::http::request::Parts;
+    use crate::auth::auth::{ClaimNames, LocalTokens};
+    use axum::{middleware, Extension};
     use axum::http::StatusCode;
     use axum::Json;
     use expense_tracker_db::setup::DbPool;
+    use expense_tracker_services::user_service::user_service;
     use expense_tracker_services::{ExpenseError};
-    use utoipa::r#gen::serde_json::Value;
     use utoipa_axum::router::OpenApiRouter;
-    use uuid::Uuid;
     use crate::template_api::template_api;
 
     /// The generic response that is returned by APIs.
     pub type ApiResponse<T> = (StatusCode, Json<T>);
 
     const VERSION_ONE: &str = "/v1";
-    const SUB_CLAIM: &str = "sub";
-    const PREFERRED_USERNAME_CLAIM: &str = "preferred_username";
 
-    /// Registers the APIs with token validation.
-    pub async fn router(pool: DbPool) -> OpenApiRouter {
+    /// Registers the APIs with token validation. The user of a request is read from the claims
+    /// with the given names, and their stored profile is kept in sync with them.
+    pub async fn router(pool: DbPool, claim_names: ClaimNames) -> OpenApiRouter {
         OpenApiRouter::new()
             .nest(VERSION_ONE, user_api::register(pool.clone()))
             .nest(VERSION_ONE, pot_api::register(pool.clone()))
+            .nest(VERSION_ONE, invitation_api::register(pool.clone()))
+            .nest(VERSION_ONE, activity_api::register(pool.clone()))
+            .nest(VERSION_ONE, guest_api::register(pool.clone()))
             .nest(VERSION_ONE, currency_api::register(pool.clone()))
             .nest(VERSION_ONE, expense_api::register(pool.clone()))
             .nest(VERSION_ONE, template_api::register(pool.clone()).await)
+            .nest(VERSION_ONE, admin_api::register(pool.clone()))
+            .nest(VERSION_ONE, api_token_api::register(pool.clone()))
+            .layer(middleware::from_fn_with_state(
+                user_service::new_service(pool),
+                user_api::sync_profile,
+            ))
+            .layer(Extension(claim_names))
     }
 
     /// Registers the health API without token validation, so it is always possible to
@@ -45,11 +70,19 @@ pub mod api {
         OpenApiRouter::new().nest(VERSION_ONE, health_api::register())
     }
 
+    /// Registers the API to log in with local accounts without token validation, since it issues
+    /// the tokens. Only used if the built-in authentication is used instead of an OIDC provider.
+    pub async fn add_local_auth_api(pool: DbPool, local_tokens: LocalTokens) -> OpenApiRouter {
+        OpenApiRouter::new().nest(VERSION_ONE, auth_api::register(pool, local_tokens))
+    }
+
     /// Checks the given `Error` and gets the correct error message.
     /// Returns one of:
+    /// - 400
     /// - 403
     /// - 404
     /// - 409
+    /// - 423
     /// - 500
     pub fn check_error(err: ExpenseError) -> ApiResponse<String> {
         match err {
@@ -59,35 +92,7 @@ pub mod api {
             ExpenseError::Conflict(message) => (StatusCode::CONFLICT, Json(message)),
             ExpenseError::Locked(message) => (StatusCode::LOCKED, Json(message)),
             ExpenseError::CronConfigError(message) => (StatusCode::INTERNAL_SERVER_ERROR, Json(message)),
+            ExpenseError::BadRequest(message) => (StatusCode::BAD_REQUEST, Json(message)),
         }
     }
-
-    /// Gets the sub_claim from the given Request that has been put there by the auth middleware.
-    pub fn get_sub_claim(parts: &Parts) -> Result<Uuid, ApiResponse<String>> {
-        let claims: &Value = parts.extensions.get().unwrap();
-
-        let sub_claim = claims
-            .get(SUB_CLAIM)
-            .expect("Sub claim must be set!")
-            .as_str()
-            .ok_or(ExpenseError::Internal("Sub claim must be set!".to_string()))
-            .map_err(check_error);
-
-        Ok(Uuid::parse_str(sub_claim?).expect("Failed to parse uuid"))
-    }
-
-    /// Gets the preferred_username from the given Request that has been put there by the auth middleware.
-    pub fn get_username(parts: &Parts) -> Result<String, ApiResponse<String>> {
-        let claims: &Value = parts.extensions.get().unwrap();
-
-        let user_name = claims
-            .get(PREFERRED_USERNAME_CLAIM)
-            .expect("Username must be set!")
-            .as_str()
-            .ok_or(ExpenseError::Internal("Username must be set!".to_string()))
-            .map_err(check_error);
-
-        Ok(user_name?.to_string())
-    }
-
 }
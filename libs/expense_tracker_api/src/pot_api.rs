@@ -1,26 +1,52 @@
 pub mod pot_api {
-    use crate::api::{check_error, get_sub_claim, ApiResponse};
+    use crate::api::{check_error, ApiResponse};
+    use crate::auth::auth::AuthUser;
     use crate::currency_api::currency_api::CurrencyDTO;
+    use std::io;
     use std::sync::Arc;
 
     use crate::expense_api::expense_api::{ExpenseDTO, NewExpenseDTO};
+    use crate::invitation_api::invitation_api::PotInvitationDTO;
     use crate::user_api::user_api::UserDTO;
-    use axum::extract::{Path, State};
-    use axum::http::request::Parts;
-    use axum::http::StatusCode;
+    use axum::body::Body;
+    use axum::extract::{Path, Query, State};
+    use axum::http::header;
+        use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
     use axum::Json;
-    use chrono::{DateTime, Utc};
-    use expense_tracker_db::pots::pots::{NewPot, Pot, PotToUser};
+    use chrono::{DateTime, NaiveDate, Utc};
+    use expense_tracker_db::currencies::currencies::Currency;
+    use expense_tracker_db::pots::pots::{NewPot, Pot, PotRole, PotToUser};
+    use expense_tracker_db::settlements::settlements::{NewSettlement, Settlement};
     use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::splits::splits::Split;
     use expense_tracker_db::users::users::User;
     use expense_tracker_services::currency_service::currency_service;
     use expense_tracker_services::currency_service::currency_service::CurrencyService;
     use expense_tracker_services::expense_service::expense_service;
-    use expense_tracker_services::expense_service::expense_service::ExpenseService;
+    use expense_tracker_services::expense_service::expense_service::{
+        ExpenseFilter, ExpenseService, ExpenseSort, ExpenseSortField, Pagination, PotBalance,
+        SettledAmounts,
+    };
+    use expense_tracker_services::import_service::import_service;
+    use expense_tracker_services::import_service::import_service::{
+        ColumnMapping, ImportResult, ImportService,
+    };
+    use expense_tracker_services::invitation_service::invitation_service;
+    use expense_tracker_services::invitation_service::invitation_service::InvitationService;
+    use expense_tracker_services::money::money::{
+        format_minor_units, from_minor_units, to_minor_units,
+    };
     use expense_tracker_services::pot_service::pot_service;
-    use expense_tracker_services::pot_service::pot_service::PotService;
+    use expense_tracker_services::pot_service::pot_service::{
+        CurrencySettlement, CurrencySummary, MemberSummary, MonthSummary, PotExport, PotService,
+        PotUpdate, SettlementPlan, SettlementTransfer,
+    };
+    use expense_tracker_services::settlement_service::settlement_service;
+    use expense_tracker_services::settlement_service::settlement_service::SettlementService;
+    use futures_util::StreamExt;
     use serde::{Deserialize, Serialize};
-    use utoipa::ToSchema;
+    use utoipa::{IntoParams, ToSchema};
     use utoipa_axum::router::OpenApiRouter;
     use utoipa_axum::routes;
     use uuid::Uuid;
@@ -30,27 +56,50 @@ pub mod pot_api {
         pot_service: PotService,
         currency_service: CurrencyService,
         expense_service: ExpenseService,
+        settlement_service: SettlementService,
+        import_service: ImportService,
+        invitation_service: InvitationService,
+    }
+
+    impl PotApiState {
+        /// Creates the state of the PotAPI, with all services using the given pool.
+        pub(crate) fn new(pool: DbPool) -> Self {
+            Self {
+                pot_service: pot_service::new_service(pool.clone()),
+                currency_service: currency_service::new_service(pool.clone()),
+                expense_service: expense_service::new_service(pool.clone()),
+                settlement_service: settlement_service::new_service(pool.clone()),
+                import_service: import_service::new_service(pool.clone()),
+                invitation_service: invitation_service::new_service(pool),
+            }
+        }
     }
 
     /// Registers all functions of the Pot API.
     pub fn register(pool: DbPool) -> OpenApiRouter {
-        let shared_state = Arc::new(PotApiState {
-            pot_service: pot_service::new_service(pool.clone()),
-            currency_service: currency_service::new_service(pool.clone()),
-            expense_service: expense_service::new_service(pool.clone()),
-        });
+        let shared_state = Arc::new(PotApiState::new(pool));
 
         OpenApiRouter::new()
             .routes(routes!(create_pot))
             .routes(routes!(get_pots))
             .routes(routes!(add_expense))
             .routes(routes!(get_pot_expenses))
-            .routes(routes!(add_users_to_pot))
+            .routes(routes!(get_pot_expense_page))
+            .routes(routes!(add_users_to_pot, update_pot))
             .routes(routes!(remove_user_from_pot))
+            .routes(routes!(leave_pot))
+            .routes(routes!(get_pot_members))
+            .routes(routes!(set_member_role))
+            .routes(routes!(transfer_ownership))
             .routes(routes!(delete_pot))
             .routes(routes!(archive))
             .routes(routes!(unarchive))
             .routes(routes!(pay_pot))
+            .routes(routes!(get_settlement_plan))
+            .routes(routes!(get_pot_summary))
+            .routes(routes!(export_pot))
+            .routes(routes!(import_expenses))
+            .routes(routes!(create_settlement, get_settlements))
             .with_state(shared_state)
     }
 
@@ -60,11 +109,20 @@ pub mod pot_api {
         id: i32,
         owner_id: Uuid,
         name: String,
+        description: Option<String>,
         default_currency: CurrencyDTO,
         users: Vec<UserDTO>,
-        /// Indicates the amount of money, the user is owed or owes others. If positive, other users
-        /// need to pay that amount to the user. If negative, the user has to pay the given amount.
+        /// Indicates the amount of money, the user is owed or owes others, converted into the
+        /// default currency of the pot. If positive, other users need to pay that amount to the
+        /// user. If negative, the user has to pay the given amount. 0 if `conversion_unavailable`
+        /// is set.
         net_balance: f64,
+        /// Indicates that an exchange rate needed to convert the balances into the default
+        /// currency of the pot is unknown, so `net_balance` could not be computed. The balances
+        /// per currency are given regardless.
+        conversion_unavailable: bool,
+        /// The balances of the user per currency, as in `net_balance`.
+        balances: Vec<BalanceDTO>,
         archived: bool,
         created_at: DateTime<Utc>,
         archived_at: Option<DateTime<Utc>>
@@ -76,14 +134,22 @@ pub mod pot_api {
             pot: Pot,
             default_currency: CurrencyDTO,
             users: Vec<UserDTO>,
-            net_balance: f64) -> Self {
+            balance: PotBalance) -> Self {
+            let net_balance = balance
+                .converted()
+                .map(|converted| from_minor_units(converted, default_currency.minor_units()));
+            let conversion_unavailable = net_balance.is_none();
+
             Self {
                 id: pot.id(),
                 owner_id: pot.owner_id(),
                 name: pot.name().to_string(),
+                description: pot.description().map(str::to_string),
                 default_currency,
                 users,
-                net_balance,
+                net_balance: net_balance.unwrap_or(0.0),
+                conversion_unavailable,
+                balances: BalanceDTO::from_vec(balance.balances()),
                 archived: pot.is_archived(),
                 created_at: pot.created_at(),
                 archived_at: pot.archived_at()
@@ -92,7 +158,7 @@ pub mod pot_api {
 
         /// Create a vec<PotDTO> from a vec<Pot>.
         pub fn from_vec(
-            pot_vec: Vec<(Pot, Vec<User>, f64)>,
+            pot_vec: Vec<(Pot, Vec<User>, PotBalance)>,
             currency_vec: Vec<CurrencyDTO>,
         ) -> Vec<Self> {
             let mut dtos: Vec<PotDTO> = vec![];
@@ -116,18 +182,73 @@ pub mod pot_api {
         }
     }
 
+    /// DTO containing the balance of a user in a single currency.
+    #[derive(ToSchema, Serialize)]
+    pub struct BalanceDTO {
+        currency: CurrencyDTO,
+        amount: f64,
+    }
+
+    impl BalanceDTO {
+        /// Creates the DTOs from the given balances in minor units.
+        fn from_vec(balances: &[(Currency, i64)]) -> Vec<Self> {
+            balances
+                .iter()
+                .map(|(currency, amount)| Self {
+                    currency: CurrencyDTO::from(currency.clone()),
+                    amount: from_minor_units(*amount, currency.minor_units()),
+                })
+                .collect()
+        }
+    }
+
     /// DTO used when creating a new Pot.
     #[derive(ToSchema, Serialize, Deserialize)]
     pub struct NewPotDTO {
         #[schema(max_length=24)]
         name: String,
         default_currency_id: i32,
+        #[schema(max_length=500)]
+        description: Option<String>,
     }
 
     impl NewPotDTO {
         /// Converts the DTO to the db object.
         fn to_db(&self, owner_id: Uuid) -> NewPot {
-            NewPot::new(owner_id, self.name.clone(), self.default_currency_id)
+            let mut new_pot = NewPot::new(owner_id, self.name.clone(), self.default_currency_id);
+            new_pot.set_description(self.description.clone());
+            new_pot
+        }
+    }
+
+    /// DTO used when updating the settings of a Pot. Fields that are not given are left
+    /// untouched.
+    #[derive(ToSchema, Serialize, Deserialize)]
+    pub struct PotUpdateDTO {
+        #[schema(max_length=24)]
+        name: Option<String>,
+        /// The currency the balances of the pot are converted into. The expenses keep their
+        /// currencies.
+        default_currency_id: Option<i32>,
+        /// An empty description removes the description of the pot.
+        #[schema(max_length=500)]
+        description: Option<String>,
+    }
+
+    impl PotUpdateDTO {
+        /// Return false if all properties are set to None.
+        fn is_valid(&self) -> bool {
+            self.name.is_some() || self.default_currency_id.is_some() || self.description.is_some()
+        }
+
+        /// Converts the DTO to the update of the service.
+        fn to_update(&self) -> PotUpdate {
+            let description = self
+                .description
+                .as_ref()
+                .map(|description| Some(description.clone()).filter(|text| !text.is_empty()));
+
+            PotUpdate::new(self.name.clone(), self.default_currency_id, description)
         }
     }
 
@@ -153,6 +274,705 @@ pub mod pot_api {
         }
     }
 
+    /// The role of a member within a pot. Each role includes the permissions of the roles listed
+    /// before it.
+    #[derive(ToSchema, Serialize, Deserialize, Clone, Copy)]
+    #[serde(rename_all = "snake_case")]
+    pub enum PotRoleDTO {
+        /// Can only view the pot.
+        Viewer,
+        /// Can add expenses and settlements and change their own expenses.
+        Member,
+        /// Can manage the members and invitations, change all expenses and archive or pay the pot.
+        Admin,
+        /// Can delete the pot and transfer its ownership. Each pot has exactly one owner.
+        Owner,
+    }
+
+    impl PotRoleDTO {
+        /// Creates a new PotRoleDTO from a db PotRole.
+        pub fn from(pot_role: PotRole) -> Self {
+            match pot_role {
+                PotRole::Viewer => PotRoleDTO::Viewer,
+                PotRole::Member => PotRoleDTO::Member,
+                PotRole::Admin => PotRoleDTO::Admin,
+                PotRole::Owner => PotRoleDTO::Owner,
+            }
+        }
+
+        /// Converts the DTO to the db object.
+        fn to_db(self) -> PotRole {
+            match self {
+                PotRoleDTO::Viewer => PotRole::Viewer,
+                PotRoleDTO::Member => PotRole::Member,
+                PotRoleDTO::Admin => PotRole::Admin,
+                PotRoleDTO::Owner => PotRole::Owner,
+            }
+        }
+    }
+
+    /// DTO containing a member of a pot together with their role.
+    #[derive(ToSchema, Serialize)]
+    pub struct PotMemberDTO {
+        user: UserDTO,
+        role: PotRoleDTO,
+    }
+
+    impl PotMemberDTO {
+        /// Creates a new PotMemberDTO from a db User and their role.
+        pub fn from(user: User, role: PotRole) -> Self {
+            Self {
+                user: UserDTO::from(user),
+                role: PotRoleDTO::from(role),
+            }
+        }
+
+        /// Creates the DTOs from the given members.
+        pub fn from_vec(members: Vec<(User, PotRole)>) -> Vec<Self> {
+            members
+                .into_iter()
+                .map(|(user, role)| Self::from(user, role))
+                .collect()
+        }
+    }
+
+    /// DTO used to change the role of a member.
+    #[derive(ToSchema, Serialize, Deserialize)]
+    pub struct SetPotRoleDTO {
+        role: PotRoleDTO,
+    }
+
+    /// DTO used to transfer the ownership of a pot to another member.
+    #[derive(ToSchema, Serialize, Deserialize)]
+    pub struct TransferOwnershipDTO {
+        owner_id: Uuid,
+    }
+
+    /// DTO referencing a split that is cleared by a settlement.
+    #[derive(ToSchema, Serialize)]
+    pub struct SettlementSplitDTO {
+        expense_id: i32,
+        /// The user owing the money of this split.
+        user_id: Uuid,
+        amount: f64,
+    }
+
+    impl SettlementSplitDTO {
+        fn from_vec(splits: &[Split], minor_units: i32) -> Vec<Self> {
+            splits
+                .iter()
+                .map(|split| Self {
+                    expense_id: split.expense_id(),
+                    user_id: split.user_id(),
+                    amount: from_minor_units(split.amount(), minor_units),
+                })
+                .collect()
+        }
+    }
+
+    /// DTO describing a single transfer of a settlement plan.
+    #[derive(ToSchema, Serialize)]
+    pub struct SettlementTransferDTO {
+        /// The user who has to pay.
+        from: Uuid,
+        /// The user who receives the money.
+        to: Uuid,
+        amount: f64,
+        /// The splits that are cleared once this transfer has been made.
+        splits: Vec<SettlementSplitDTO>,
+    }
+
+    impl SettlementTransferDTO {
+        fn from(transfer: &SettlementTransfer, minor_units: i32) -> Self {
+            Self {
+                from: transfer.from(),
+                to: transfer.to(),
+                amount: from_minor_units(transfer.amount(), minor_units),
+                splits: SettlementSplitDTO::from_vec(transfer.splits(), minor_units),
+            }
+        }
+    }
+
+    /// DTO containing the settlement plan of a pot for a single currency.
+    #[derive(ToSchema, Serialize)]
+    pub struct CurrencySettlementPlanDTO {
+        currency: CurrencyDTO,
+        transfers: Vec<SettlementTransferDTO>,
+        /// Splits without a transfer from their debtor to their creditor, because their debts
+        /// cancel each other out, have already been covered by settlements or are paid through
+        /// the transfers of others.
+        netted_splits: Vec<SettlementSplitDTO>,
+    }
+
+    impl CurrencySettlementPlanDTO {
+        /// Creates the DTO from the given settlement.
+        fn from(settlement: &CurrencySettlement) -> Self {
+            let minor_units = settlement.currency().minor_units();
+
+            Self {
+                currency: CurrencyDTO::from(settlement.currency().clone()),
+                transfers: settlement
+                    .transfers()
+                    .iter()
+                    .map(|transfer| SettlementTransferDTO::from(transfer, minor_units))
+                    .collect(),
+                netted_splits: SettlementSplitDTO::from_vec(
+                    settlement.netted_splits(),
+                    minor_units,
+                ),
+            }
+        }
+    }
+
+    /// DTO containing the settlement plan of a pot.
+    #[derive(ToSchema, Serialize)]
+    pub struct SettlementPlanDTO {
+        /// The settlement plans per currency used in the pot.
+        currencies: Vec<CurrencySettlementPlanDTO>,
+        /// A single settlement plan for all currencies, converted into the default currency of
+        /// the pot at the rate of the day each expense was spent on or settlement made. The
+        /// amounts of its splits are converted as well. Null if an exchange rate needed for the
+        /// conversion is unknown.
+        converted: Option<CurrencySettlementPlanDTO>,
+    }
+
+    impl SettlementPlanDTO {
+        /// Creates the DTO from the given settlement plan.
+        pub fn from(plan: SettlementPlan) -> Self {
+            Self {
+                currencies: plan
+                    .currencies()
+                    .iter()
+                    .map(CurrencySettlementPlanDTO::from)
+                    .collect(),
+                converted: plan.converted().map(CurrencySettlementPlanDTO::from),
+            }
+        }
+    }
+
+    /// DTO describing what a member of a pot paid for and consumed in a single currency.
+    #[derive(ToSchema, Serialize)]
+    pub struct MemberSummaryDTO {
+        user_id: Uuid,
+        /// The total amount of the expenses the member paid for.
+        paid: f64,
+        /// The total amount of the splits of the member.
+        consumed: f64,
+        /// The open balance of the member, taking settlements into account. If positive, the
+        /// member is owed money. If negative, they owe others money.
+        outstanding: f64,
+    }
+
+    impl MemberSummaryDTO {
+        fn from(summary: &MemberSummary, minor_units: i32) -> Self {
+            Self {
+                user_id: summary.member_id(),
+                paid: from_minor_units(summary.paid(), minor_units),
+                consumed: from_minor_units(summary.consumed(), minor_units),
+                outstanding: from_minor_units(summary.outstanding(), minor_units),
+            }
+        }
+    }
+
+    /// DTO describing the expenses of a pot in a single currency and month.
+    #[derive(ToSchema, Serialize)]
+    pub struct MonthSummaryDTO {
+        /// The first day of the month.
+        month: NaiveDate,
+        total: f64,
+        expense_count: i64,
+    }
+
+    impl MonthSummaryDTO {
+        fn from(summary: &MonthSummary, minor_units: i32) -> Self {
+            Self {
+                month: summary.month(),
+                total: from_minor_units(summary.total(), minor_units),
+                expense_count: summary.expense_count(),
+            }
+        }
+    }
+
+    /// DTO containing the summary of a pot for a single currency.
+    #[derive(ToSchema, Serialize)]
+    pub struct CurrencySummaryDTO {
+        currency: CurrencyDTO,
+        /// The total amount of all expenses in this currency.
+        total: f64,
+        expense_count: i64,
+        /// The members that spent, consumed or settled anything in this currency.
+        members: Vec<MemberSummaryDTO>,
+        /// The months with expenses in this currency, the oldest first.
+        months: Vec<MonthSummaryDTO>,
+    }
+
+    impl CurrencySummaryDTO {
+        /// Creates the DTOs from the given summaries.
+        pub fn from_vec(summaries: Vec<CurrencySummary>) -> Vec<Self> {
+            summaries
+                .iter()
+                .map(|summary| {
+                    let minor_units = summary.currency().minor_units();
+
+                    Self {
+                        currency: CurrencyDTO::from(summary.currency().clone()),
+                        total: from_minor_units(summary.total(), minor_units),
+                        expense_count: summary.expense_count(),
+                        members: summary
+                            .members()
+                            .iter()
+                            .map(|member| MemberSummaryDTO::from(member, minor_units))
+                            .collect(),
+                        months: summary
+                            .months()
+                            .iter()
+                            .map(|month| MonthSummaryDTO::from(month, minor_units))
+                            .collect(),
+                    }
+                })
+                .collect()
+        }
+    }
+
+    /// The fields the expenses of a pot can be sorted by.
+    #[derive(ToSchema, Deserialize, Default)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ExpenseSortBy {
+        #[default]
+        SpentAt,
+        CreatedAt,
+        TotalAmount,
+        Description,
+    }
+
+    /// The direction the expenses of a pot are sorted in.
+    #[derive(ToSchema, Deserialize, Default)]
+    #[serde(rename_all = "lowercase")]
+    pub enum SortOrder {
+        Asc,
+        #[default]
+        Desc,
+    }
+
+    /// The parameters of a listing of the expenses of a pot.
+    #[derive(IntoParams, Deserialize)]
+    pub struct ExpenseListParams {
+        /// The number of the page, starting at 1. Defaults to 1.
+        #[serde(default = "default_page")]
+        page: i64,
+        /// The number of expenses per page, at most 200. Defaults to 50.
+        #[serde(default = "default_per_page")]
+        per_page: i64,
+        /// The field to sort by. Defaults to spent_at.
+        #[serde(default)]
+        #[param(inline)]
+        sort_by: ExpenseSortBy,
+        /// The direction to sort in. Defaults to desc.
+        #[serde(default)]
+        #[param(inline)]
+        order: SortOrder,
+        /// Only expenses paid by the given user.
+        owner_id: Option<Uuid>,
+        /// Only expenses the given user has a split of.
+        participant_id: Option<Uuid>,
+        currency_id: Option<i32>,
+        /// If true, only expenses whose splits are all paid. If false, only expenses with at
+        /// least one unpaid split.
+        paid: Option<bool>,
+        /// Only expenses whose description contains the given text, ignoring case.
+        description: Option<String>,
+        /// Only expenses spent at or after the given time.
+        spent_from: Option<DateTime<Utc>>,
+        /// Only expenses spent before the given time.
+        spent_to: Option<DateTime<Utc>>,
+    }
+
+    fn default_page() -> i64 {
+        1
+    }
+
+    fn default_per_page() -> i64 {
+        50
+    }
+
+    /// DTO for a single page of the expenses of a pot.
+    #[derive(ToSchema, Serialize)]
+    pub struct ExpensePageDTO {
+        expenses: Vec<ExpenseDTO>,
+        page: i64,
+        per_page: i64,
+        /// The number of expenses matching the filter on all pages.
+        total_count: i64,
+    }
+
+    /// The formats a pot can be exported in.
+    #[derive(ToSchema, Deserialize, Default, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ExportFormat {
+        #[default]
+        Json,
+        /// One row per split, e.g. to be opened in a spreadsheet.
+        Csv,
+    }
+
+    /// The parameters of a pot export.
+    #[derive(IntoParams, Deserialize)]
+    pub struct ExportParams {
+        /// The format of the export. Defaults to json.
+        #[serde(default)]
+        #[param(inline)]
+        format: ExportFormat,
+    }
+
+    /// The header of a CSV export, each following row describing a single split.
+    const CSV_HEADER: [&str; 11] = [
+        "expense_id",
+        "spent_at",
+        "description",
+        "owner_id",
+        "owner_name",
+        "currency",
+        "total_amount",
+        "user_id",
+        "user_name",
+        "amount",
+        "is_paid",
+    ];
+
+    /// DTO describing a split of an exported expense.
+    #[derive(ToSchema, Serialize)]
+    pub struct ExportedSplitDTO {
+        user_id: Uuid,
+        user_name: String,
+        amount: f64,
+        is_paid: bool,
+    }
+
+    /// DTO describing an exported expense.
+    #[derive(ToSchema, Serialize)]
+    pub struct ExportedExpenseDTO {
+        id: i32,
+        description: String,
+        owner_id: Uuid,
+        owner_name: String,
+        currency: CurrencyDTO,
+        total_amount: f64,
+        spent_at: DateTime<Utc>,
+        created_at: DateTime<Utc>,
+        updated_at: DateTime<Utc>,
+        splits: Vec<ExportedSplitDTO>,
+    }
+
+    /// DTO containing the export of a pot.
+    #[derive(ToSchema, Serialize)]
+    pub struct PotExportDTO {
+        id: i32,
+        name: String,
+        archived: bool,
+        archived_at: Option<DateTime<Utc>>,
+        /// All expenses of the pot, ordered by the date they were spent on.
+        expenses: Vec<ExportedExpenseDTO>,
+    }
+
+    impl PotExportDTO {
+        /// Creates the DTO from the given export.
+        pub fn from(export: &PotExport) -> Self {
+            let user_name = |user_id: Uuid| export.user_name(user_id).unwrap_or_default().to_string();
+
+            Self {
+                id: export.pot().id(),
+                name: export.pot().name().to_string(),
+                archived: export.pot().is_archived(),
+                archived_at: export.pot().archived_at(),
+                expenses: export
+                    .expenses()
+                    .iter()
+                    .map(|(expense, splits, currency)| ExportedExpenseDTO {
+                        id: expense.id(),
+                        description: expense.description().to_string(),
+                        owner_id: expense.owner_id(),
+                        owner_name: user_name(expense.owner_id()),
+                        currency: CurrencyDTO::from(currency.clone()),
+                        total_amount: from_minor_units(
+                            expense.total_amount(),
+                            currency.minor_units(),
+                        ),
+                        spent_at: expense.spent_at(),
+                        created_at: expense.created_at(),
+                        updated_at: expense.updated_at(),
+                        splits: splits
+                            .iter()
+                            .map(|split| ExportedSplitDTO {
+                                user_id: split.user_id(),
+                                user_name: user_name(split.user_id()),
+                                amount: from_minor_units(split.amount(), currency.minor_units()),
+                                is_paid: split.is_paid(),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    /// Writes the given rows as CSV.
+    fn to_csv(rows: Vec<Vec<String>>) -> Vec<u8> {
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        for row in rows {
+            writer
+                .write_record(row)
+                .expect("Writing CSV into memory can't fail");
+        }
+
+        writer.into_inner().expect("Writing CSV into memory can't fail")
+    }
+
+    /// Prefixes text that spreadsheet applications would evaluate as a formula with `'`, so a
+    /// description like `=HYPERLINK(..)` is shown as it was entered.
+    pub(crate) fn escape_csv_cell(cell: &str) -> String {
+        if cell.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+            format!("'{}", cell)
+        } else {
+            cell.to_string()
+        }
+    }
+
+    /// Creates the CSV rows of all expenses of the given export, one per split. Amounts are
+    /// written with all digits of the minor unit, e.g. 10.50.
+    fn to_csv_rows(export: &PotExport) -> Vec<u8> {
+        let user_name = |user_id: Uuid| escape_csv_cell(export.user_name(user_id).unwrap_or_default());
+
+        to_csv(
+            export
+                .expenses()
+                .iter()
+                .flat_map(|(expense, splits, currency)| {
+                    splits.iter().map(move |split| {
+                        vec![
+                            expense.id().to_string(),
+                            expense.spent_at().to_rfc3339(),
+                            escape_csv_cell(expense.description()),
+                            expense.owner_id().to_string(),
+                            user_name(expense.owner_id()),
+                            escape_csv_cell(currency.code().unwrap_or(currency.symbol())),
+                            format_minor_units(expense.total_amount(), currency.minor_units()),
+                            split.user_id().to_string(),
+                            user_name(split.user_id()),
+                            format_minor_units(split.amount(), currency.minor_units()),
+                            split.is_paid().to_string(),
+                        ]
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// The delimiter of an imported CSV, if none is given.
+    const DEFAULT_DELIMITER: char = ',';
+
+    /// The format of the dates in an imported CSV, if none is given.
+    const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+    /// DTO mapping the fields of an expense to the columns of a CSV, by the names in its header.
+    #[derive(ToSchema, Serialize, Deserialize)]
+    pub struct ColumnMappingDTO {
+        /// The day the money has been spent. If not mapped, the time of the import is used.
+        date: Option<String>,
+        description: String,
+        /// The total amount in major units, e.g. 10.05.
+        amount: String,
+        /// The code or symbol of the currency. If not mapped, the default currency of the pot is
+        /// used.
+        currency: Option<String>,
+        /// The name or id of the user who paid. If not mapped, the bearer is the payer.
+        payer: Option<String>,
+        /// The names or ids of the users the amount is split equally among, separated by `;`.
+        /// If not mapped, the amount is split among all users of the pot.
+        participants: Option<String>,
+    }
+
+    /// DTO used to import expenses from a CSV into a pot.
+    #[derive(ToSchema, Serialize, Deserialize)]
+    pub struct ImportDTO {
+        /// The content of the CSV, including a header.
+        csv: String,
+        columns: ColumnMappingDTO,
+        /// If true, the rows are only validated and no expense is created.
+        #[serde(default)]
+        dry_run: bool,
+        /// Defaults to `,`.
+        delimiter: Option<char>,
+        /// The format of the dates, e.g. `%d.%m.%Y`. Defaults to `%Y-%m-%d`.
+        date_format: Option<String>,
+    }
+
+    impl ImportDTO {
+        /// Converts the DTO to the mapping used by the import.
+        fn to_mapping(&self) -> Result<ColumnMapping, ApiResponse<String>> {
+            let delimiter = self.delimiter.unwrap_or(DEFAULT_DELIMITER);
+
+            if !delimiter.is_ascii() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json("The delimiter must be an ASCII character".to_string()),
+                ));
+            }
+
+            Ok(ColumnMapping::new(
+                self.columns.date.clone(),
+                self.columns.description.clone(),
+                self.columns.amount.clone(),
+                self.columns.currency.clone(),
+                self.columns.payer.clone(),
+                self.columns.participants.clone(),
+                delimiter as u8,
+                self.date_format
+                    .clone()
+                    .unwrap_or(DEFAULT_DATE_FORMAT.to_string()),
+            ))
+        }
+    }
+
+    /// DTO describing a row of an import that could not be turned into an expense.
+    #[derive(ToSchema, Serialize)]
+    pub struct ImportRowErrorDTO {
+        /// The line of the row in the CSV, the header being line 1.
+        row: u64,
+        message: String,
+    }
+
+    /// DTO describing the result of an import.
+    #[derive(ToSchema, Serialize)]
+    pub struct ImportResultDTO {
+        dry_run: bool,
+        /// The number of rows that can be imported.
+        valid_rows: usize,
+        /// The rows that can't be imported. If there are any, no expense is created.
+        errors: Vec<ImportRowErrorDTO>,
+        /// The expenses that have been created. Empty for a dry run or if there are errors.
+        created: Vec<ExpenseDTO>,
+    }
+
+    impl ImportResultDTO {
+        /// Creates the DTO from the given result.
+        fn from(
+            result: &ImportResult,
+            dry_run: bool,
+            requester_id: Uuid,
+            settled_amounts: &SettledAmounts,
+        ) -> Self {
+            Self {
+                dry_run,
+                valid_rows: result.valid_rows(),
+                errors: result
+                    .errors()
+                    .iter()
+                    .map(|error| ImportRowErrorDTO {
+                        row: error.row(),
+                        message: error.message().to_string(),
+                    })
+                    .collect(),
+                created: result
+                    .created()
+                    .iter()
+                    .map(|(expense, splits, currency)| {
+                        ExpenseDTO::from(
+                            expense.clone(),
+                            currency.clone(),
+                            splits.clone(),
+                            requester_id,
+                            settled_amounts,
+                        )
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    /// DTO used when working with existing settlements.
+    #[derive(ToSchema, Serialize)]
+    pub struct SettlementDTO {
+        id: i32,
+        pot_id: i32,
+        payer_id: Uuid,
+        receiver_id: Uuid,
+        amount: f64,
+        currency: CurrencyDTO,
+        created_at: DateTime<Utc>,
+        /// Set once the pot or all splits the settlement covered have been paid. Cleared
+        /// settlements no longer count towards the balances of the pot.
+        cleared_at: Option<DateTime<Utc>>,
+        /// The part of the amount covering splits that have been paid since, which no longer
+        /// counts towards the balances of the pot.
+        cleared_amount: f64,
+    }
+
+    impl SettlementDTO {
+        /// Creates a new SettlementDTO from a db Settlement.
+        pub fn from(settlement: Settlement, currency: CurrencyDTO) -> Self {
+            Self {
+                id: settlement.id(),
+                pot_id: settlement.pot_id(),
+                payer_id: settlement.payer_id(),
+                receiver_id: settlement.receiver_id(),
+                amount: from_minor_units(settlement.amount(), currency.minor_units()),
+                cleared_amount: from_minor_units(
+                    settlement.cleared_amount(),
+                    currency.minor_units(),
+                ),
+                currency,
+                created_at: settlement.created_at(),
+                cleared_at: settlement.cleared_at(),
+            }
+        }
+
+        /// Create a vec<SettlementDTO> from a vec<Settlement>.
+        pub fn from_vec(settlements: Vec<Settlement>, currency_vec: Vec<CurrencyDTO>) -> Vec<Self> {
+            let mut dtos = vec![];
+
+            for settlement in settlements {
+                let currency = currency_vec
+                    .iter()
+                    .find(|c| c.id() == settlement.currency_id());
+
+                if let Some(currency) = currency {
+                    dtos.push(SettlementDTO::from(settlement, currency.clone()))
+                }
+            }
+
+            dtos
+        }
+    }
+
+    /// DTO used to record a payment from one user of a pot to another one.
+    #[derive(ToSchema, Serialize, Deserialize)]
+    pub struct NewSettlementDTO {
+        /// The user who paid. Either payer or receiver must be the bearer.
+        payer_id: Uuid,
+        /// The user who received the money.
+        receiver_id: Uuid,
+        amount: f64,
+        currency_id: i32,
+    }
+
+    impl NewSettlementDTO {
+        /// Converts the DTO to the db object, rounding the amount to the given minor units.
+        fn to_db(&self, pot_id: i32, minor_units: i32) -> NewSettlement {
+            NewSettlement::new(
+                pot_id,
+                self.payer_id,
+                self.receiver_id,
+                to_minor_units(self.amount, minor_units),
+                self.currency_id,
+            )
+        }
+
+        pub fn currency_id(&self) -> i32 {
+            self.currency_id
+        }
+    }
+
     /// Creates a pot from the given DTO for the bearer.
     #[utoipa::path(
         post,
@@ -160,6 +980,7 @@ pub mod pot_api {
         tag = "Pots",
         responses(
             (status = 201, description = "The pot has been created", body = PotDTO),
+            (status = 400, description = "The name or the description is too long."),
         ),
         request_body = NewPotDTO,
         security(
@@ -168,10 +989,10 @@ pub mod pot_api {
     )]
     pub async fn create_pot(
         State(pot_api_state): State<Arc<PotApiState>>,
-        parts: Parts,
+        auth_user: AuthUser,
         Json(new_pot): Json<NewPotDTO>,
     ) -> Result<ApiResponse<PotDTO>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         let result = pot_api_state
             .pot_service
@@ -185,7 +1006,7 @@ pub mod pot_api {
                 result.0,
                 CurrencyDTO::from(result.1),
                 UserDTO::from_vec(result.2),
-                0.0
+                PotBalance::new(vec![], Some(0))
             )),
         ))
     }
@@ -204,9 +1025,9 @@ pub mod pot_api {
     )]
     pub async fn get_pots(
         State(pot_api_state): State<Arc<PotApiState>>,
-        parts: Parts,
+        auth_user: AuthUser,
     ) -> Result<ApiResponse<Vec<PotDTO>>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         let loaded_pots = pot_api_state
             .pot_service
@@ -220,39 +1041,48 @@ pub mod pot_api {
             .await
             .map_err(check_error)?;
 
-        let all_currencies = CurrencyDTO::from_vec(all_currencies);
-
-        let mut loaded_pots_with_balance = vec![];
+        let just_pots: Vec<&Pot> = loaded_pots.iter().map(|(pot, _)| pot).collect();
 
-        for (pot, users) in loaded_pots {
-            let balance = pot_api_state
-                .expense_service
-                .get_pot_net_balance(pot.id(), subject_id)
-                .await
-                .map_err(check_error)?;
+        let mut balances = pot_api_state
+            .expense_service
+            .get_pot_net_balances(&just_pots, &all_currencies, subject_id)
+            .await
+            .map_err(check_error)?;
 
-            loaded_pots_with_balance.push((pot, users, balance))
-        }
+        let loaded_pots_with_balance = loaded_pots
+            .into_iter()
+            .map(|(pot, users)| {
+                let balance = balances
+                    .remove(&pot.id())
+                    .unwrap_or_else(|| PotBalance::new(vec![], Some(0)));
+                (pot, users, balance)
+            })
+            .collect();
 
         Ok((
             StatusCode::OK,
-            Json(PotDTO::from_vec(loaded_pots_with_balance, all_currencies)),
+            Json(PotDTO::from_vec(
+                loaded_pots_with_balance,
+                CurrencyDTO::from_vec(all_currencies),
+            )),
         ))
     }
 
-    /// Adds the given users to the pot if Bearer is the owner of that pot.
+    /// Updates the name, default currency or description of the given pot. Only the owner of the
+    /// pot can do this.
     #[utoipa::path(
-            put,
+            patch,
             path = "/pots/{pot_id}",
             tag = "Pots",
             responses(
-                (status = 200, description = "The users have successfully been added to the pot"),
-                (status = 403, description = "The users could not be added due to the caller not being the owner of the given pot."),
-                (status = 409, description = "The users were already added to the pot."),
-                (status = 423, description = "The users can't be added, as the pot is archived."),
+                (status = 200, description = "The pot has been updated.", body = PotDTO),
+                (status = 400, description = "The update is invalid, e.g. because it is empty or the name is too long."),
+                (status = 403, description = "The pot could not be updated due to the caller not being the owner of the given pot."),
+                (status = 404, description = "The pot or the currency could not be found."),
+                (status = 423, description = "The pot can't be updated, as it is archived."),
                 (status = 500, description = "An internal server error occurred")
             ),
-            request_body = Vec<AddUserToPotDTO>,
+            request_body = PotUpdateDTO,
             params(
                 ("pot_id" = i32, Path, description = "Pot database id for the pot.  ")
             ),
@@ -260,66 +1090,119 @@ pub mod pot_api {
                     ("bearer" = [])
             )
         )]
-    pub async fn add_users_to_pot(
+    pub async fn update_pot(
         State(pot_api_state): State<Arc<PotApiState>>,
         Path(pot_id): Path<i32>,
-        part: Parts,
-        Json(add_user_to_pot_dto): Json<Vec<AddUserToPotDTO>>,
-    ) -> Result<ApiResponse<String>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&part)?;
+        auth_user: AuthUser,
+        Json(pot_update): Json<PotUpdateDTO>,
+    ) -> Result<ApiResponse<PotDTO>, ApiResponse<String>> {
+        let subject_id = auth_user.id();
 
-        // create a vector of PotToUser so that they can be added to the database
-        let mut new_user_ids = vec![];
-        for user in add_user_to_pot_dto {
-            let new_user_id = user.user_id();
-            new_user_ids.push(PotToUser::new(pot_id, new_user_id));
+        if !pot_update.is_valid() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json("At least one setting of the pot must be updated".to_string()),
+            ));
         }
 
-        let result = pot_api_state
+        let (pot, currency, users) = pot_api_state
             .pot_service
-            .add_users_to_pot(new_user_ids, subject_id)
+            .update_pot(pot_id, pot_update.to_update(), subject_id)
             .await
             .map_err(check_error)?;
 
-        if !result {
-            return Ok((
-                StatusCode::FORBIDDEN,
-                Json(format!("Users could not be added to pot {}", pot_id)),
-            ));
-        }
+        let balance = pot_api_state
+            .expense_service
+            .get_pot_net_balance(pot_id, subject_id)
+            .await
+            .map_err(check_error)?;
 
         Ok((
             StatusCode::OK,
-            Json(format!("Users successfully added to pot {}", pot_id)),
+            Json(PotDTO::from(
+                pot,
+                CurrencyDTO::from(currency),
+                UserDTO::from_vec(users),
+                balance,
+            )),
         ))
     }
 
+    /// Invites the given users to the pot if Bearer is at least an admin of that pot. The users are only
+    /// added to the pot once they accept the invitation. Users that are already invited get
+    /// their invitation renewed.
     #[utoipa::path(
             put,
-            path = "/pots/{pot_id}/archive",
+            path = "/pots/{pot_id}",
             tag = "Pots",
             responses(
-                (status = 200, description = "The pot has been successfully archived."),
-                (status = 403, description = "The pot could not be archived due to the caller not being the owner of the given pot."),
-                (status = 409, description = "The pot was already archived."),
+                (status = 200, description = "The users have successfully been invited to the pot", body = Vec<PotInvitationDTO>),
+                (status = 403, description = "The users could not be invited due to the caller not being an admin of the given pot."),
+                (status = 404, description = "The pot or one of the users could not be found."),
+                (status = 409, description = "The users are already part of the pot."),
+                (status = 423, description = "The users can't be invited, as the pot is archived."),
                 (status = 500, description = "An internal server error occurred")
             ),
+            request_body = Vec<AddUserToPotDTO>,
             params(
-                ("pot_id" = i32, Path, description = "Pot database id for the pot")
+                ("pot_id" = i32, Path, description = "Pot database id for the pot.  ")
             ),
             security(
                     ("bearer" = [])
             )
-    )]
-    pub async fn archive(
+        )]
+    pub async fn add_users_to_pot(
         State(pot_api_state): State<Arc<PotApiState>>,
         Path(pot_id): Path<i32>,
-        part: Parts,
-    ) -> Result<ApiResponse<String>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&part)?;
+        auth_user: AuthUser,
+        Json(add_user_to_pot_dto): Json<Vec<AddUserToPotDTO>>,
+    ) -> Result<ApiResponse<Vec<PotInvitationDTO>>, ApiResponse<String>> {
+        let invitee_ids = add_user_to_pot_dto
+            .iter()
+            .map(AddUserToPotDTO::user_id)
+            .collect();
 
-        let result = pot_api_state
-            .pot_service
+        let invitations = pot_api_state
+            .invitation_service
+            .invite_users(pot_id, auth_user.id(), invitee_ids)
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(invitations.into_iter().map(PotInvitationDTO::from).collect()),
+        ))
+    }
+
+    /// Archives the given pot, so that it can't be changed anymore. Requires Bearer to be at least
+    /// an admin of the pot.
+    #[utoipa::path(
+            put,
+            path = "/pots/{pot_id}/archive",
+            tag = "Pots",
+            responses(
+                (status = 200, description = "The pot has been successfully archived."),
+                (status = 403, description = "The pot could not be archived due to the caller not being an admin of the given pot."),
+                (status = 404, description = "Indicates that the desired pot does not exists."),
+                (status = 409, description = "The pot was already archived."),
+                (status = 500, description = "An internal server error occurred")
+            ),
+            params(
+                ("pot_id" = i32, Path, description = "Pot database id for the pot")
+            ),
+            security(
+                    ("bearer" = [])
+            )
+    )]
+    pub async fn archive(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<String>, ApiResponse<String>> {
+        let subject_id = auth_user.id();
+
+        let result = pot_api_state
+            .pot_service
             .archive(pot_id, subject_id)
             .await
             .map_err(check_error)?;
@@ -343,13 +1226,15 @@ pub mod pot_api {
         ))
     }
 
+    /// Unarchives the given pot. Requires Bearer to be at least an admin of the pot.
     #[utoipa::path(
         put,
         path = "/pots/{pot_id}/unarchive",
         tag = "Pots",
         responses(
                 (status = 200, description = "The pot has been successfully unarchived."),
-                (status = 403, description = "The pot could not be unarchived due to the caller not being the owner of the given pot."),
+                (status = 403, description = "The pot could not be unarchived due to the caller not being an admin of the given pot."),
+                (status = 404, description = "Indicates that the desired pot does not exists."),
                 (status = 409, description = "The pot is not archived."),
                 (status = 500, description = "An internal server error occurred")
         ),
@@ -363,9 +1248,9 @@ pub mod pot_api {
     pub async fn unarchive(
         State(pot_api_state): State<Arc<PotApiState>>,
         Path(pot_id): Path<i32>,
-        part: Parts,
+        auth_user: AuthUser,
     ) -> Result<ApiResponse<String>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&part)?;
+        let subject_id = auth_user.id();
 
         let result = pot_api_state
             .pot_service
@@ -392,7 +1277,7 @@ pub mod pot_api {
         ))
     }
 
-    /// Marks the given pot as paid. Only the pot owner can do this.
+    /// Marks the given pot as paid. Only admins of the pot can do this.
     /// This is a function used to mark the pot as paid with a single button click.
     /// No restore is possible as of now. So the pot is considered final.
     #[utoipa::path(
@@ -401,7 +1286,8 @@ pub mod pot_api {
         tag = "Pots",
         responses(
                 (status = 200, description = "The pot has been successfully payed."),
-                (status = 403, description = "The pot could not be payed."),
+                (status = 403, description = "The pot could not be payed due to the caller not being an admin of the given pot."),
+                (status = 404, description = "Indicates that the desired pot does not exists."),
                 (status = 500, description = "An internal server error occurred")
         ),
         params(
@@ -414,28 +1300,197 @@ pub mod pot_api {
     pub async fn pay_pot(
         State(pot_api_state): State<Arc<PotApiState>>,
         Path(pot_id): Path<i32>,
-        part: Parts
+        auth_user: AuthUser
     ) -> Result<ApiResponse<String>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&part)?;
+        let subject_id = auth_user.id();
 
-        let result = pot_api_state.pot_service.pay_pot(pot_id, subject_id).await;
+        pot_api_state
+            .pot_service
+            .pay_pot(pot_id, subject_id)
+            .await
+            .map_err(check_error)?;
 
-        if result.is_ok() {
-            return Ok((StatusCode::OK, Json("Pot paid successfully".to_string())));
-        }
+        Ok((StatusCode::OK, Json("Pot paid successfully".to_string())))
+    }
 
-        Err((StatusCode::FORBIDDEN, Json("Pot could not be paid".to_string())))
+    /// Gets the transfers needed to clear all unpaid expenses of the given pot, grouped by
+    /// currency and converted into the default currency of the pot. Debts are simplified, so that
+    /// as few transfers as possible are necessary.
+    #[utoipa::path(
+        get,
+        path = "/pots/{pot_id}/settlement",
+        tag = "Pots",
+        responses(
+                (status = 200, description = "The settlement plan of the pot.", body = SettlementPlanDTO),
+                (status = 404, description = "Indicates that the desired pot does not exists."),
+                (status = 500, description = "An internal server error occurred")
+        ),
+        params(
+                ("pot_id" = i32, Path, description = "Pot database id for the pot")
+        ),
+        security(
+                    ("bearer" = [])
+        )
+    )]
+    pub async fn get_settlement_plan(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        auth_user: AuthUser
+    ) -> Result<ApiResponse<SettlementPlanDTO>, ApiResponse<String>> {
+        let subject_id = auth_user.id();
+
+        let plan = pot_api_state
+            .pot_service
+            .get_settlement_plan(pot_id, subject_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(SettlementPlanDTO::from(plan))))
     }
 
-    /// Removes the given user from the pot, if Bearer is the owner of that pot.
+    /// Gets the summary of the given pot per currency: the total spent, what each member paid for
+    /// and consumed, their open balances and the totals per month.
+    #[utoipa::path(
+        get,
+        path = "/pots/{pot_id}/summary",
+        tag = "Pots",
+        responses(
+                (status = 200, description = "The summary of the pot.", body = Vec<CurrencySummaryDTO>),
+                (status = 404, description = "Indicates that the desired pot does not exists."),
+                (status = 500, description = "An internal server error occurred")
+        ),
+        params(
+                ("pot_id" = i32, Path, description = "Pot database id for the pot")
+        ),
+        security(
+                    ("bearer" = [])
+        )
+    )]
+    pub async fn get_pot_summary(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        auth_user: AuthUser
+    ) -> Result<ApiResponse<Vec<CurrencySummaryDTO>>, ApiResponse<String>> {
+        let subject_id = auth_user.id();
+
+        let summaries = pot_api_state
+            .pot_service
+            .get_pot_summary(pot_id, subject_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(CurrencySummaryDTO::from_vec(summaries))))
+    }
+
+    /// Records a payment from one user of the pot to another one, e.g. a bank transfer. The
+    /// payment is counted against the balances of the pot. The bearer must either be the payer
+    /// or the receiver and at least a member of the pot.
+    #[utoipa::path(
+        post,
+        path = "/pots/{pot_id}/settlements",
+        tag = "Pots",
+        responses(
+                (status = 201, description = "The settlement has been recorded.", body = SettlementDTO),
+                (status = 400, description = "The settlement is invalid, e.g. because the amount is not positive."),
+                (status = 403, description = "The bearer is neither the payer nor the receiver or only a viewer of the pot."),
+                (status = 404, description = "The pot, the currency or one of the users could not be found."),
+                (status = 423, description = "The settlement can't be recorded, as the pot is archived."),
+                (status = 500, description = "An internal server error occurred")
+        ),
+        request_body = NewSettlementDTO,
+        params(
+                ("pot_id" = i32, Path, description = "Pot database id for the pot")
+        ),
+        security(
+                    ("bearer" = [])
+        )
+    )]
+    pub async fn create_settlement(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        auth_user: AuthUser,
+        Json(new_settlement): Json<NewSettlementDTO>,
+    ) -> Result<ApiResponse<SettlementDTO>, ApiResponse<String>> {
+        let subject_id = auth_user.id();
+
+        let currency = pot_api_state
+            .currency_service
+            .get_currency_by_id(new_settlement.currency_id())
+            .await
+            .map_err(check_error)?;
+
+        let (settlement, currency) = pot_api_state
+            .settlement_service
+            .create_settlement(
+                new_settlement.to_db(pot_id, currency.minor_units()),
+                subject_id,
+            )
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::CREATED,
+            Json(SettlementDTO::from(settlement, CurrencyDTO::from(currency))),
+        ))
+    }
+
+    /// Gets all settlements that have been recorded for the given pot.
+    #[utoipa::path(
+        get,
+        path = "/pots/{pot_id}/settlements",
+        tag = "Pots",
+        responses(
+                (status = 200, description = "The settlements of the pot.", body = Vec<SettlementDTO>),
+                (status = 404, description = "Indicates that the desired pot does not exists."),
+                (status = 500, description = "An internal server error occurred")
+        ),
+        params(
+                ("pot_id" = i32, Path, description = "Pot database id for the pot")
+        ),
+        security(
+                    ("bearer" = [])
+        )
+    )]
+    pub async fn get_settlements(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<Vec<SettlementDTO>>, ApiResponse<String>> {
+        let subject_id = auth_user.id();
+
+        let settlements = pot_api_state
+            .settlement_service
+            .get_settlements(pot_id, subject_id)
+            .await
+            .map_err(check_error)?;
+
+        let all_currencies = pot_api_state
+            .currency_service
+            .get_currencies()
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(SettlementDTO::from_vec(
+                settlements,
+                CurrencyDTO::from_vec(all_currencies),
+            )),
+        ))
+    }
+
+    /// Removes the given user from the pot, if Bearer is at least an admin of that pot and has a
+    /// higher role than the user. Users with an open balance in the pot can't be removed, until
+    /// it is settled.
     #[utoipa::path(
             put,
             path = "/pots/{pot_id}/remove_user",
             tag = "Pots",
             responses(
                 (status = 204, description = "The user has successfully been removed from the pot"),
-                (status = 403, description = "The user could not be removed due to the caller not being the owner of the given pot."),
+                (status = 403, description = "The user could not be removed due to the caller not having a higher role than the user, at least admin."),
                 (status = 404, description = "The user is not part of the pot."),
+                (status = 409, description = "The user is the owner of the pot or still has an open balance in it."),
                 (status = 423, description = "The user can't be removed, as the pot is archived."),
                 (status = 500, description = "An internal server error occurred")
             ),
@@ -450,10 +1505,10 @@ pub mod pot_api {
     pub async fn remove_user_from_pot(
         State(pot_api_state): State<Arc<PotApiState>>,
         Path(pot_id): Path<i32>,
-        part: Parts,
+        auth_user: AuthUser,
         Json(remove_user_from_pot_dto): Json<RemoveUserFromPotDTO>,
     ) -> Result<ApiResponse<String>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&part)?;
+        let subject_id = auth_user.id();
         let new_user_id = remove_user_from_pot_dto.user_id();
 
         let result = pot_api_state
@@ -481,6 +1536,148 @@ pub mod pot_api {
         ))
     }
 
+    /// Removes the Bearer from the pot. Members with an open balance in the pot can't leave it,
+    /// until it is settled. The owner can't leave their pot.
+    #[utoipa::path(
+            post,
+            path = "/pots/{pot_id}/leave",
+            tag = "Pots",
+            responses(
+                (status = 204, description = "The Bearer has left the pot"),
+                (status = 404, description = "The pot could not be found or the Bearer is not part of it."),
+                (status = 409, description = "The Bearer is the owner of the pot or still has an open balance in it."),
+                (status = 423, description = "The Bearer can't leave the pot, as it is archived."),
+                (status = 500, description = "An internal server error occurred")
+            ),
+            params(
+                ("pot_id" = i32, Path, description = "Pot database id for the pot.  ")
+            ),
+            security(
+                    ("bearer" = [])
+            )
+    )]
+    pub async fn leave_pot(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<String>, ApiResponse<String>> {
+        pot_api_state
+            .pot_service
+            .leave_pot(pot_id, auth_user.id())
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::NO_CONTENT,
+            Json(format!("User {} has left pot {}", auth_user.id(), pot_id)),
+        ))
+    }
+
+    /// Gets the members of the given pot together with their roles, starting with the owner.
+    #[utoipa::path(
+            get,
+            path = "/pots/{pot_id}/members",
+            tag = "Pots",
+            responses(
+                (status = 200, description = "The members of the pot.", body = Vec<PotMemberDTO>),
+                (status = 404, description = "Indicates that the desired pot does not exists."),
+                (status = 500, description = "An internal server error occurred")
+            ),
+            params(
+                ("pot_id" = i32, Path, description = "Pot database id for the pot.  ")
+            ),
+            security(
+                    ("bearer" = [])
+            )
+    )]
+    pub async fn get_pot_members(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<Vec<PotMemberDTO>>, ApiResponse<String>> {
+        let members = pot_api_state
+            .pot_service
+            .get_members(pot_id, auth_user.id())
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(PotMemberDTO::from_vec(members))))
+    }
+
+    /// Changes the role of the given member of the pot. Bearer must be at least an admin of the
+    /// pot and can only change roles below their own. The owner can't be set this way, the
+    /// ownership has to be transferred instead.
+    #[utoipa::path(
+            put,
+            path = "/pots/{pot_id}/members/{user_id}/role",
+            tag = "Pots",
+            responses(
+                (status = 200, description = "The role has been changed.", body = PotMemberDTO),
+                (status = 400, description = "The role can't be set, as it is the owner."),
+                (status = 403, description = "The caller is not allowed to change the role of the user or to hand out the role."),
+                (status = 404, description = "The pot could not be found or the user is not part of it."),
+                (status = 500, description = "An internal server error occurred")
+            ),
+            request_body = SetPotRoleDTO,
+            params(
+                ("pot_id" = i32, Path, description = "Pot database id for the pot.  "),
+                ("user_id" = Uuid, Path, description = "The id of the member.")
+            ),
+            security(
+                    ("bearer" = [])
+            )
+    )]
+    pub async fn set_member_role(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path((pot_id, user_id)): Path<(i32, Uuid)>,
+        auth_user: AuthUser,
+        Json(set_pot_role_dto): Json<SetPotRoleDTO>,
+    ) -> Result<ApiResponse<PotMemberDTO>, ApiResponse<String>> {
+        let (member, role) = pot_api_state
+            .pot_service
+            .set_member_role(pot_id, auth_user.id(), user_id, set_pot_role_dto.role.to_db())
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(PotMemberDTO::from(member, role))))
+    }
+
+    /// Transfers the ownership of the pot to another member. Only the owner can do this. The
+    /// previous owner stays part of the pot as an admin.
+    #[utoipa::path(
+            put,
+            path = "/pots/{pot_id}/owner",
+            tag = "Pots",
+            responses(
+                (status = 200, description = "The ownership has been transferred, returns the members of the pot.", body = Vec<PotMemberDTO>),
+                (status = 400, description = "The new owner already owns the pot."),
+                (status = 403, description = "The caller is not the owner of the pot."),
+                (status = 404, description = "The pot could not be found or the new owner is not part of it."),
+                (status = 500, description = "An internal server error occurred")
+            ),
+            request_body = TransferOwnershipDTO,
+            params(
+                ("pot_id" = i32, Path, description = "Pot database id for the pot.  ")
+            ),
+            security(
+                    ("bearer" = [])
+            )
+    )]
+    pub async fn transfer_ownership(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        auth_user: AuthUser,
+        Json(transfer_ownership_dto): Json<TransferOwnershipDTO>,
+    ) -> Result<ApiResponse<Vec<PotMemberDTO>>, ApiResponse<String>> {
+        let members = pot_api_state
+            .pot_service
+            .transfer_ownership(pot_id, auth_user.id(), transfer_ownership_dto.owner_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(PotMemberDTO::from_vec(members))))
+    }
+
     /// Adds a new expense in the name of the user from the Bearer token to the pot with the given
     /// pot_id if it exists.
     #[utoipa::path(
@@ -489,6 +1686,8 @@ pub mod pot_api {
         tag = "Pots",
         responses(
             (status = 201, description = "Indicates that the expense has been created for the given pot.",body = ExpenseDTO),
+            (status = 400, description = "The splits are invalid, e.g. because they don't add up to the total amount."),
+            (status = 403, description = "The user is only a viewer of the pot."),
             (status = 404, description = "Indicates that the pot for this expense does not exist."),
             (status = 423, description = "The user can't be added, as the pot is archived."),
         ),
@@ -503,10 +1702,10 @@ pub mod pot_api {
     pub async fn add_expense(
         State(pot_api_state): State<Arc<PotApiState>>,
         Path(pot_id): Path<i32>,
-        part: Parts,
+        auth_user: AuthUser,
         Json(new_expense): Json<NewExpenseDTO>,
     ) -> Result<ApiResponse<ExpenseDTO>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&part)?;
+        let subject_id = auth_user.id();
 
         let loaded_pot = pot_api_state
             .pot_service
@@ -522,7 +1721,8 @@ pub mod pot_api {
             .expense_service
             .create_expense(
                 new_expense.to_db(loaded_pot.id(), subject_id),
-                new_expense.splits_to_new_db(),
+                new_expense.total_amount(),
+                new_expense.split_mode()?,
             )
             .await
             .map_err(check_error)?;
@@ -531,13 +1731,185 @@ pub mod pot_api {
         let splits = expense_splits_result.1;
         let currency = expense_splits_result.2;
 
+        let settled_amounts = pot_api_state
+            .expense_service
+            .get_settled_amounts(pot_id, subject_id)
+            .await
+            .map_err(check_error)?;
+
         Ok((
             StatusCode::CREATED,
-            Json(ExpenseDTO::from(expense, currency, splits, subject_id)),
+            Json(ExpenseDTO::from(expense, currency, splits, subject_id, &settled_amounts)),
         ))
     }
 
-    /// Gets the sum of all expenses for the given user of the given pot.
+    /// Imports the expenses of the given CSV into the given pot, e.g. from a bank statement or
+    /// another app. The columns of the CSV are mapped to the fields of an expense by the names in
+    /// its header. Each expense is split equally among its participants.
+    /// All rows are validated first. Only if all of them are valid, the expenses are created,
+    /// all at once. With `dry_run`, only the validation takes place.
+    #[utoipa::path(
+        post,
+        path = "/pots/{pot_id}/import",
+        tag = "Pots",
+        responses(
+            (status = 200, description = "All rows are valid, no expense has been created as it is a dry run.", body = ImportResultDTO),
+            (status = 201, description = "The expenses have been created.", body = ImportResultDTO),
+            (status = 400, description = "Some rows are invalid, they are listed in the errors. No expense has been created.", body = ImportResultDTO),
+            (status = 404, description = "Indicates that the desired pot does not exists."),
+            (status = 423, description = "The expenses can't be imported, as the pot is archived."),
+            (status = 500, description = "An internal server error occurred")
+        ),
+        request_body = ImportDTO,
+        params(
+            ("pot_id" = i32, Path, description = "Pot database id for the pot")
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn import_expenses(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        auth_user: AuthUser,
+        Json(import): Json<ImportDTO>,
+    ) -> Result<ApiResponse<ImportResultDTO>, ApiResponse<String>> {
+        let subject_id = auth_user.id();
+
+        let result = pot_api_state
+            .import_service
+            .import_expenses(
+                pot_id,
+                &import.csv,
+                &import.to_mapping()?,
+                import.dry_run,
+                subject_id,
+            )
+            .await
+            .map_err(check_error)?;
+
+        let settled_amounts = pot_api_state
+            .expense_service
+            .get_settled_amounts(pot_id, subject_id)
+            .await
+            .map_err(check_error)?;
+
+        let status = if !result.errors().is_empty() {
+            StatusCode::BAD_REQUEST
+        } else if import.dry_run {
+            StatusCode::OK
+        } else {
+            StatusCode::CREATED
+        };
+
+        Ok((
+            status,
+            Json(ImportResultDTO::from(
+                &result,
+                import.dry_run,
+                subject_id,
+                &settled_amounts,
+            )),
+        ))
+    }
+
+    /// Exports all expenses of the given pot with their splits, currencies, the names of the
+    /// users and whether the splits have been paid. Archived pots can be exported as well.
+    /// As CSV, each row describes a single split.
+    #[utoipa::path(
+        get,
+        path = "/pots/{pot_id}/export",
+        tag = "Pots",
+        responses(
+            (
+                status = 200,
+                description = "The export of the pot",
+                content(
+                    (PotExportDTO = "application/json"),
+                    (String = "text/csv")
+                )
+            ),
+            (status = 404, description = "Indicates that the desired pot does not exists."),
+            (status = 500, description = "An internal server error occurred")
+        ),
+        params(
+            ("pot_id" = i32, Path, description = "Pot database id for the pot"),
+            ExportParams
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn export_pot(
+        State(pot_api_state): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        Query(params): Query<ExportParams>,
+        auth_user: AuthUser,
+    ) -> Result<Response, ApiResponse<String>> {
+        let subject_id = auth_user.id();
+
+        if params.format == ExportFormat::Json {
+            let export = pot_api_state
+                .pot_service
+                .export_pot(pot_id, subject_id)
+                .await
+                .map_err(check_error)?;
+
+            return Ok((StatusCode::OK, Json(PotExportDTO::from(&export))).into_response());
+        }
+
+        // the first batch is loaded right away, so a missing pot is still reported as such
+        let first_batch = pot_api_state
+            .pot_service
+            .export_pot_batch(pot_id, subject_id, None)
+            .await
+            .map_err(check_error)?;
+
+        let first_rows = [
+            to_csv(vec![CSV_HEADER.map(str::to_string).to_vec()]),
+            to_csv_rows(&first_batch),
+        ];
+
+        // the following batches are only loaded once the client has read the previous ones
+        let pot_service = pot_api_state.pot_service.clone();
+        let next_rows = futures_util::stream::try_unfold(
+            first_batch.next_position(),
+            move |position| {
+                let pot_service = pot_service.clone();
+
+                async move {
+                    let Some(position) = position else {
+                        return Ok(None);
+                    };
+
+                    let batch = pot_service
+                        .export_pot_batch(pot_id, subject_id, Some(position))
+                        .await
+                        .map_err(|err| io::Error::other(err.to_string()))?;
+
+                    Ok(Some((to_csv_rows(&batch), batch.next_position())))
+                }
+            },
+        );
+
+        let rows = futures_util::stream::iter(first_rows.map(Ok::<_, io::Error>)).chain(next_rows);
+
+        Ok((
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"pot-{}.csv\"", pot_id),
+                ),
+            ],
+            Body::from_stream(rows),
+        )
+            .into_response())
+    }
+
+    /// Gets all expenses of the given pot. The sum of each expense is given for the requesting
+    /// user. See `GET /pots/{pot_id}/expenses` for a sorted, filtered and paginated listing.
     #[utoipa::path(
         get,
         path = "/pots/{pot_id}",
@@ -563,9 +1935,9 @@ pub mod pot_api {
     pub async fn get_pot_expenses(
         State(pot_api_service): State<Arc<PotApiState>>,
         Path(pot_id): Path<i32>,
-        parts: Parts,
+        auth_user: AuthUser,
     ) -> Result<ApiResponse<Vec<ExpenseDTO>>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         let result = pot_api_service
             .expense_service
@@ -573,9 +1945,103 @@ pub mod pot_api {
             .await
             .map_err(check_error)?;
 
+        let settled_amounts = pot_api_service
+            .expense_service
+            .get_settled_amounts(pot_id, subject_id)
+            .await
+            .map_err(check_error)?;
+
+        Ok((
+            StatusCode::OK,
+            Json(ExpenseDTO::from_vec(result, subject_id, &settled_amounts)),
+        ))
+    }
+
+    /// Gets a page of the expenses of the given pot, sorted and filtered by the given
+    /// parameters. The sum of each expense is given for the requesting user.
+    #[utoipa::path(
+        get,
+        path = "/pots/{pot_id}/expenses",
+        tag = "Pots",
+        responses(
+            (
+                status = 200,
+                description = "The page of expenses for the pot with the given id",
+                body = ExpensePageDTO
+            ),
+            (
+                status = 400,
+                description = "Indicates that the page or page size is invalid"
+            ),
+            (
+                status = 404,
+                description = "Indicates that the desired pot does not exists"
+            )
+        ),
+        params(
+            ("pot_id" = i32, Path, description = "Pot database id for the pot.  "),
+            ExpenseListParams
+        ),
+        security(
+            ("bearer" = [])
+        )
+    )]
+    pub async fn get_pot_expense_page(
+        State(pot_api_service): State<Arc<PotApiState>>,
+        Path(pot_id): Path<i32>,
+        Query(params): Query<ExpenseListParams>,
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<ExpensePageDTO>, ApiResponse<String>> {
+        let subject_id = auth_user.id();
+
+        let filter = ExpenseFilter::new(
+            params.owner_id,
+            params.participant_id,
+            params.currency_id,
+            params.paid,
+            params.description,
+            params.spent_from,
+            params.spent_to,
+        );
+
+        let field = match params.sort_by {
+            ExpenseSortBy::SpentAt => ExpenseSortField::SpentAt,
+            ExpenseSortBy::CreatedAt => ExpenseSortField::CreatedAt,
+            ExpenseSortBy::TotalAmount => ExpenseSortField::TotalAmount,
+            ExpenseSortBy::Description => ExpenseSortField::Description,
+        };
+        let sort = ExpenseSort::new(field, matches!(params.order, SortOrder::Desc));
+
+        let result = pot_api_service
+            .expense_service
+            .get_expense_page(
+                pot_id,
+                subject_id,
+                &filter,
+                &sort,
+                &Pagination::new(params.page, params.per_page),
+            )
+            .await
+            .map_err(check_error)?;
+
+        let settled_amounts = pot_api_service
+            .expense_service
+            .get_settled_amounts(pot_id, subject_id)
+            .await
+            .map_err(check_error)?;
+
         Ok((
             StatusCode::OK,
-            Json(ExpenseDTO::from_vec(result, subject_id)),
+            Json(ExpensePageDTO {
+                expenses: ExpenseDTO::from_vec(
+                    result.expenses().clone(),
+                    subject_id,
+                    &settled_amounts,
+                ),
+                page: params.page,
+                per_page: params.per_page,
+                total_count: result.total_count(),
+            }),
         ))
     }
 
@@ -601,9 +2067,9 @@ pub mod pot_api {
     pub async fn delete_pot(
         State(pot_api_service): State<Arc<PotApiState>>,
         Path(pot_id): Path<i32>,
-        parts: Parts,
+        auth_user: AuthUser,
     ) -> Result<ApiResponse<String>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         pot_api_service
             .pot_service
@@ -617,3 +2083,86 @@ pub mod pot_api {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::auth::{AuthUser, ClaimNames};
+    use crate::pot_api::pot_api::{escape_csv_cell, get_pots, PotApiState};
+    use axum::extract::State;
+    use expense_tracker_db::expenses::expenses::NewExpense;
+    use expense_tracker_db::settlements::settlements::NewSettlement;
+    use expense_tracker_db::test_support::counting_pool;
+    use expense_tracker_services::expense_service::expense_service;
+    use expense_tracker_services::expense_service::expense_service::SplitMode;
+    use expense_tracker_services::settlement_service::settlement_service;
+    use expense_tracker_services::test_support::test_support::TestFixture;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use utoipa::r#gen::serde_json::json;
+
+    #[test]
+    fn test_escape_csv_cell() {
+        for cell in ["=1+1", "+49 123", "-2", "@SUM(A1:A2)", "\tTab", "\rReturn"] {
+            assert_eq!(escape_csv_cell(cell), format!("'{}", cell));
+        }
+
+        for cell in ["Dinner", "", "1+1", "Mail: a@example.com"] {
+            assert_eq!(escape_csv_cell(cell), cell);
+        }
+    }
+
+    /// Listing the pots of a user, including their members, currencies and balances, runs the
+    /// same number of queries, regardless of the number of pots.
+    #[tokio::test]
+    #[ignore = "needs a database, see expense_tracker_db::test_support"]
+    async fn test_get_pots_query_count() {
+        let fixture = TestFixture::new(3).await;
+        let expense_service = expense_service::new_service(fixture.pool());
+        let settlement_service = settlement_service::new_service(fixture.pool());
+        let currency = fixture.currency();
+
+        let owner = fixture.user(0);
+        let members = [fixture.user(1), fixture.user(2)];
+
+        let query_count = Arc::new(AtomicUsize::new(0));
+        let state = Arc::new(PotApiState::new(counting_pool(query_count.clone()).await));
+        let auth_user = || {
+            AuthUser::from_claims(&json!({ "sub": owner.to_string() }), &ClaimNames::default())
+                .unwrap()
+        };
+
+        let mut counts = vec![];
+
+        for pot_count in 1..=10 {
+            let pot = fixture
+                .create_pot(&format!("Pot {}", pot_count), owner, &members)
+                .await;
+
+            expense_service
+                .create_expense(
+                    NewExpense::new(owner, pot.id(), "Dinner".to_string(), currency.id(), None),
+                    Some(30.0),
+                    SplitMode::Equal(vec![owner, members[0], members[1]]),
+                )
+                .await
+                .unwrap();
+
+            settlement_service
+                .create_settlement(
+                    NewSettlement::new(pot.id(), members[0], owner, 500, currency.id()),
+                    owner,
+                )
+                .await
+                .unwrap();
+
+            query_count.store(0, Ordering::SeqCst);
+            let (_, loaded_pots) = get_pots(State(state.clone()), auth_user()).await.unwrap();
+            counts.push(query_count.load(Ordering::SeqCst));
+
+            assert_eq!(loaded_pots.len(), pot_count);
+        }
+
+        // pots, members, currencies, settlements, expenses, splits and exchange rates
+        assert!(counts.iter().all(|count| *count == 7), "{:?}", counts);
+    }
+}
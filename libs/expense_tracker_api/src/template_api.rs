@@ -2,8 +2,7 @@ pub mod template_api {
     use std::collections::HashSet;
     use std::sync::Arc;
     use axum::extract::{Path, State};
-    use axum::http::request::Parts;
-    use axum::Json;
+        use axum::Json;
     use hyper::StatusCode;
     use serde::{Deserialize, Serialize};
     use utoipa::ToSchema;
@@ -15,7 +14,8 @@ pub mod template_api {
     use expense_tracker_db::template_pots::template_pots::{NewPotTemplate, PotTemplate};
     use expense_tracker_db::users::users::User;
     use expense_tracker_services::template_service::pot_template_service::{PotTemplateService, TemplateUpdate};
-    use crate::api::{check_error, get_sub_claim, ApiResponse};
+    use crate::api::{check_error, ApiResponse};
+    use crate::auth::auth::AuthUser;
     use crate::currency_api::currency_api::CurrencyDTO;
     use crate::user_api::user_api::UserDTO;
 
@@ -170,10 +170,10 @@ pub mod template_api {
     )]
     pub async fn create_pot_template(
         State(template_api_state): State<Arc<TemplateApiState>>,
-        parts: Parts,
+        auth_user: AuthUser,
         Json(new_pot_tempalte): Json<NewPotTemplateDTO>
     ) -> Result<ApiResponse<PotTemplateDTO>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         let result = template_api_state
             .pot_template_service
@@ -201,9 +201,9 @@ pub mod template_api {
     )]
     pub async fn get_pot_templates(
         State(template_api_state): State<Arc<TemplateApiState>>,
-        parts: Parts
+        auth_user: AuthUser
     ) -> Result<ApiResponse<Vec<PotTemplateDTO>>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         let result = template_api_state
             .pot_template_service
@@ -214,9 +214,7 @@ pub mod template_api {
         let result =
             Vec::from_iter(
                 result.into_iter()
-                    .map(|result_tuple|
-                        PotTemplateDTO::from(result_tuple)
-                    )
+                    .map(PotTemplateDTO::from)
             );
 
         Ok((
@@ -243,9 +241,9 @@ pub mod template_api {
     pub async fn get_pot_template_by_id(
         State(template_api_state): State<Arc<TemplateApiState>>,
         Path(template_id): Path<i32>,
-        parts: Parts
+        auth_user: AuthUser
     ) -> Result<ApiResponse<PotTemplateDTO>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         let result = template_api_state
             .pot_template_service
@@ -281,10 +279,10 @@ pub mod template_api {
     pub async fn add_users_to_template(
         State(template_api_state): State<Arc<TemplateApiState>>,
         Path(template_id): Path<i32>,
-        parts: Parts,
+        auth_user: AuthUser,
         Json(add_users_dto): Json<UserListDTO>
     ) -> Result<ApiResponse<String>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         template_api_state
             .pot_template_service
@@ -321,10 +319,10 @@ pub mod template_api {
     pub async fn remove_users_from_template(
         State(template_api_state): State<Arc<TemplateApiState>>,
         Path(template_id): Path<i32>,
-        parts: Parts,
+        auth_user: AuthUser,
         Json(remove_users_dto): Json<UserListDTO>
     ) -> Result<ApiResponse<String>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         template_api_state
             .pot_template_service
@@ -359,9 +357,9 @@ pub mod template_api {
     pub async fn delete_pot_template(
         State(template_api_state): State<Arc<TemplateApiState>>,
         Path(template_id): Path<i32>,
-        parts: Parts
+        auth_user: AuthUser
     ) -> Result<ApiResponse<String>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         template_api_state
             .pot_template_service
@@ -398,10 +396,10 @@ pub mod template_api {
     pub async fn update_template(
         State(template_api_state): State<Arc<TemplateApiState>>,
         Path(template_id): Path<i32>,
-        parts: Parts,
+        auth_user: AuthUser,
         Json(update_template_dto): Json<PotTemplateUpdateDTO>
     ) -> Result<ApiResponse<String>, ApiResponse<String>> {
-        let subject_id = get_sub_claim(&parts)?;
+        let subject_id = auth_user.id();
 
         if !update_template_dto.is_valid() {
             return Err((
@@ -1,11 +1,14 @@
 pub mod user_api {
-    use crate::api::{check_error, get_sub_claim, get_username, ApiResponse};
-    use axum::body::Body;
-    use axum::extract::State;
-    use axum::http::{Request, StatusCode};
+    use crate::api::{check_error, ApiResponse};
+    use crate::auth::auth::{unauthorized, AuthUser};
+    use axum::extract::{Request, State};
+    use axum::http::StatusCode;
+    use axum::middleware::Next;
+    use axum::response::Response;
     use axum::Json;
     use expense_tracker_db::setup::DbPool;
     use expense_tracker_db::users::users::User;
+    use log::warn;
     use expense_tracker_services::user_service::user_service;
     use expense_tracker_services::user_service::user_service::UserService;
     use serde::{Deserialize, Serialize};
@@ -17,7 +20,7 @@ pub mod user_api {
     /// Registers all functions of the Users API.
     pub fn register(pool: DbPool) -> OpenApiRouter {
         OpenApiRouter::new()
-            .routes(routes!(current_user))
+            .routes(routes!(current_user, update_current_user))
             .routes(routes!(get_users))
             .with_state(user_service::new_service(pool))
     }
@@ -26,16 +29,28 @@ pub mod user_api {
     #[derive(ToSchema, Serialize, Clone, Deserialize)]
     pub struct UserDTO {
         uuid: Uuid,
+        /// The display name chosen by the user, or the name given by the OIDC provider.
         name: String,
+        avatar_url: Option<String>,
+        /// Whether the user is a guest of a single pot without an account.
+        #[serde(default)]
+        is_guest: bool,
     }
 
     impl UserDTO {
-        pub fn new(uuid: Uuid, name: String) -> Self {
-            Self { uuid, name }
+        pub fn new(uuid: Uuid, name: String, avatar_url: Option<String>) -> Self {
+            Self { uuid, name, avatar_url, is_guest: false }
         }
 
         pub fn from(user: User) -> UserDTO {
-            Self::new(user.id(),user.name().to_string())
+            Self {
+                is_guest: user.is_guest(),
+                ..Self::new(
+                    user.id(),
+                    user.name().to_string(),
+                    user.avatar_url().map(str::to_string),
+                )
+            }
         }
 
         pub fn from_vec(users: Vec<User>) -> Vec<UserDTO> {
@@ -53,14 +68,70 @@ pub mod user_api {
         }
     }
 
-    /// Creates a new user from the given DTO.
+    /// The DTO representing the user of the bearer, including their whole profile.
+    #[derive(ToSchema, Serialize)]
+    pub struct CurrentUserDTO {
+        uuid: Uuid,
+        /// The display name chosen by the user, or the name given by the OIDC provider.
+        name: String,
+        /// The name given by the OIDC provider.
+        provider_name: String,
+        /// The display name chosen by the user, overriding the name given by the OIDC provider.
+        display_name: Option<String>,
+        email: Option<String>,
+        avatar_url: Option<String>,
+    }
+
+    impl CurrentUserDTO {
+        pub fn from(user: User) -> Self {
+            Self {
+                uuid: user.id(),
+                name: user.name().to_string(),
+                provider_name: user.provider_name().to_string(),
+                display_name: user.display_name().map(str::to_string),
+                email: user.email().map(str::to_string),
+                avatar_url: user.avatar_url().map(str::to_string),
+            }
+        }
+    }
+
+    /// DTO used to change the display name of the current user.
+    #[derive(ToSchema, Deserialize)]
+    pub struct CurrentUserUpdateDTO {
+        /// The display name overriding the name given by the OIDC provider. Null removes the
+        /// display name again.
+        #[schema(max_length = 24)]
+        display_name: Option<String>,
+    }
+
+    /// Keeps the stored profile of the user of a request in sync with the claims of their
+    /// token. The profile is written at most once per few minutes for each user and failures
+    /// are only logged, so they never fail the request.
+    pub(crate) async fn sync_profile(
+        State(service): State<UserService>,
+        auth_user: Result<AuthUser, ApiResponse<String>>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        if let Ok(auth_user) = auth_user
+            && let Err(err) = service.sync_profile(auth_user.id(), auth_user.profile()).await
+        {
+            warn!("Could not sync the profile of user {}: {}", auth_user.id(), err);
+        }
+
+        next.run(request).await
+    }
+
+    /// Gets the user of the bearer, creating them from the claims of the token on their first
+    /// call.
     #[utoipa::path(
             get,
             path = "/current_user",
             tag = "Users",
             responses(
-                (status = 200, description = "The user does already exist", body = UserDTO),
-                (status = 201, description = "The user", body = UserDTO),
+                (status = 200, description = "The user does already exist", body = CurrentUserDTO),
+                (status = 201, description = "The user", body = CurrentUserDTO),
+                (status = 401, description = "The token has no valid user id, or the user does not exist yet and the token has no name"),
                 (status = 500, description = "The server error")
             ),
             security(
@@ -69,32 +140,64 @@ pub mod user_api {
     )]
     pub async fn current_user(
         State(service): State<UserService>,
-        request: Request<Body>,
-    ) -> Result<ApiResponse<UserDTO>, ApiResponse<String>> {
-        let (parts, _) = request.into_parts();
-        let uuid = get_sub_claim(&parts)?;
+        auth_user: AuthUser,
+    ) -> Result<ApiResponse<CurrentUserDTO>, ApiResponse<String>> {
+        let uuid = auth_user.id();
         let user = service.get_user_by_id(uuid).await;
 
         if let Ok(user) = user {
-            return Ok((StatusCode::OK, Json(UserDTO::from(user))));
+            return Ok((StatusCode::OK, Json(CurrentUserDTO::from(user))));
         }
 
-        let user_name = get_username(&parts)?;
-        let new_user = User::new(uuid, user_name);
+        let user_name = auth_user
+            .name()
+            .ok_or_else(|| unauthorized("the token has no user name".to_string()))?;
+        let new_user = User::from_profile(uuid, user_name.to_string(), auth_user.profile());
 
         let res = service.create_user(new_user).await.map_err(check_error)?;
 
-        Ok((StatusCode::CREATED, Json(UserDTO::from(res))))
+        Ok((StatusCode::CREATED, Json(CurrentUserDTO::from(res))))
     }
 
-    /// Returns a list of a users registered in the system. Needs a bearer token to track
-    /// who requested it.
+    /// Sets the display name of the user of the bearer, which is shown instead of the name
+    /// given by the OIDC provider.
+    #[utoipa::path(
+            patch,
+            path = "/current_user",
+            tag = "Users",
+            request_body = CurrentUserUpdateDTO,
+            responses(
+                (status = 200, description = "The updated user", body = CurrentUserDTO),
+                (status = 400, description = "The display name is empty or too long"),
+                (status = 401, description = "The token has no valid user id"),
+                (status = 404, description = "The user does not exist yet"),
+                (status = 500, description = "The server error")
+            ),
+            security(
+                ("bearer" = [])
+            )
+    )]
+    pub async fn update_current_user(
+        State(service): State<UserService>,
+        auth_user: AuthUser,
+        Json(update): Json<CurrentUserUpdateDTO>,
+    ) -> Result<ApiResponse<CurrentUserDTO>, ApiResponse<String>> {
+        let user = service
+            .set_display_name(auth_user.id(), update.display_name)
+            .await
+            .map_err(check_error)?;
+
+        Ok((StatusCode::OK, Json(CurrentUserDTO::from(user))))
+    }
+
+    /// Returns a list of the users sharing a pot with the bearer, including the bearer. Admins
+    /// get all users registered in the system.
     #[utoipa::path(
         get,
         path = "/users",
         tag = "Users",
         responses(
-            (status = 200, description = "All users in the database", body = Vec<UserDTO>),
+            (status = 200, description = "The users sharing a pot with the bearer, or all users for admins", body = Vec<UserDTO>),
         ),
         security(
                 ("bearer" = [])
@@ -102,8 +205,14 @@ pub mod user_api {
     )]
     pub async fn get_users(
         State(service): State<UserService>,
+        auth_user: AuthUser,
     ) -> Result<ApiResponse<Vec<UserDTO>>, ApiResponse<String>> {
-        let res = service.get_users(None).await.map_err(check_error)?;
+        let res = if auth_user.is_admin() {
+            service.get_users(None).await
+        } else {
+            service.get_users_sharing_pots(auth_user.id()).await
+        }
+        .map_err(check_error)?;
         Ok((StatusCode::OK, Json(UserDTO::from_vec(res))))
     }
 }
@@ -0,0 +1,12 @@
+CREATE TABLE settlements
+(
+    id          SERIAL PRIMARY KEY,
+    pot_id      INTEGER REFERENCES pots (id)       ON DELETE CASCADE NOT NULL,
+    payer_id    UUID REFERENCES users (id)         ON DELETE CASCADE NOT NULL,
+    receiver_id UUID REFERENCES users (id)         ON DELETE CASCADE NOT NULL,
+    amount      DOUBLE PRECISION                   NOT NULL,
+    currency_id INTEGER REFERENCES currencies (id) NOT NULL,
+    created_at  TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    CONSTRAINT amount_positive_check CHECK (amount > 0),
+    CONSTRAINT payer_receiver_check CHECK (payer_id <> receiver_id)
+);
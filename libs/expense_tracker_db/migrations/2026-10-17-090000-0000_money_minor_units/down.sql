@@ -0,0 +1,26 @@
+ALTER TABLE settlements ADD COLUMN amount_major DOUBLE PRECISION;
+
+UPDATE settlements
+SET amount_major = settlements.amount / POWER(10, currencies.minor_units)
+FROM currencies
+WHERE currencies.id = settlements.currency_id;
+
+ALTER TABLE settlements DROP CONSTRAINT amount_positive_check;
+ALTER TABLE settlements ALTER COLUMN amount TYPE DOUBLE PRECISION USING amount_major;
+ALTER TABLE settlements DROP COLUMN amount_major;
+
+ALTER TABLE settlements
+ADD CONSTRAINT amount_positive_check CHECK (amount > 0);
+
+ALTER TABLE expense_splits ADD COLUMN amount_major DOUBLE PRECISION;
+
+UPDATE expense_splits
+SET amount_major = expense_splits.amount / POWER(10, currencies.minor_units)
+FROM expenses, currencies
+WHERE expenses.id = expense_splits.expense_id
+  AND currencies.id = expenses.currency_id;
+
+ALTER TABLE expense_splits ALTER COLUMN amount TYPE DOUBLE PRECISION USING amount_major;
+ALTER TABLE expense_splits DROP COLUMN amount_major;
+
+ALTER TABLE currencies DROP COLUMN minor_units;
@@ -0,0 +1,31 @@
+-- the number of digits after the decimal separator, e.g. 2 for cents
+ALTER TABLE currencies ADD COLUMN minor_units INTEGER NOT NULL DEFAULT 2;
+
+ALTER TABLE currencies
+ADD CONSTRAINT minor_units_check CHECK (minor_units BETWEEN 0 AND 4);
+
+-- amounts are stored as integers in the minor unit of their currency
+ALTER TABLE expense_splits ADD COLUMN amount_minor BIGINT;
+
+UPDATE expense_splits
+SET amount_minor = ROUND((expense_splits.amount * POWER(10, currencies.minor_units))::NUMERIC)
+FROM expenses, currencies
+WHERE expenses.id = expense_splits.expense_id
+  AND currencies.id = expenses.currency_id;
+
+ALTER TABLE expense_splits ALTER COLUMN amount TYPE BIGINT USING amount_minor;
+ALTER TABLE expense_splits DROP COLUMN amount_minor;
+
+ALTER TABLE settlements ADD COLUMN amount_minor BIGINT;
+
+UPDATE settlements
+SET amount_minor = ROUND((settlements.amount * POWER(10, currencies.minor_units))::NUMERIC)
+FROM currencies
+WHERE currencies.id = settlements.currency_id;
+
+ALTER TABLE settlements DROP CONSTRAINT amount_positive_check;
+ALTER TABLE settlements ALTER COLUMN amount TYPE BIGINT USING amount_minor;
+ALTER TABLE settlements DROP COLUMN amount_minor;
+
+ALTER TABLE settlements
+ADD CONSTRAINT amount_positive_check CHECK (amount > 0);
@@ -0,0 +1,4 @@
+ALTER TABLE expenses DROP COLUMN updated_at;
+ALTER TABLE expenses DROP COLUMN created_at;
+ALTER TABLE expenses DROP COLUMN spent_at;
+ALTER TABLE expenses DROP COLUMN total_amount;
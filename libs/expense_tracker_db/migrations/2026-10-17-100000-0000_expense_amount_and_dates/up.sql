@@ -0,0 +1,14 @@
+-- the total amount paid by the owner, in the minor unit of the expense's currency
+ALTER TABLE expenses ADD COLUMN total_amount BIGINT NOT NULL DEFAULT 0;
+
+UPDATE expenses
+SET total_amount = COALESCE(
+    (SELECT SUM(expense_splits.amount) FROM expense_splits WHERE expense_splits.expense_id = expenses.id),
+    0
+);
+
+ALTER TABLE expenses ALTER COLUMN total_amount DROP DEFAULT;
+
+ALTER TABLE expenses ADD COLUMN spent_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now();
+ALTER TABLE expenses ADD COLUMN created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now();
+ALTER TABLE expenses ADD COLUMN updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now();
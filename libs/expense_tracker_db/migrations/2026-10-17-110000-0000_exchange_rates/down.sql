@@ -0,0 +1,3 @@
+DROP TABLE exchange_rates;
+
+ALTER TABLE currencies DROP COLUMN code;
@@ -0,0 +1,19 @@
+-- the ISO 4217 code of a currency, used to import exchange rates
+ALTER TABLE currencies ADD COLUMN code TEXT UNIQUE;
+
+UPDATE currencies SET code = 'CHF' WHERE symbol = 'CHF';
+UPDATE currencies SET code = 'EUR' WHERE symbol = '€';
+UPDATE currencies SET code = 'USD' WHERE symbol = 'US$';
+
+-- one unit of the base currency is worth `rate` units of the quote currency from `valid_on` on
+CREATE TABLE exchange_rates
+(
+    id                SERIAL PRIMARY KEY,
+    base_currency_id  INTEGER          NOT NULL REFERENCES currencies (id) ON DELETE CASCADE,
+    quote_currency_id INTEGER          NOT NULL REFERENCES currencies (id) ON DELETE CASCADE,
+    rate              DOUBLE PRECISION NOT NULL,
+    valid_on          DATE             NOT NULL,
+    CONSTRAINT rate_positive_check CHECK (rate > 0),
+    CONSTRAINT base_quote_check CHECK (base_currency_id <> quote_currency_id),
+    UNIQUE (base_currency_id, quote_currency_id, valid_on)
+);
@@ -0,0 +1,6 @@
+-- the profile of the user as given by the OIDC provider, synced from the claims of their tokens
+ALTER TABLE users ADD COLUMN email TEXT;
+ALTER TABLE users ADD COLUMN avatar_url TEXT;
+
+-- a name chosen by the user, overriding the name given by the OIDC provider
+ALTER TABLE users ADD COLUMN display_name TEXT;
@@ -0,0 +1,11 @@
+-- accounts used to log in with a username and password, if the built-in authentication is used
+-- instead of an OIDC provider
+CREATE TABLE local_accounts
+(
+    user_id       UUID PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    username      TEXT UNIQUE                            NOT NULL,
+    -- the argon2 hash of the password in the PHC string format
+    password_hash TEXT                                   NOT NULL,
+    is_admin      BOOLEAN                                NOT NULL DEFAULT false,
+    created_at    TIMESTAMP WITH TIME ZONE               NOT NULL DEFAULT now()
+);
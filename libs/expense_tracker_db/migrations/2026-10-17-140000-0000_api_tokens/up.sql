@@ -0,0 +1,15 @@
+-- long-lived tokens created by users for scripts and integrations
+CREATE TABLE api_tokens
+(
+    id           SERIAL PRIMARY KEY,
+    user_id      UUID REFERENCES users (id) ON DELETE CASCADE NOT NULL,
+    name         TEXT                                         NOT NULL,
+    -- the start of the token, so users can tell their tokens apart
+    token_prefix TEXT                                         NOT NULL,
+    -- the SHA-256 hash of the token, the token itself is never stored
+    token_hash   TEXT UNIQUE                                  NOT NULL,
+    scopes       TEXT[]                                       NOT NULL,
+    created_at   TIMESTAMP WITH TIME ZONE                     NOT NULL DEFAULT now(),
+    expires_at   TIMESTAMP WITH TIME ZONE,
+    last_used_at TIMESTAMP WITH TIME ZONE
+);
@@ -0,0 +1 @@
+DROP TABLE pot_invitations;
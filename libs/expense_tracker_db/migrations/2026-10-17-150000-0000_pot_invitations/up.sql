@@ -0,0 +1,14 @@
+-- invitations of users to pots, which are only added to the pot once they accept
+CREATE TABLE pot_invitations
+(
+    id         SERIAL PRIMARY KEY,
+    pot_id     INTEGER REFERENCES pots (id) ON DELETE CASCADE NOT NULL,
+    inviter_id UUID REFERENCES users (id) ON DELETE CASCADE  NOT NULL,
+    -- the invited user, NULL for invite codes, which can be shared with anyone
+    invitee_id UUID REFERENCES users (id) ON DELETE CASCADE,
+    code       TEXT UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE                      NOT NULL DEFAULT now(),
+    expires_at TIMESTAMP WITH TIME ZONE                      NOT NULL,
+    UNIQUE (pot_id, invitee_id),
+    CHECK ((invitee_id IS NULL) <> (code IS NULL))
+);
@@ -0,0 +1 @@
+ALTER TABLE pots_to_users DROP COLUMN role;
@@ -0,0 +1,13 @@
+-- the role of each member of a pot, which decides what they are allowed to do
+ALTER TABLE pots_to_users
+    ADD COLUMN role TEXT NOT NULL DEFAULT 'member'
+        CHECK (role IN ('owner', 'admin', 'member', 'viewer'));
+
+-- owners were only stored on the pot so far
+INSERT INTO pots_to_users (pot_id, user_id, role)
+SELECT id, owner_id, 'owner'
+FROM pots
+ON CONFLICT (pot_id, user_id) DO UPDATE SET role = 'owner';
+
+-- every pot has exactly one owner, which is also stored as pots.owner_id
+CREATE UNIQUE INDEX pots_to_users_single_owner ON pots_to_users (pot_id) WHERE role = 'owner';
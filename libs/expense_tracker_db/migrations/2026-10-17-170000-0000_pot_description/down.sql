@@ -0,0 +1,2 @@
+ALTER TABLE pots
+DROP COLUMN description;
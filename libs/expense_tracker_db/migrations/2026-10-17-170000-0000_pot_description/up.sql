@@ -0,0 +1,5 @@
+ALTER TABLE pots
+ADD COLUMN description TEXT;
+
+ALTER TABLE pots
+ADD CONSTRAINT description_length_check CHECK (length(description) <= 500);
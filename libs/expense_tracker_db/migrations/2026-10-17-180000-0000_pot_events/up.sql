@@ -0,0 +1,17 @@
+-- append-only log of everything that happened in a pot
+CREATE TABLE pot_events
+(
+    id         SERIAL PRIMARY KEY,
+    pot_id     INTEGER REFERENCES pots (id) ON DELETE CASCADE NOT NULL,
+    -- the user that triggered the event, NULL for admins of the application
+    actor_id   UUID REFERENCES users (id) ON DELETE SET NULL,
+    kind       TEXT                                          NOT NULL,
+    -- the user the event is about, e.g. the member that has been added
+    subject_id UUID REFERENCES users (id) ON DELETE SET NULL,
+    -- no foreign key, as the events of deleted expenses are kept
+    expense_id INTEGER,
+    details    TEXT,
+    created_at TIMESTAMP WITH TIME ZONE                      NOT NULL DEFAULT now()
+);
+
+CREATE INDEX pot_events_pot_id_id ON pot_events (pot_id, id);
@@ -0,0 +1,3 @@
+DELETE FROM users WHERE guest_pot_id IS NOT NULL;
+ALTER TABLE users DROP COLUMN claim_code;
+ALTER TABLE users DROP COLUMN guest_pot_id;
@@ -0,0 +1,8 @@
+-- guests are placeholder members of a single pot that never log in, until a user claims them
+ALTER TABLE users ADD COLUMN guest_pot_id INTEGER REFERENCES pots (id) ON DELETE CASCADE;
+
+-- the code a user can claim the guest with, handed out by the admins of the pot
+ALTER TABLE users ADD COLUMN claim_code TEXT UNIQUE;
+
+ALTER TABLE users
+ADD CONSTRAINT claim_code_guest_check CHECK (claim_code IS NULL OR guest_pot_id IS NOT NULL);
@@ -0,0 +1,2 @@
+ALTER TABLE settlements
+ADD COLUMN cleared_at TIMESTAMPTZ;
@@ -0,0 +1,2 @@
+ALTER TABLE settlements
+ADD COLUMN cleared_amount BIGINT NOT NULL DEFAULT 0;
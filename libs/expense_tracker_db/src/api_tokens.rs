@@ -0,0 +1,101 @@
+pub mod api_tokens {
+    use crate::schema::api_tokens;
+    use chrono::{DateTime, Utc};
+    use diesel::{Insertable, Queryable, Selectable};
+    use uuid::Uuid;
+
+    /// A long-lived token created by a user for scripts and integrations. Requests made with it
+    /// are made on behalf of the user, limited to the scopes of the token.
+    #[derive(Selectable, Queryable)]
+    pub struct ApiToken {
+        id: i32,
+        user_id: Uuid,
+        name: String,
+        token_prefix: String,
+        token_hash: String,
+        scopes: Vec<String>,
+        created_at: DateTime<Utc>,
+        expires_at: Option<DateTime<Utc>>,
+        last_used_at: Option<DateTime<Utc>>,
+    }
+
+    impl ApiToken {
+        /// Getter for id.
+        pub fn id(&self) -> i32 {
+            self.id
+        }
+
+        /// Getter for user_id.
+        pub fn user_id(&self) -> Uuid {
+            self.user_id
+        }
+
+        /// Getter for name.
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        /// The start of the token, so users can tell their tokens apart.
+        pub fn token_prefix(&self) -> &str {
+            &self.token_prefix
+        }
+
+        /// The SHA-256 hash of the token.
+        pub fn token_hash(&self) -> &str {
+            &self.token_hash
+        }
+
+        /// Getter for scopes.
+        pub fn scopes(&self) -> &Vec<String> {
+            &self.scopes
+        }
+
+        /// Getter for created_at.
+        pub fn created_at(&self) -> DateTime<Utc> {
+            self.created_at
+        }
+
+        /// Getter for expires_at.
+        pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+            self.expires_at
+        }
+
+        /// Getter for last_used_at.
+        pub fn last_used_at(&self) -> Option<DateTime<Utc>> {
+            self.last_used_at
+        }
+    }
+
+    /// Used to create a new API token.
+    #[derive(Insertable)]
+    #[diesel(table_name = api_tokens)]
+    pub struct NewApiToken {
+        user_id: Uuid,
+        name: String,
+        token_prefix: String,
+        token_hash: String,
+        scopes: Vec<String>,
+        expires_at: Option<DateTime<Utc>>,
+    }
+
+    impl NewApiToken {
+        /// Constructor for NewApiToken.
+        pub fn new(
+            user_id: Uuid,
+            name: String,
+            token_prefix: String,
+            token_hash: String,
+            scopes: Vec<String>,
+            expires_at: Option<DateTime<Utc>>,
+        ) -> Self {
+            Self {
+                user_id,
+                name,
+                token_prefix,
+                token_hash,
+                scopes,
+                expires_at,
+            }
+        }
+    }
+}
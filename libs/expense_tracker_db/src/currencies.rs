@@ -11,6 +11,8 @@ pub mod currencies {
         id: i32,
         name: String,
         symbol: String,
+        minor_units: i32,
+        code: Option<String>,
     }
 
     impl Currency {
@@ -25,6 +27,17 @@ pub mod currencies {
         pub fn symbol(&self) -> &str {
             &self.symbol
         }
+
+        /// The number of digits after the decimal separator, e.g. 2 for cents.
+        /// All amounts in this currency are stored as integers in this minor unit.
+        pub fn minor_units(&self) -> i32 {
+            self.minor_units
+        }
+
+        /// The ISO 4217 code of the currency, e.g. EUR. Used to match imported exchange rates.
+        pub fn code(&self) -> Option<&str> {
+            self.code.as_deref()
+        }
     }
 
     /// This struct is used to define a new currency.
@@ -33,12 +46,19 @@ pub mod currencies {
     pub struct NewCurrency {
         name: String,
         symbol: String,
+        minor_units: i32,
+        code: Option<String>,
     }
 
     impl NewCurrency {
         /// Create a new NewCurrency struct instance.
-        pub fn new(name: String, symbol: String) -> Self {
-            NewCurrency { name, symbol }
+        pub fn new(name: String, symbol: String, minor_units: i32, code: Option<String>) -> Self {
+            NewCurrency {
+                name,
+                symbol,
+                minor_units,
+                code,
+            }
         }
 
         pub fn name(&self) -> &str {
@@ -48,5 +68,13 @@ pub mod currencies {
         pub fn symbol(&self) -> &str {
             &self.symbol
         }
+
+        pub fn minor_units(&self) -> i32 {
+            self.minor_units
+        }
+
+        pub fn code(&self) -> Option<&str> {
+            self.code.as_deref()
+        }
     }
 }
@@ -0,0 +1,100 @@
+pub mod exchange_rates {
+    use crate::schema::exchange_rates;
+    use chrono::NaiveDate;
+    use diesel::{Insertable, Queryable, Selectable};
+    use serde::{Deserialize, Serialize};
+
+    /// The exchange rate between two currencies on a given day. One unit of the base currency
+    /// is worth `rate` units of the quote currency, e.g. EUR/CHF 0.94.
+    #[derive(Serialize, Selectable, Queryable, Clone)]
+    #[diesel(table_name = exchange_rates)]
+    pub struct ExchangeRate {
+        id: i32,
+        base_currency_id: i32,
+        quote_currency_id: i32,
+        rate: f64,
+        valid_on: NaiveDate,
+    }
+
+    impl ExchangeRate {
+        /// Constructor for ExchangeRate.
+        pub fn new(
+            id: i32,
+            base_currency_id: i32,
+            quote_currency_id: i32,
+            rate: f64,
+            valid_on: NaiveDate,
+        ) -> Self {
+            Self {
+                id,
+                base_currency_id,
+                quote_currency_id,
+                rate,
+                valid_on,
+            }
+        }
+
+        /// Getter for id.
+        pub fn id(&self) -> i32 {
+            self.id
+        }
+
+        /// Getter for base_currency_id.
+        pub fn base_currency_id(&self) -> i32 {
+            self.base_currency_id
+        }
+
+        /// Getter for quote_currency_id.
+        pub fn quote_currency_id(&self) -> i32 {
+            self.quote_currency_id
+        }
+
+        /// Getter for rate.
+        pub fn rate(&self) -> f64 {
+            self.rate
+        }
+
+        /// Getter for valid_on.
+        pub fn valid_on(&self) -> NaiveDate {
+            self.valid_on
+        }
+    }
+
+    /// This struct is used to create a new exchange rate in the database.
+    #[derive(Deserialize, Insertable, Clone)]
+    #[diesel(table_name = exchange_rates)]
+    pub struct NewExchangeRate {
+        base_currency_id: i32,
+        quote_currency_id: i32,
+        rate: f64,
+        valid_on: NaiveDate,
+    }
+
+    impl NewExchangeRate {
+        /// Constructor
+        pub fn new(base_currency_id: i32, quote_currency_id: i32, rate: f64, valid_on: NaiveDate) -> Self {
+            Self {
+                base_currency_id,
+                quote_currency_id,
+                rate,
+                valid_on,
+            }
+        }
+
+        pub fn base_currency_id(&self) -> i32 {
+            self.base_currency_id
+        }
+
+        pub fn quote_currency_id(&self) -> i32 {
+            self.quote_currency_id
+        }
+
+        pub fn rate(&self) -> f64 {
+            self.rate
+        }
+
+        pub fn valid_on(&self) -> NaiveDate {
+            self.valid_on
+        }
+    }
+}
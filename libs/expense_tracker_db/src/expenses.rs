@@ -1,5 +1,6 @@
 pub mod expenses {
     use crate::pots::pots::Pot;
+    use chrono::{DateTime, Utc};
     use crate::schema::expenses;
     use diesel::{Associations, Insertable, Queryable, Selectable};
     use serde::{Deserialize, Serialize};
@@ -16,6 +17,10 @@ pub mod expenses {
         owner_id: Uuid,
         description: String,
         currency_id: i32,
+        total_amount: i64,
+        spent_at: DateTime<Utc>,
+        created_at: DateTime<Utc>,
+        updated_at: DateTime<Utc>,
     }
 
     /// This struct is used to create a new expense in the database.
@@ -26,16 +31,24 @@ pub mod expenses {
         pot_id: i32,
         description: String,
         currency_id: i32,
+        total_amount: i64,
+        /// Defaults to now, if not given.
+        spent_at: Option<DateTime<Utc>>,
     }
 
     impl Expense {
         /// Constructor for Expense
+        #[allow(clippy::too_many_arguments)]
         pub fn new(
             id: i32,
             pot_id: i32,
             owner_id: Uuid,
             description: String,
             currency_id: i32,
+            total_amount: i64,
+            spent_at: DateTime<Utc>,
+            created_at: DateTime<Utc>,
+            updated_at: DateTime<Utc>,
         ) -> Expense {
             Expense {
                 id,
@@ -43,6 +56,10 @@ pub mod expenses {
                 owner_id,
                 description,
                 currency_id,
+                total_amount,
+                spent_at,
+                created_at,
+                updated_at,
             }
         }
 
@@ -69,16 +86,67 @@ pub mod expenses {
         pub fn currency_id(&self) -> i32 {
             self.currency_id
         }
+
+        /// Getter for total_amount, in the minor unit of the expense's currency.
+        pub fn total_amount(&self) -> i64 {
+            self.total_amount
+        }
+
+        /// Getter for spent_at, the date the money has been spent.
+        pub fn spent_at(&self) -> DateTime<Utc> {
+            self.spent_at
+        }
+
+        /// Getter for created_at
+        pub fn created_at(&self) -> DateTime<Utc> {
+            self.created_at
+        }
+
+        /// Getter for updated_at
+        pub fn updated_at(&self) -> DateTime<Utc> {
+            self.updated_at
+        }
     }
 
     impl NewExpense {
-        pub fn new(owner_id: Uuid, pot_id: i32, description: String, currency_id: i32) -> Self {
+        /// The total amount is 0 until it is set with `set_total_amount`.
+        pub fn new(
+            owner_id: Uuid,
+            pot_id: i32,
+            description: String,
+            currency_id: i32,
+            spent_at: Option<DateTime<Utc>>,
+        ) -> Self {
             Self {
                 owner_id,
                 pot_id,
                 description,
                 currency_id,
+                total_amount: 0,
+                spent_at,
             }
         }
+
+        pub fn set_total_amount(&mut self, total_amount: i64) {
+            self.total_amount = total_amount
+        }
+
+        /// Getter for owner_id
+        pub fn owner_id(&self) -> Uuid {
+            self.owner_id
+        }
+
+        /// Getter for pot_id
+        pub fn pot_id(&self) -> i32 {
+            self.pot_id
+        }
+
+        pub fn currency_id(&self) -> i32 {
+            self.currency_id
+        }
+
+        pub fn description(&self) -> &str {
+            &self.description
+        }
     }
 }
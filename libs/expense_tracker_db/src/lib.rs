@@ -1,9 +1,18 @@
+// Every file wraps its content in a module named like the file itself.
+#![allow(clippy::module_inception)]
+
 use thiserror::Error;
 
+pub mod api_tokens;
 pub mod currencies;
+pub mod exchange_rates;
 pub mod expenses;
+pub mod local_accounts;
+pub mod pot_events;
+pub mod pot_invitations;
 pub mod pots;
 pub mod schema;
+pub mod settlements;
 pub mod splits;
 pub mod users;
 pub mod template_pots;
@@ -33,7 +42,7 @@ pub mod setup {
     use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
     use std::error::Error;
 
-    /// The exact type of the DbPool in this application.
+    // The exact type of the DbPool in this application.
     // pub type DbPool = deadpool_diesel::postgres::Pool;
 
     /// The exact type of the connection pool that is used in this application.
@@ -58,3 +67,65 @@ pub mod setup {
             .expect("Failed to run migrations");
     }
 }
+
+/// Helpers for tests that need a database. Those tests are marked with `#[ignore]` and run with
+/// `TEST_DATABASE_URL=<url> cargo test --workspace -- --include-ignored`.
+#[cfg(feature = "test-support")]
+pub mod test_support {
+    use crate::setup::{setup_db, DbPool};
+    use diesel::connection::InstrumentationEvent;
+    use diesel_async::pooled_connection::bb8::Pool;
+    use diesel_async::pooled_connection::{
+        AsyncDieselConnectionManager, ManagerConfig, RecyclingMethod,
+    };
+    use diesel_async::{AsyncConnection, AsyncPgConnection};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// The url of the test database, taken from `TEST_DATABASE_URL`. Panics if it is not set,
+    /// so a test that needs a database never passes without one.
+    pub fn test_database_url() -> String {
+        std::env::var("TEST_DATABASE_URL")
+            .expect("TEST_DATABASE_URL must be set to run tests that need a database")
+    }
+
+    /// Sets up the test database, running all migrations on it.
+    pub async fn test_db() -> DbPool {
+        setup_db(&test_database_url())
+            .await
+            .expect("the test database could not be set up")
+    }
+
+    /// Creates a pool on the test database with a single connection, counting the queries run
+    /// on it in `query_count`.
+    pub async fn counting_pool(query_count: Arc<AtomicUsize>) -> DbPool {
+        let mut config = ManagerConfig::<AsyncPgConnection>::default();
+        config.recycling_method = RecyclingMethod::Fast;
+        config.custom_setup = Box::new(move |url| {
+            let query_count = query_count.clone();
+            let url = url.to_string();
+
+            Box::pin(async move {
+                let mut conn = AsyncPgConnection::establish(&url).await?;
+                conn.set_instrumentation(move |event: InstrumentationEvent<'_>| {
+                    if let InstrumentationEvent::StartQuery { .. } = event {
+                        query_count.fetch_add(1, Ordering::SeqCst);
+                    }
+                });
+                Ok(conn)
+            })
+        });
+
+        let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(
+            test_database_url(),
+            config,
+        );
+
+        Pool::builder()
+            .max_size(1)
+            .test_on_check_out(false)
+            .build(manager)
+            .await
+            .expect("the counting pool could not be created")
+    }
+}
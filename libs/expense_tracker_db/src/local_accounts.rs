@@ -0,0 +1,66 @@
+pub mod local_accounts {
+    use crate::schema::local_accounts;
+    use chrono::{DateTime, Utc};
+    use diesel::{Insertable, Queryable, Selectable};
+    use uuid::Uuid;
+
+    /// An account to log in with a username and password, used instead of an OIDC provider
+    /// if the built-in authentication is enabled. Each account belongs to exactly one user.
+    #[derive(Selectable, Queryable)]
+    pub struct LocalAccount {
+        user_id: Uuid,
+        username: String,
+        password_hash: String,
+        is_admin: bool,
+        created_at: DateTime<Utc>,
+    }
+
+    impl LocalAccount {
+        /// Getter for user_id.
+        pub fn user_id(&self) -> Uuid {
+            self.user_id
+        }
+
+        /// Getter for username.
+        pub fn username(&self) -> &str {
+            &self.username
+        }
+
+        /// The argon2 hash of the password in the PHC string format.
+        pub fn password_hash(&self) -> &str {
+            &self.password_hash
+        }
+
+        /// Getter for is_admin.
+        pub fn is_admin(&self) -> bool {
+            self.is_admin
+        }
+
+        /// Getter for created_at.
+        pub fn created_at(&self) -> DateTime<Utc> {
+            self.created_at
+        }
+    }
+
+    /// Used to create a new local account.
+    #[derive(Insertable)]
+    #[diesel(table_name = local_accounts)]
+    pub struct NewLocalAccount {
+        user_id: Uuid,
+        username: String,
+        password_hash: String,
+        is_admin: bool,
+    }
+
+    impl NewLocalAccount {
+        /// Constructor for NewLocalAccount.
+        pub fn new(user_id: Uuid, username: String, password_hash: String, is_admin: bool) -> Self {
+            Self {
+                user_id,
+                username,
+                password_hash,
+                is_admin,
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+pub mod pot_events {
+    use crate::schema::pot_events;
+    use chrono::{DateTime, Utc};
+    use diesel::deserialize::{self, FromSql, FromSqlRow};
+    use diesel::expression::AsExpression;
+    use diesel::pg::{Pg, PgValue};
+    use diesel::serialize::{self, Output, ToSql};
+    use diesel::sql_types::Text;
+    use diesel::{Insertable, Queryable, Selectable};
+    use uuid::Uuid;
+
+    /// The kinds of events that are recorded for a pot.
+    #[derive(AsExpression, FromSqlRow, Clone, Copy, PartialEq, Eq, Debug)]
+    #[diesel(sql_type = Text)]
+    pub enum PotEventKind {
+        PotCreated,
+        PotUpdated,
+        PotArchived,
+        PotUnarchived,
+        PotPaid,
+        MemberAdded,
+        MemberJoined,
+        MemberRemoved,
+        MemberLeft,
+        RoleChanged,
+        OwnershipTransferred,
+        GuestAdded,
+        GuestClaimed,
+        ExpenseAdded,
+        ExpenseUpdated,
+        ExpenseDeleted,
+        ExpensePaid,
+        SettlementRecorded,
+    }
+
+    impl PotEventKind {
+        const ALL: [PotEventKind; 18] = [
+            PotEventKind::PotCreated,
+            PotEventKind::PotUpdated,
+            PotEventKind::PotArchived,
+            PotEventKind::PotUnarchived,
+            PotEventKind::PotPaid,
+            PotEventKind::MemberAdded,
+            PotEventKind::MemberJoined,
+            PotEventKind::MemberRemoved,
+            PotEventKind::MemberLeft,
+            PotEventKind::RoleChanged,
+            PotEventKind::OwnershipTransferred,
+            PotEventKind::GuestAdded,
+            PotEventKind::GuestClaimed,
+            PotEventKind::ExpenseAdded,
+            PotEventKind::ExpenseUpdated,
+            PotEventKind::ExpenseDeleted,
+            PotEventKind::ExpensePaid,
+            PotEventKind::SettlementRecorded,
+        ];
+
+        /// The name the kind is stored with.
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                PotEventKind::PotCreated => "pot_created",
+                PotEventKind::PotUpdated => "pot_updated",
+                PotEventKind::PotArchived => "pot_archived",
+                PotEventKind::PotUnarchived => "pot_unarchived",
+                PotEventKind::PotPaid => "pot_paid",
+                PotEventKind::MemberAdded => "member_added",
+                PotEventKind::MemberJoined => "member_joined",
+                PotEventKind::MemberRemoved => "member_removed",
+                PotEventKind::MemberLeft => "member_left",
+                PotEventKind::RoleChanged => "role_changed",
+                PotEventKind::OwnershipTransferred => "ownership_transferred",
+                PotEventKind::GuestAdded => "guest_added",
+                PotEventKind::GuestClaimed => "guest_claimed",
+                PotEventKind::ExpenseAdded => "expense_added",
+                PotEventKind::ExpenseUpdated => "expense_updated",
+                PotEventKind::ExpenseDeleted => "expense_deleted",
+                PotEventKind::ExpensePaid => "expense_paid",
+                PotEventKind::SettlementRecorded => "settlement_recorded",
+            }
+        }
+
+        /// Gets the kind stored with the given name.
+        pub fn parse(name: &str) -> Option<Self> {
+            Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+        }
+    }
+
+    impl ToSql<Text, Pg> for PotEventKind {
+        fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+            <str as ToSql<Text, Pg>>::to_sql(self.as_str(), &mut out.reborrow())
+        }
+    }
+
+    impl FromSql<Text, Pg> for PotEventKind {
+        fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+            let name = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+
+            PotEventKind::parse(&name).ok_or_else(|| format!("Unknown pot event {}", name).into())
+        }
+    }
+
+    /// An event that happened in a pot. Events are only ever added, never changed, so they
+    /// document the history of a pot.
+    #[derive(Selectable, Queryable)]
+    pub struct PotEvent {
+        id: i32,
+        pot_id: i32,
+        actor_id: Option<Uuid>,
+        kind: PotEventKind,
+        subject_id: Option<Uuid>,
+        expense_id: Option<i32>,
+        details: Option<String>,
+        created_at: DateTime<Utc>,
+    }
+
+    impl PotEvent {
+        /// Getter for id.
+        pub fn id(&self) -> i32 {
+            self.id
+        }
+
+        /// Getter for pot_id.
+        pub fn pot_id(&self) -> i32 {
+            self.pot_id
+        }
+
+        /// The user that triggered the event, `None` for admins of the application or deleted
+        /// users.
+        pub fn actor_id(&self) -> Option<Uuid> {
+            self.actor_id
+        }
+
+        /// Getter for kind.
+        pub fn kind(&self) -> PotEventKind {
+            self.kind
+        }
+
+        /// The user the event is about, e.g. the member that has been added.
+        pub fn subject_id(&self) -> Option<Uuid> {
+            self.subject_id
+        }
+
+        /// The expense the event is about. The expense may have been deleted since.
+        pub fn expense_id(&self) -> Option<i32> {
+            self.expense_id
+        }
+
+        /// Getter for details.
+        pub fn details(&self) -> Option<&str> {
+            self.details.as_deref()
+        }
+
+        /// Getter for created_at.
+        pub fn created_at(&self) -> DateTime<Utc> {
+            self.created_at
+        }
+    }
+
+    /// Used to record a new PotEvent.
+    #[derive(Insertable)]
+    #[diesel(table_name = pot_events)]
+    pub struct NewPotEvent {
+        pot_id: i32,
+        actor_id: Option<Uuid>,
+        kind: PotEventKind,
+        subject_id: Option<Uuid>,
+        expense_id: Option<i32>,
+        details: Option<String>,
+    }
+
+    impl NewPotEvent {
+        /// Creates an event of the given kind, triggered by the given actor.
+        pub fn new(pot_id: i32, actor_id: Option<Uuid>, kind: PotEventKind) -> Self {
+            Self {
+                pot_id,
+                actor_id,
+                kind,
+                subject_id: None,
+                expense_id: None,
+                details: None,
+            }
+        }
+
+        /// Sets the user the event is about.
+        pub fn with_subject(mut self, subject_id: Uuid) -> Self {
+            self.subject_id = Some(subject_id);
+            self
+        }
+
+        /// Sets the expense the event is about.
+        pub fn with_expense(mut self, expense_id: i32) -> Self {
+            self.expense_id = Some(expense_id);
+            self
+        }
+
+        /// Sets a short description of what happened, e.g. the new name of the pot.
+        pub fn with_details(mut self, details: String) -> Self {
+            self.details = Some(details);
+            self
+        }
+    }
+}
@@ -0,0 +1,102 @@
+pub mod pot_invitations {
+    use crate::schema::pot_invitations;
+    use chrono::{DateTime, Utc};
+    use diesel::{Insertable, Queryable, Selectable};
+    use uuid::Uuid;
+
+    /// An invitation to a pot. Invited users are only added to the pot once they accept the
+    /// invitation. Invitations either address a single user or hold a code, which can be shared
+    /// with anyone, e.g. people that never logged in before.
+    #[derive(Selectable, Queryable)]
+    pub struct PotInvitation {
+        id: i32,
+        pot_id: i32,
+        inviter_id: Uuid,
+        invitee_id: Option<Uuid>,
+        code: Option<String>,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    }
+
+    impl PotInvitation {
+        /// Getter for id.
+        pub fn id(&self) -> i32 {
+            self.id
+        }
+
+        /// Getter for pot_id.
+        pub fn pot_id(&self) -> i32 {
+            self.pot_id
+        }
+
+        /// Getter for inviter_id.
+        pub fn inviter_id(&self) -> Uuid {
+            self.inviter_id
+        }
+
+        /// The invited user, `None` for invitations with a code.
+        pub fn invitee_id(&self) -> Option<Uuid> {
+            self.invitee_id
+        }
+
+        /// The code to accept the invitation with, `None` for invitations of a single user.
+        pub fn code(&self) -> Option<&str> {
+            self.code.as_deref()
+        }
+
+        /// Getter for created_at.
+        pub fn created_at(&self) -> DateTime<Utc> {
+            self.created_at
+        }
+
+        /// Getter for expires_at.
+        pub fn expires_at(&self) -> DateTime<Utc> {
+            self.expires_at
+        }
+    }
+
+    /// Used to create a new PotInvitation.
+    #[derive(Insertable)]
+    #[diesel(table_name = pot_invitations)]
+    pub struct NewPotInvitation {
+        pot_id: i32,
+        inviter_id: Uuid,
+        invitee_id: Option<Uuid>,
+        code: Option<String>,
+        expires_at: DateTime<Utc>,
+    }
+
+    impl NewPotInvitation {
+        /// Creates an invitation of the given user.
+        pub fn for_user(
+            pot_id: i32,
+            inviter_id: Uuid,
+            invitee_id: Uuid,
+            expires_at: DateTime<Utc>,
+        ) -> Self {
+            Self {
+                pot_id,
+                inviter_id,
+                invitee_id: Some(invitee_id),
+                code: None,
+                expires_at,
+            }
+        }
+
+        /// Creates an invitation that can be accepted by anyone knowing the given code.
+        pub fn with_code(
+            pot_id: i32,
+            inviter_id: Uuid,
+            code: String,
+            expires_at: DateTime<Utc>,
+        ) -> Self {
+            Self {
+                pot_id,
+                inviter_id,
+                invitee_id: None,
+                code: Some(code),
+                expires_at,
+            }
+        }
+    }
+}
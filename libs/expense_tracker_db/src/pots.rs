@@ -2,6 +2,11 @@ pub mod pots {
     use chrono::{DateTime, Utc};
     use crate::schema::pots;
     use crate::schema::pots_to_users;
+    use diesel::deserialize::{self, FromSql, FromSqlRow};
+    use diesel::expression::AsExpression;
+    use diesel::pg::{Pg, PgValue};
+    use diesel::serialize::{self, Output, ToSql};
+    use diesel::sql_types::Text;
     use diesel::{Insertable, Queryable, Selectable};
     use serde::{Deserialize, Serialize};
     use uuid::Uuid;
@@ -9,7 +14,7 @@ pub mod pots {
 
     /// Represents a pot. A pot is an accumulation of expenses and is owned by a single
     /// user. A pot can be shared with multiple users. The users can leave a pot
-    /// anytime.
+    /// anytime, as long as they have no open balance in it. The owner can't leave their pot.
     /// The owner of a pot needs to invite other users to participate in a pot.
     #[derive(Serialize, Selectable, Queryable)]
     pub struct Pot {
@@ -20,6 +25,7 @@ pub mod pots {
         archived: bool,
         created_at: DateTime<Utc>,
         archived_at: Option<DateTime<Utc>>,
+        description: Option<String>,
     }
 
     impl Pot {
@@ -38,6 +44,7 @@ pub mod pots {
                 archived: false,
                 created_at,
                 archived_at: None,
+                description: None,
             }
         }
 
@@ -69,6 +76,9 @@ pub mod pots {
 
         /// Getter for archived_at.
         pub fn archived_at(&self) -> Option<DateTime<Utc>> { self.archived_at }
+
+        /// Getter for description.
+        pub fn description(&self) -> Option<&str> { self.description.as_deref() }
     }
 
     /// This struct is used to create a new pot in the database.
@@ -79,6 +89,7 @@ pub mod pots {
         name: String,
         default_currency_id: i32,
         created_at: DateTime<Utc>,
+        description: Option<String>,
     }
     impl NewPot {
         /// Constructor
@@ -88,16 +99,18 @@ pub mod pots {
                 name,
                 default_currency_id,
                 created_at: Utc::now(),
+                description: None,
             }
         }
 
         /// Used to create a NewPot from a PotTemplate
-        pub fn from_template(template: &PotTemplate, name: &String) -> Self {
+        pub fn from_template(template: &PotTemplate, name: &str) -> Self {
             Self {
                 owner_id: template.owner_id(),
-                name: name.clone(),
+                name: name.to_string(),
                 default_currency_id: template.default_currency_id(),
                 created_at: Utc::now(),
+                description: None,
             }
         }
 
@@ -112,6 +125,68 @@ pub mod pots {
         pub fn default_currency_id(&self) -> i32 {
             self.default_currency_id
         }
+
+        pub fn description(&self) -> Option<&str> {
+            self.description.as_deref()
+        }
+
+        pub fn set_description(&mut self, description: Option<String>) {
+            self.description = description
+        }
+    }
+
+    /// The role of a member of a pot, which decides what they are allowed to do. Roles are
+    /// ordered, so every role is allowed to do everything the lower roles are allowed to do.
+    #[derive(
+        AsExpression, FromSqlRow, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd,
+        Ord, Debug, Default,
+    )]
+    #[diesel(sql_type = Text)]
+    #[serde(rename_all = "snake_case")]
+    pub enum PotRole {
+        /// Can see the pot, but not change anything.
+        Viewer,
+        /// Can add expenses and settlements.
+        #[default]
+        Member,
+        /// Can additionally manage the members of the pot and archive it.
+        Admin,
+        /// Can additionally delete the pot and transfer its ownership. Every pot has exactly
+        /// one owner.
+        Owner,
+    }
+
+    impl PotRole {
+        /// The name the role is stored with.
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                PotRole::Viewer => "viewer",
+                PotRole::Member => "member",
+                PotRole::Admin => "admin",
+                PotRole::Owner => "owner",
+            }
+        }
+
+        /// Gets the role stored with the given name.
+        pub fn parse(name: &str) -> Option<Self> {
+            [PotRole::Viewer, PotRole::Member, PotRole::Admin, PotRole::Owner]
+                .into_iter()
+                .find(|role| role.as_str() == name)
+        }
+    }
+
+    impl ToSql<Text, Pg> for PotRole {
+        fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+            <str as ToSql<Text, Pg>>::to_sql(self.as_str(), &mut out.reborrow())
+        }
+    }
+
+    impl FromSql<Text, Pg> for PotRole {
+        fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+            let name = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+
+            PotRole::parse(&name).ok_or_else(|| format!("Unknown pot role {}", name).into())
+        }
     }
 
     /// This struct is used to create a new pots_to_user relationship in the database.
@@ -120,11 +195,19 @@ pub mod pots {
     pub struct PotToUser {
         pot_id: i32,
         user_id: Uuid,
+        #[serde(default)]
+        role: PotRole,
     }
 
     impl PotToUser {
+        /// Creates a new relationship, making the user a member of the pot.
         pub fn new(pot_id: i32, user_id: Uuid) -> Self {
-            Self { pot_id, user_id }
+            Self::with_role(pot_id, user_id, PotRole::Member)
+        }
+
+        /// Creates a new relationship, giving the user the given role in the pot.
+        pub fn with_role(pot_id: i32, user_id: Uuid, role: PotRole) -> Self {
+            Self { pot_id, user_id, role }
         }
 
         pub fn pot_id(&self) -> i32 {
@@ -134,5 +217,9 @@ pub mod pots {
         pub fn user_id(&self) -> Uuid {
             self.user_id
         }
+
+        pub fn role(&self) -> PotRole {
+            self.role
+        }
     }
 }
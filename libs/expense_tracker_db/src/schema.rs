@@ -1,10 +1,36 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    api_tokens (id) {
+        id -> Int4,
+        user_id -> Uuid,
+        name -> Text,
+        token_prefix -> Text,
+        token_hash -> Text,
+        scopes -> Array<Text>,
+        created_at -> Timestamptz,
+        expires_at -> Nullable<Timestamptz>,
+        last_used_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     currencies (id) {
         id -> Int4,
         name -> Text,
         symbol -> Text,
+        minor_units -> Int4,
+        code -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    exchange_rates (id) {
+        id -> Int4,
+        base_currency_id -> Int4,
+        quote_currency_id -> Int4,
+        rate -> Float8,
+        valid_on -> Date,
     }
 }
 
@@ -12,7 +38,7 @@ diesel::table! {
     expense_splits (expense_id, user_id) {
         expense_id -> Int4,
         user_id -> Uuid,
-        amount -> Float8,
+        amount -> Int8,
         is_paid -> Bool,
     }
 }
@@ -24,6 +50,20 @@ diesel::table! {
         pot_id -> Int4,
         description -> Text,
         currency_id -> Int4,
+        total_amount -> Int8,
+        spent_at -> Timestamptz,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    local_accounts (user_id) {
+        user_id -> Uuid,
+        username -> Text,
+        password_hash -> Text,
+        is_admin -> Bool,
+        created_at -> Timestamptz,
     }
 }
 
@@ -35,6 +75,31 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    pot_events (id) {
+        id -> Int4,
+        pot_id -> Int4,
+        actor_id -> Nullable<Uuid>,
+        kind -> Text,
+        subject_id -> Nullable<Uuid>,
+        expense_id -> Nullable<Int4>,
+        details -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    pot_invitations (id) {
+        id -> Int4,
+        pot_id -> Int4,
+        inviter_id -> Uuid,
+        invitee_id -> Nullable<Uuid>,
+        code -> Nullable<Text>,
+        created_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     pot_templates (id) {
         id -> Int4,
@@ -54,6 +119,7 @@ diesel::table! {
         created_at -> Timestamptz,
         archived_at -> Nullable<Timestamptz>,
         archived -> Bool,
+        description -> Nullable<Text>,
     }
 }
 
@@ -61,6 +127,21 @@ diesel::table! {
     pots_to_users (pot_id, user_id) {
         pot_id -> Int4,
         user_id -> Uuid,
+        role -> Text,
+    }
+}
+
+diesel::table! {
+    settlements (id) {
+        id -> Int4,
+        pot_id -> Int4,
+        payer_id -> Uuid,
+        receiver_id -> Uuid,
+        amount -> Int8,
+        currency_id -> Int4,
+        created_at -> Timestamptz,
+        cleared_at -> Nullable<Timestamptz>,
+        cleared_amount -> Int8,
     }
 }
 
@@ -68,14 +149,23 @@ diesel::table! {
     users (id) {
         id -> Uuid,
         name -> Text,
+        email -> Nullable<Text>,
+        avatar_url -> Nullable<Text>,
+        display_name -> Nullable<Text>,
+        guest_pot_id -> Nullable<Int4>,
+        claim_code -> Nullable<Text>,
     }
 }
 
+diesel::joinable!(api_tokens -> users (user_id));
 diesel::joinable!(expense_splits -> expenses (expense_id));
 diesel::joinable!(expense_splits -> users (user_id));
 diesel::joinable!(expenses -> currencies (currency_id));
 diesel::joinable!(expenses -> pots (pot_id));
 diesel::joinable!(expenses -> users (owner_id));
+diesel::joinable!(local_accounts -> users (user_id));
+diesel::joinable!(pot_events -> pots (pot_id));
+diesel::joinable!(pot_invitations -> pots (pot_id));
 diesel::joinable!(pot_template_users -> pot_templates (pot_template_id));
 diesel::joinable!(pot_template_users -> users (user_id));
 diesel::joinable!(pot_templates -> currencies (default_currency_id));
@@ -84,14 +174,22 @@ diesel::joinable!(pots -> currencies (default_currency_id));
 diesel::joinable!(pots -> users (owner_id));
 diesel::joinable!(pots_to_users -> pots (pot_id));
 diesel::joinable!(pots_to_users -> users (user_id));
+diesel::joinable!(settlements -> currencies (currency_id));
+diesel::joinable!(settlements -> pots (pot_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_tokens,
     currencies,
+    exchange_rates,
     expense_splits,
     expenses,
+    local_accounts,
+    pot_events,
+    pot_invitations,
     pot_template_users,
     pot_templates,
     pots,
     pots_to_users,
+    settlements,
     users,
 );
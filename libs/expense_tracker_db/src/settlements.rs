@@ -0,0 +1,153 @@
+pub mod settlements {
+    use crate::pots::pots::Pot;
+    use crate::schema::settlements;
+    use chrono::{DateTime, Utc};
+    use diesel::{Associations, Insertable, Queryable, Selectable};
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    /// A settlement is a payment from one user of a pot to another one, e.g. a bank transfer
+    /// or cash. It is counted against the balances of the pot and can cover debts of multiple
+    /// expenses or only a part of them. The part covering splits that are marked as paid is
+    /// cleared, as it is part of that payment, and the whole settlement once the pot is paid.
+    #[derive(Serialize, Selectable, Queryable, Associations, Clone)]
+    #[diesel(belongs_to(Pot))]
+    pub struct Settlement {
+        id: i32,
+        pot_id: i32,
+        payer_id: Uuid,
+        receiver_id: Uuid,
+        amount: i64,
+        currency_id: i32,
+        created_at: DateTime<Utc>,
+        cleared_at: Option<DateTime<Utc>>,
+        cleared_amount: i64,
+    }
+
+    impl Settlement {
+        /// Constructor for a Settlement that has not been cleared yet.
+        pub fn new(
+            id: i32,
+            pot_id: i32,
+            payer_id: Uuid,
+            receiver_id: Uuid,
+            amount: i64,
+            currency_id: i32,
+            created_at: DateTime<Utc>,
+        ) -> Self {
+            Self {
+                id,
+                pot_id,
+                payer_id,
+                receiver_id,
+                amount,
+                currency_id,
+                created_at,
+                cleared_at: None,
+                cleared_amount: 0,
+            }
+        }
+
+        /// Getter for id.
+        pub fn id(&self) -> i32 {
+            self.id
+        }
+
+        /// Getter for pot_id.
+        pub fn pot_id(&self) -> i32 {
+            self.pot_id
+        }
+
+        /// Getter for payer_id.
+        pub fn payer_id(&self) -> Uuid {
+            self.payer_id
+        }
+
+        /// Getter for receiver_id.
+        pub fn receiver_id(&self) -> Uuid {
+            self.receiver_id
+        }
+
+        /// Getter for amount, in the minor unit of the settlement's currency.
+        pub fn amount(&self) -> i64 {
+            self.amount
+        }
+
+        /// Getter for currency_id.
+        pub fn currency_id(&self) -> i32 {
+            self.currency_id
+        }
+
+        /// Getter for created_at.
+        pub fn created_at(&self) -> DateTime<Utc> {
+            self.created_at
+        }
+
+        /// Getter for cleared_at. Cleared settlements are kept as payment history, but no longer
+        /// count towards the balances of the pot.
+        pub fn cleared_at(&self) -> Option<DateTime<Utc>> {
+            self.cleared_at
+        }
+
+        /// Getter for cleared_amount, the part of the amount that has been cleared by paying
+        /// the splits it covered.
+        pub fn cleared_amount(&self) -> i64 {
+            self.cleared_amount
+        }
+
+        /// The part of the amount that still counts towards the balances of the pot.
+        pub fn open_amount(&self) -> i64 {
+            self.amount - self.cleared_amount
+        }
+    }
+
+    /// This struct is used to create a new settlement in the database.
+    #[derive(Deserialize, Insertable, Clone)]
+    #[diesel(table_name = settlements)]
+    pub struct NewSettlement {
+        pot_id: i32,
+        payer_id: Uuid,
+        receiver_id: Uuid,
+        amount: i64,
+        currency_id: i32,
+    }
+
+    impl NewSettlement {
+        /// Constructor
+        pub fn new(
+            pot_id: i32,
+            payer_id: Uuid,
+            receiver_id: Uuid,
+            amount: i64,
+            currency_id: i32,
+        ) -> Self {
+            Self {
+                pot_id,
+                payer_id,
+                receiver_id,
+                amount,
+                currency_id,
+            }
+        }
+
+        pub fn pot_id(&self) -> i32 {
+            self.pot_id
+        }
+
+        pub fn payer_id(&self) -> Uuid {
+            self.payer_id
+        }
+
+        pub fn receiver_id(&self) -> Uuid {
+            self.receiver_id
+        }
+
+        pub fn amount(&self) -> i64 {
+            self.amount
+        }
+
+        pub fn currency_id(&self) -> i32 {
+            self.currency_id
+        }
+    }
+}
@@ -16,13 +16,13 @@ pub mod splits {
     pub struct Split {
         expense_id: i32,
         user_id: Uuid,
-        amount: f64,
+        amount: i64,
         is_paid: bool,
     }
 
     impl Split {
         /// Constructor for a Split.
-        pub fn new(expense_id: i32, user_id: Uuid, amount: f64, is_paid: bool) -> Self {
+        pub fn new(expense_id: i32, user_id: Uuid, amount: i64, is_paid: bool) -> Self {
             Split {
                 expense_id,
                 user_id,
@@ -41,8 +41,8 @@ pub mod splits {
             self.user_id
         }
 
-        /// Getter for amount.
-        pub fn amount(&self) -> f64 {
+        /// Getter for amount, in the minor unit of the expense's currency.
+        pub fn amount(&self) -> i64 {
             self.amount
         }
 
@@ -58,12 +58,12 @@ pub mod splits {
     pub struct NewSplit {
         expense_id: i32,
         user_id: Uuid,
-        amount: f64,
+        amount: i64,
         is_paid: bool,
     }
 
     impl NewSplit {
-        pub fn new(expense_id: i32, user_id: Uuid, amount: f64, is_paid: bool) -> Self {
+        pub fn new(expense_id: i32, user_id: Uuid, amount: i64, is_paid: bool) -> Self {
             Self {
                 expense_id,
                 user_id,
@@ -91,12 +91,12 @@ pub mod splits {
     /// Use this struct if you want to create a new Expense with splits.
     pub struct NewExpenseSplit {
         user_id: Uuid,
-        amount: f64,
+        amount: i64,
         is_paid: bool,
     }
 
     impl NewExpenseSplit {
-        pub fn new(user_id: Uuid, amount: f64) -> Self {
+        pub fn new(user_id: Uuid, amount: i64) -> Self {
             Self {
                 user_id,
                 amount,
@@ -104,6 +104,16 @@ pub mod splits {
             }
         }
 
+        /// Getter for user_id.
+        pub fn user_id(&self) -> Uuid {
+            self.user_id
+        }
+
+        /// Getter for amount, in the minor unit of the expense's currency.
+        pub fn amount(&self) -> i64 {
+            self.amount
+        }
+
         pub fn set_payment_status(&mut self, is_paid : bool) {
             self.is_paid = is_paid
         }
@@ -5,17 +5,49 @@ pub mod users {
     use uuid::Uuid;
 
     /// Defines a user of the systems.
-    /// A user can hold pots, expenses and so on.
+    /// A user can hold pots, expenses and so on. Guests are users without an account, which
+    /// only exist in a single pot until a user claims them.
     #[derive(Serialize, Selectable, Queryable, Insertable)]
     pub struct User {
         id: Uuid,
         name: String,
+        email: Option<String>,
+        avatar_url: Option<String>,
+        display_name: Option<String>,
+        guest_pot_id: Option<i32>,
+        #[serde(skip)]
+        claim_code: Option<String>,
     }
 
     impl User {
         /// Constructor for User
         pub fn new(uuid: Uuid, name: String) -> User {
-            User { id: uuid, name }
+            User {
+                id: uuid,
+                name,
+                email: None,
+                avatar_url: None,
+                display_name: None,
+                guest_pot_id: None,
+                claim_code: None,
+            }
+        }
+
+        /// Creates a new guest of the given pot.
+        pub fn new_guest(uuid: Uuid, name: String, pot_id: i32) -> User {
+            User {
+                guest_pot_id: Some(pot_id),
+                ..Self::new(uuid, name)
+            }
+        }
+
+        /// Creates a new user with the given profile given by the OIDC provider.
+        pub fn from_profile(uuid: Uuid, name: String, profile: UserProfile) -> User {
+            User {
+                email: profile.email,
+                avatar_url: profile.avatar_url,
+                ..Self::new(uuid, name)
+            }
         }
 
         /// Getter for user_id
@@ -23,8 +55,83 @@ pub mod users {
             self.id
         }
 
+        /// The name the user is shown with. This is the display name chosen by the user, if
+        /// set, or the name given by the OIDC provider.
         pub fn name(&self) -> &str {
+            self.display_name.as_deref().unwrap_or(&self.name)
+        }
+
+        /// The name given by the OIDC provider.
+        pub fn provider_name(&self) -> &str {
             &self.name
         }
+
+        /// Getter for email.
+        pub fn email(&self) -> Option<&str> {
+            self.email.as_deref()
+        }
+
+        /// Getter for avatar_url.
+        pub fn avatar_url(&self) -> Option<&str> {
+            self.avatar_url.as_deref()
+        }
+
+        /// Getter for display_name.
+        pub fn display_name(&self) -> Option<&str> {
+            self.display_name.as_deref()
+        }
+
+        /// The pot the user is a guest of, `None` for users with an account.
+        pub fn guest_pot_id(&self) -> Option<i32> {
+            self.guest_pot_id
+        }
+
+        /// Checks if the user is a guest without an account.
+        pub fn is_guest(&self) -> bool {
+            self.guest_pot_id.is_some()
+        }
+
+        /// The code the guest can be claimed with, if one has been handed out.
+        pub fn claim_code(&self) -> Option<&str> {
+            self.claim_code.as_deref()
+        }
+
+        /// Checks if the profile given by the OIDC provider differs from the stored one.
+        /// Fields missing in the given profile are ignored.
+        pub fn differs_from(&self, profile: &UserProfile) -> bool {
+            let differs = |stored: Option<&str>, given: &Option<String>| {
+                given.as_deref().is_some_and(|given| stored != Some(given))
+            };
+
+            differs(Some(&self.name), &profile.name)
+                || differs(self.email(), &profile.email)
+                || differs(self.avatar_url(), &profile.avatar_url)
+        }
+    }
+
+    /// The profile of a user as given by the claims of their token. Fields that are `None`
+    /// are not part of the token and are not changed when updating a user.
+    #[derive(diesel::AsChangeset, Clone)]
+    #[diesel(table_name = users)]
+    pub struct UserProfile {
+        name: Option<String>,
+        email: Option<String>,
+        avatar_url: Option<String>,
+    }
+
+    impl UserProfile {
+        /// Constructor for UserProfile.
+        pub fn new(name: Option<String>, email: Option<String>, avatar_url: Option<String>) -> Self {
+            Self {
+                name,
+                email,
+                avatar_url,
+            }
+        }
+
+        /// Getter for name.
+        pub fn name(&self) -> Option<&str> {
+            self.name.as_deref()
+        }
     }
 }
@@ -0,0 +1,188 @@
+pub mod api_token_service {
+    use crate::ExpenseError::{BadRequest, NotFound};
+    use crate::{internal_error, ExpenseError};
+    use argon2::password_hash::rand_core::{OsRng, RngCore};
+    use diesel::dsl::now;
+    use diesel::internal::derives::multiconnection::chrono::{DateTime, Utc};
+    use diesel::{
+        BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper,
+    };
+    use diesel_async::RunQueryDsl;
+    use expense_tracker_db::api_tokens::api_tokens::{ApiToken, NewApiToken};
+    use expense_tracker_db::schema::api_tokens::dsl::{
+        api_tokens, created_at, expires_at, id, last_used_at, token_hash, user_id,
+    };
+    use expense_tracker_db::setup::DbPool;
+    use sha2::{Digest, Sha256};
+    use std::fmt::Write;
+    use uuid::Uuid;
+
+    /// The start of every API token, which tells them apart from the JWTs of the OIDC provider.
+    pub const API_TOKEN_PREFIX: &str = "et_";
+
+    /// The number of random bytes of an API token.
+    const TOKEN_BYTES: usize = 32;
+
+    /// The number of characters of a token stored to tell the tokens of a user apart.
+    const STORED_PREFIX_LENGTH: usize = 11;
+
+    /// The maximum length of the name of an API token.
+    const MAX_NAME_LENGTH: usize = 24;
+
+    /// A service managing the API tokens of users.
+    #[derive(Clone)]
+    pub struct ApiTokenService {
+        db_pool: DbPool,
+    }
+
+    impl ApiTokenService {
+        /// Creates a new API token for the given user. Returns the stored token together with
+        /// the token itself, which is not stored and can't be shown again.
+        pub async fn create_token(
+            &self,
+            owner_id: Uuid,
+            name: &str,
+            scopes: Vec<String>,
+            expiry: Option<DateTime<Utc>>,
+        ) -> Result<(ApiToken, String), ExpenseError> {
+            let name = name.trim();
+
+            if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+                return Err(BadRequest(format!(
+                    "The name must have between 1 and {} characters",
+                    MAX_NAME_LENGTH
+                )));
+            }
+
+            if scopes.is_empty() {
+                return Err(BadRequest("A token needs at least one scope".to_string()));
+            }
+
+            if expiry.is_some_and(|expiry| expiry <= Utc::now()) {
+                return Err(BadRequest("The expiry must be in the future".to_string()));
+            }
+
+            let token = generate_token();
+            let new_token = NewApiToken::new(
+                owner_id,
+                name.to_string(),
+                token[..STORED_PREFIX_LENGTH].to_string(),
+                hash_token(&token),
+                scopes,
+                expiry,
+            );
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let api_token = diesel::insert_into(api_tokens)
+                .values(new_token)
+                .returning(ApiToken::as_returning())
+                .get_result::<ApiToken>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            Ok((api_token, token))
+        }
+
+        /// Gets all API tokens of the given user, including expired ones.
+        pub async fn get_tokens(&self, owner_id: Uuid) -> Result<Vec<ApiToken>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            api_tokens
+                .filter(user_id.eq(owner_id))
+                .order(created_at.asc())
+                .select(ApiToken::as_select())
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)
+        }
+
+        /// Revokes the API token with the given id, if it belongs to the given user.
+        pub async fn revoke_token(&self, owner_id: Uuid, token_id: i32) -> Result<(), ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let deleted = diesel::delete(api_tokens.filter(id.eq(token_id).and(user_id.eq(owner_id))))
+                .execute(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            if deleted == 0 {
+                return Err(NotFound(format!("API token {} could not be found", token_id)));
+            }
+
+            Ok(())
+        }
+
+        /// Gets the stored API token matching the given token, unless it expired, and marks it
+        /// as used.
+        pub async fn resolve(&self, token: &str) -> Result<Option<ApiToken>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            diesel::update(
+                api_tokens.filter(
+                    token_hash
+                        .eq(hash_token(token))
+                        .and(expires_at.is_null().or(expires_at.gt(now))),
+                ),
+            )
+            .set(last_used_at.eq(now))
+            .returning(ApiToken::as_returning())
+            .get_result::<ApiToken>(&mut conn)
+            .await
+            .optional()
+            .map_err(internal_error)
+        }
+    }
+
+    /// Generates a new random API token.
+    pub(crate) fn generate_token() -> String {
+        let mut bytes = [0u8; TOKEN_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+
+        bytes
+            .iter()
+            .fold(API_TOKEN_PREFIX.to_string(), |mut token, byte| {
+                let _ = write!(token, "{:02x}", byte);
+                token
+            })
+    }
+
+    /// Hashes the given token with SHA-256. Unlike passwords, tokens are random and long enough,
+    /// so a fast hash is sufficient and allows looking them up by their hash.
+    pub(crate) fn hash_token(token: &str) -> String {
+        Sha256::digest(token.as_bytes())
+            .iter()
+            .fold(String::new(), |mut hash, byte| {
+                let _ = write!(hash, "{:02x}", byte);
+                hash
+            })
+    }
+
+    /// Creates a new ApiTokenService.
+    pub fn new_service(pool: DbPool) -> ApiTokenService {
+        ApiTokenService { db_pool: pool }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api_token_service::api_token_service::{generate_token, hash_token, API_TOKEN_PREFIX};
+
+    #[test]
+    fn test_generate_token() {
+        let token = generate_token();
+
+        assert!(token.starts_with(API_TOKEN_PREFIX));
+        assert_eq!(token.len(), API_TOKEN_PREFIX.len() + 64);
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn test_hash_token() {
+        assert_eq!(
+            hash_token("et_abc"),
+            "ec23dc13954f46a2cc909c4f7ead356a5fd21669a02484862e719b9de6c8a70c"
+        );
+        assert_ne!(hash_token("et_abc"), hash_token("et_abd"));
+    }
+}
@@ -46,6 +46,7 @@ pub mod cron_manager_service {
             self.started = false;
         }
 
+        #[cfg(test)]
         pub(crate) fn id(&self) -> Uuid {
             self.id
         }
@@ -55,6 +56,12 @@ pub mod cron_manager_service {
         pub(crate) cron_jobs : Vec<CronJobWrapper>
     }
 
+    impl Default for CronManagerService {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
     impl CronManagerService {
 
         pub fn new() -> Self {
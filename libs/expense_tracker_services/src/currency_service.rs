@@ -1,12 +1,159 @@
 pub mod currency_service {
-    use crate::ExpenseError::Conflict;
+    use crate::money::money::convert;
+    use crate::ExpenseError::{BadRequest, Conflict, NotFound};
     use crate::{internal_error, not_found_error, ExpenseError};
-    use diesel::{ExpressionMethods, QueryDsl, SelectableHelper};
-    use diesel_async::RunQueryDsl;
+    use diesel::internal::derives::multiconnection::chrono::NaiveDate;
+    use diesel::result::Error;
+    use diesel::upsert::excluded;
+    use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, SelectableHelper};
+    use diesel_async::scoped_futures::ScopedFutureExt;
+    use diesel_async::{AsyncConnection, RunQueryDsl};
     use expense_tracker_db::currencies::currencies::{Currency, NewCurrency};
+    use expense_tracker_db::exchange_rates::exchange_rates::{ExchangeRate, NewExchangeRate};
     use expense_tracker_db::schema::currencies::dsl::currencies;
-    use expense_tracker_db::schema::currencies::{id, symbol};
+    use expense_tracker_db::schema::currencies::{code, id, symbol};
+    use expense_tracker_db::schema::exchange_rates::dsl::exchange_rates;
+    use expense_tracker_db::schema::exchange_rates::{
+        base_currency_id, quote_currency_id, rate, valid_on,
+    };
     use expense_tracker_db::setup::DbPool;
+    use std::collections::{BTreeSet, HashMap};
+
+    /// The maximum number of digits after the decimal separator a currency can have.
+    pub const MAX_MINOR_UNITS: i32 = 4;
+
+    /// The code of the currency all ECB reference rates are quoted against.
+    const ECB_BASE_CURRENCY: &str = "EUR";
+
+    /// The number of exchange rates inserted per statement when importing, which keeps the
+    /// number of bind parameters below the limit of postgres.
+    const IMPORT_CHUNK_SIZE: usize = 1000;
+
+    /// Holds exchange rates in memory to look up the rate between two currencies on a given
+    /// day without querying the database for every amount.
+    pub struct ExchangeRateTable {
+        /// Maps the base and quote currency to their rates, ordered by the day they are valid on.
+        rates: HashMap<(i32, i32), Vec<(NaiveDate, f64)>>,
+    }
+
+    impl ExchangeRateTable {
+        /// Creates a new ExchangeRateTable from the given exchange rates.
+        pub fn new(known_rates: Vec<ExchangeRate>) -> Self {
+            let mut rates: HashMap<(i32, i32), Vec<(NaiveDate, f64)>> = HashMap::new();
+
+            for exchange_rate in known_rates {
+                rates
+                    .entry((exchange_rate.base_currency_id(), exchange_rate.quote_currency_id()))
+                    .or_default()
+                    .push((exchange_rate.valid_on(), exchange_rate.rate()));
+            }
+
+            for day_rates in rates.values_mut() {
+                day_rates.sort_by_key(|(day, _)| *day);
+            }
+
+            Self { rates }
+        }
+
+        /// Gets the rate to convert from one currency into another on the given day. The latest
+        /// rate valid on or before that day is used, so that e.g. weekends without published
+        /// rates use the one of the preceding friday. If there is no rate between the two
+        /// currencies, their rates against a common third currency are combined, as for ECB rates
+        /// which are all quoted against EUR.
+        /// Returns None if no rate is known.
+        pub fn rate(&self, from: i32, to: i32, on: NaiveDate) -> Option<f64> {
+            if from == to {
+                return Some(1.0);
+            }
+
+            self.direct_rate(from, to, on).or_else(|| {
+                let intermediates = self
+                    .rates
+                    .keys()
+                    .flat_map(|(base, quote)| [*base, *quote])
+                    .filter(|currency| *currency != from && *currency != to)
+                    .collect::<BTreeSet<i32>>();
+
+                intermediates.into_iter().find_map(|via| {
+                    Some(self.direct_rate(from, via, on)? * self.direct_rate(via, to, on)?)
+                })
+            })
+        }
+
+        /// Converts the given amount in minor units of `from` into minor units of `to`, using the
+        /// rate on the given day. Returns None if no rate is known.
+        pub fn convert(&self, amount: i64, from: &Currency, to: &Currency, on: NaiveDate)
+            -> Option<i64> {
+            self.rate(from.id(), to.id(), on)
+                .map(|found| convert(amount, found, from.minor_units(), to.minor_units()))
+        }
+
+        /// Gets the rate between the two currencies, either as entered or inverted. If both are
+        /// known, the more recent one is used.
+        fn direct_rate(&self, from: i32, to: i32, on: NaiveDate) -> Option<f64> {
+            let latest = |base: i32, quote: i32| {
+                self.rates
+                    .get(&(base, quote))
+                    .and_then(|day_rates| day_rates.iter().rev().find(|(day, _)| *day <= on))
+                    .copied()
+            };
+
+            match (latest(from, to), latest(to, from)) {
+                (Some(direct), Some(inverse)) if inverse.0 > direct.0 => Some(1.0 / inverse.1),
+                (Some(direct), _) => Some(direct.1),
+                (None, Some(inverse)) => Some(1.0 / inverse.1),
+                (None, None) => None,
+            }
+        }
+    }
+
+    /// Parses the given ECB reference rate XML, like the daily or historic dumps published on
+    /// the ECB website. Returns the day, the currency code and the rate of one EUR in that
+    /// currency for each rate found.
+    pub(crate) fn parse_ecb_rates(xml: &str) -> Result<Vec<(NaiveDate, String, f64)>, ExpenseError> {
+        let document = roxmltree::Document::parse(xml)
+            .map_err(|err| BadRequest(format!("The exchange rates are not valid XML: {}", err)))?;
+
+        let mut parsed_rates = vec![];
+
+        for day in document
+            .descendants()
+            .filter(|node| node.has_tag_name("Cube") && node.has_attribute("time"))
+        {
+            let time = day.attribute("time").unwrap_or_default();
+            let day_valid_on = NaiveDate::parse_from_str(time, "%Y-%m-%d")
+                .map_err(|_| BadRequest(format!("Invalid date {} in exchange rates", time)))?;
+
+            for currency_rate in day.children().filter(|node| node.has_tag_name("Cube")) {
+                let (Some(currency_code), Some(currency_rate)) =
+                    (currency_rate.attribute("currency"), currency_rate.attribute("rate"))
+                else {
+                    continue;
+                };
+
+                let parsed_rate = currency_rate
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|parsed| parsed.is_finite() && *parsed > 0.0)
+                    .ok_or_else(|| {
+                        BadRequest(format!(
+                            "Invalid rate {} for {} in exchange rates",
+                            currency_rate, currency_code
+                        ))
+                    })?;
+
+                parsed_rates.push((day_valid_on, currency_code.to_string(), parsed_rate));
+            }
+        }
+
+        if parsed_rates.is_empty() {
+            return Err(BadRequest(
+                "The given XML does not contain any exchange rates".to_string(),
+            ));
+        }
+
+        Ok(parsed_rates)
+    }
 
     /// The service responsible for interacting with Currency related logic.
     #[derive(Clone)]
@@ -53,6 +200,24 @@ pub mod currency_service {
             &self,
             new_currency: NewCurrency,
         ) -> Result<Currency, ExpenseError> {
+            if !(0..=MAX_MINOR_UNITS).contains(&new_currency.minor_units()) {
+                return Err(BadRequest(format!(
+                    "The minor units of a currency must be between 0 and {}",
+                    MAX_MINOR_UNITS
+                )));
+            }
+
+            let is_valid_code = |new_code: &str| {
+                new_code.len() == 3 && new_code.chars().all(|c| c.is_ascii_uppercase())
+            };
+
+            if !new_currency.code().is_none_or(is_valid_code) {
+                return Err(BadRequest(format!(
+                    "{} is not a valid ISO 4217 currency code",
+                    new_currency.code().unwrap_or_default()
+                )));
+            }
+
             let existing_currency = self
                 .get_currency_by_symbol(new_currency.symbol().to_string())
                 .await;
@@ -60,12 +225,28 @@ pub mod currency_service {
             if existing_currency.is_ok() {
                 return Err(Conflict(format!(
                     "There is already a currency with symbol {}!",
-                    new_currency.symbol().to_string()
+                    new_currency.symbol()
                 )));
             }
 
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
+            if let Some(new_code) = new_currency.code() {
+                let existing_codes = currencies
+                    .filter(code.eq(new_code))
+                    .count()
+                    .get_result::<i64>(&mut conn)
+                    .await
+                    .map_err(internal_error)?;
+
+                if existing_codes > 0 {
+                    return Err(Conflict(format!(
+                        "There is already a currency with code {}!",
+                        new_code
+                    )));
+                }
+            }
+
             let res = diesel::insert_into(currencies)
                 .values(new_currency)
                 .returning(Currency::as_returning())
@@ -88,6 +269,156 @@ pub mod currency_service {
 
             Ok(loaded_currencies)
         }
+
+        /// Creates the given exchange rate. If there already is a rate for the same currencies
+        /// on the same day, it is replaced.
+        pub async fn create_exchange_rate(
+            &self,
+            new_exchange_rate: NewExchangeRate,
+        ) -> Result<ExchangeRate, ExpenseError> {
+            if !new_exchange_rate.rate().is_finite() || new_exchange_rate.rate() <= 0.0 {
+                return Err(BadRequest(
+                    "The exchange rate must be a positive number".to_string(),
+                ));
+            }
+
+            if new_exchange_rate.base_currency_id() == new_exchange_rate.quote_currency_id() {
+                return Err(BadRequest(
+                    "Base and quote currency of an exchange rate must differ".to_string(),
+                ));
+            }
+
+            // ensures that both currencies exist
+            self.get_currency_by_id(new_exchange_rate.base_currency_id()).await?;
+            self.get_currency_by_id(new_exchange_rate.quote_currency_id()).await?;
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            diesel::insert_into(exchange_rates)
+                .values(new_exchange_rate)
+                .on_conflict((base_currency_id, quote_currency_id, valid_on))
+                .do_update()
+                .set(rate.eq(excluded(rate)))
+                .returning(ExchangeRate::as_returning())
+                .get_result::<ExchangeRate>(&mut conn)
+                .await
+                .map_err(internal_error)
+        }
+
+        /// Gets all exchange rates, the most recent ones first.
+        pub async fn get_exchange_rates(&self) -> Result<Vec<ExchangeRate>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            exchange_rates
+                .order((valid_on.desc(), base_currency_id.asc(), quote_currency_id.asc()))
+                .select(ExchangeRate::as_select())
+                .load::<ExchangeRate>(&mut conn)
+                .await
+                .map_err(internal_error)
+        }
+
+        /// Imports the rates of the given ECB reference rate XML. Rates are matched to the
+        /// currencies by their code, rates of unknown currencies are skipped. Existing rates for
+        /// the same currencies and day are replaced.
+        /// Returns the number of imported rates.
+        pub async fn import_ecb_rates(&self, xml: &str) -> Result<usize, ExpenseError> {
+            let parsed_rates = parse_ecb_rates(xml)?;
+
+            let known_currencies = self.get_currencies().await?;
+
+            let euro = known_currencies
+                .iter()
+                .find(|currency| currency.code() == Some(ECB_BASE_CURRENCY))
+                .ok_or_else(|| {
+                    BadRequest(format!(
+                        "ECB rates can only be imported with a currency with code {}",
+                        ECB_BASE_CURRENCY
+                    ))
+                })?;
+
+            let new_rates = parsed_rates
+                .into_iter()
+                .filter_map(|(day_valid_on, currency_code, parsed_rate)| {
+                    known_currencies
+                        .iter()
+                        .find(|currency| {
+                            currency.id() != euro.id()
+                                && currency.code() == Some(currency_code.as_str())
+                        })
+                        .map(|quote| {
+                            NewExchangeRate::new(euro.id(), quote.id(), parsed_rate, day_valid_on)
+                        })
+                })
+                .collect::<Vec<NewExchangeRate>>();
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            conn.transaction::<_, Error, _>(|conn| {
+                async move {
+                    let mut imported = 0;
+
+                    for chunk in new_rates.chunks(IMPORT_CHUNK_SIZE) {
+                        imported += diesel::insert_into(exchange_rates)
+                            .values(chunk)
+                            .on_conflict((base_currency_id, quote_currency_id, valid_on))
+                            .do_update()
+                            .set(rate.eq(excluded(rate)))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(imported)
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(internal_error)
+        }
+
+        /// Loads the exchange rates needed to convert between the given currencies into an
+        /// ExchangeRateTable. Rates against other currencies are included, as they can be
+        /// combined to a cross rate.
+        pub async fn get_exchange_rate_table(
+            &self,
+            currency_ids: &[i32],
+        ) -> Result<ExchangeRateTable, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let loaded_rates = exchange_rates
+                .filter(
+                    base_currency_id
+                        .eq_any(currency_ids)
+                        .or(quote_currency_id.eq_any(currency_ids)),
+                )
+                .select(ExchangeRate::as_select())
+                .load::<ExchangeRate>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            Ok(ExchangeRateTable::new(loaded_rates))
+        }
+
+        /// Converts the given amount in minor units of `from` into minor units of `to`, using the
+        /// rate on the given day.
+        pub async fn convert(
+            &self,
+            amount: i64,
+            from: &Currency,
+            to: &Currency,
+            on: NaiveDate,
+        ) -> Result<i64, ExpenseError> {
+            self.get_exchange_rate_table(&[from.id(), to.id()])
+                .await?
+                .convert(amount, from, to, on)
+                .ok_or_else(|| {
+                    NotFound(format!(
+                        "There is no exchange rate from {} to {} on {}",
+                        from.symbol(),
+                        to.symbol(),
+                        on
+                    ))
+                })
+        }
     }
 
     /// Creates a new instance of CurrencyService.
@@ -95,3 +426,114 @@ pub mod currency_service {
         CurrencyService { db_pool: pool }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::currency_service::currency_service::{parse_ecb_rates, ExchangeRateTable};
+    use diesel::internal::derives::multiconnection::chrono::NaiveDate;
+    use expense_tracker_db::exchange_rates::exchange_rates::ExchangeRate;
+
+    const EUR: i32 = 1;
+    const CHF: i32 = 2;
+    const USD: i32 = 3;
+    const JPY: i32 = 4;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    fn table() -> ExchangeRateTable {
+        ExchangeRateTable::new(vec![
+            ExchangeRate::new(1, EUR, CHF, 0.94, day(5)),
+            ExchangeRate::new(2, EUR, CHF, 0.95, day(1)),
+            ExchangeRate::new(3, EUR, USD, 1.10, day(1)),
+            ExchangeRate::new(4, USD, CHF, 0.80, day(9)),
+        ])
+    }
+
+    #[test]
+    fn test_rate_uses_latest_rate_on_or_before_day() {
+        let table = table();
+
+        assert_eq!(table.rate(EUR, CHF, day(1)), Some(0.95));
+        assert_eq!(table.rate(EUR, CHF, day(4)), Some(0.95));
+        assert_eq!(table.rate(EUR, CHF, day(5)), Some(0.94));
+        assert_eq!(table.rate(EUR, CHF, day(30)), Some(0.94));
+        assert_eq!(table.rate(EUR, EUR, day(1)), Some(1.0));
+    }
+
+    #[test]
+    fn test_rate_without_rate_before_day() {
+        assert_eq!(table().rate(EUR, CHF, NaiveDate::from_ymd_opt(2026, 9, 30).unwrap()), None);
+        assert_eq!(table().rate(EUR, JPY, day(5)), None);
+    }
+
+    #[test]
+    fn test_rate_inverse() {
+        assert_eq!(table().rate(CHF, EUR, day(5)), Some(1.0 / 0.94));
+        assert_eq!(table().rate(USD, EUR, day(5)), Some(1.0 / 1.10));
+    }
+
+    #[test]
+    fn test_rate_cross_via_common_currency() {
+        let rate = table().rate(CHF, USD, day(5)).unwrap();
+
+        assert!((rate - 1.10 / 0.94).abs() < 1e-12);
+    }
+
+    /// A direct rate is preferred over combining rates, the more recent of a rate and its
+    /// inverse is used.
+    #[test]
+    fn test_rate_prefers_direct_and_recent_rates() {
+        let table = table();
+
+        assert_eq!(table.rate(USD, CHF, day(9)), Some(0.80));
+        assert_eq!(table.rate(CHF, USD, day(9)), Some(1.0 / 0.80));
+        assert!((table.rate(USD, CHF, day(8)).unwrap() - 0.94 / 1.10).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_parse_ecb_rates() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+    <gesmes:subject>Reference rates</gesmes:subject>
+    <gesmes:Sender>
+        <gesmes:name>European Central Bank</gesmes:name>
+    </gesmes:Sender>
+    <Cube>
+        <Cube time="2026-10-16">
+            <Cube currency="USD" rate="1.1612"/>
+            <Cube currency="CHF" rate="0.9321"/>
+        </Cube>
+        <Cube time="2026-10-15">
+            <Cube currency="USD" rate="1.1598"/>
+        </Cube>
+    </Cube>
+</gesmes:Envelope>"#;
+
+        let parsed = parse_ecb_rates(xml).unwrap();
+
+        assert_eq!(
+            parsed,
+            vec![
+                (day(16), "USD".to_string(), 1.1612),
+                (day(16), "CHF".to_string(), 0.9321),
+                (day(15), "USD".to_string(), 1.1598),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ecb_rates_invalid_input() {
+        assert!(parse_ecb_rates("not xml").is_err());
+        assert!(parse_ecb_rates("<Cube><Cube time=\"2026-10-16\"/></Cube>").is_err());
+        assert!(parse_ecb_rates(
+            "<Cube><Cube time=\"2026-10-16\"><Cube currency=\"USD\" rate=\"-1\"/></Cube></Cube>"
+        )
+        .is_err());
+        assert!(parse_ecb_rates(
+            "<Cube><Cube time=\"16.10.2026\"><Cube currency=\"USD\" rate=\"1.1\"/></Cube></Cube>"
+        )
+        .is_err());
+    }
+}
@@ -1,56 +1,389 @@
 pub mod expense_service {
     use crate::currency_service::currency_service;
     use crate::currency_service::currency_service::CurrencyService;
-    use crate::ExpenseError::Conflict;
+    use crate::money::money::{
+        allocate, format_minor_units, from_minor_units, round_splits, to_minor_units,
+    };
+    use crate::pot_event_service::pot_event_service::insert_events;
+    use crate::pot_role_service::pot_role_service;
+    use crate::pot_role_service::pot_role_service::PotRoleService;
+    use crate::settlement_service::settlement_service;
+    use crate::settlement_service::settlement_service::SettlementService;
+    use crate::ExpenseError::{BadRequest, Conflict, Forbidden, Internal, Locked, NotFound};
     use crate::{check_error, internal_error, not_found_error, ExpenseError};
+    use diesel::internal::derives::multiconnection::chrono::{DateTime, NaiveDate, Utc};
     use diesel::result::Error;
-    use diesel::{BoolExpressionMethods, ExpressionMethods, JoinOnDsl, QueryDsl, SelectableHelper};
+    use diesel::pg::Pg;
+    use diesel::{
+        BoolExpressionMethods, ExpressionMethods, JoinOnDsl, PgTextExpressionMethods, QueryDsl,
+        SelectableHelper,
+    };
     use diesel_async::scoped_futures::ScopedFutureExt;
-    use diesel_async::{AsyncConnection, RunQueryDsl};
+    use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
     use expense_tracker_db::currencies::currencies::Currency;
     use expense_tracker_db::expenses::expenses::{Expense, NewExpense};
+    use expense_tracker_db::pot_events::pot_events::{NewPotEvent, PotEventKind};
+    use expense_tracker_db::pots::pots::{Pot, PotRole};
     use expense_tracker_db::schema::currencies::dsl::currencies;
     use expense_tracker_db::schema::currencies::id as currencies_id;
     use expense_tracker_db::schema::expense_splits::dsl::expense_splits;
-    use expense_tracker_db::schema::expense_splits::expense_id as split_expense_id;
+    use expense_tracker_db::schema::expense_splits::{
+        expense_id as split_expense_id, is_paid as split_is_paid, user_id as split_user_id,
+    };
     use expense_tracker_db::schema::expenses::dsl::expenses;
     use expense_tracker_db::schema::expenses::{
-        id as expense_id, pot_id as expense_pot_id,
+        created_at as expense_created_at, currency_id as expense_currency_id,
+        description as expense_description, id as expense_id, owner_id as expense_owner_id,
+        pot_id as expense_pot_id, spent_at as expense_spent_at,
+        total_amount as expense_total_amount,
     };
     use expense_tracker_db::schema::pots::dsl::pots;
-    use expense_tracker_db::schema::pots::id as pots_id;
+    use expense_tracker_db::schema::pots::{archived, id as pots_id};
     use expense_tracker_db::schema::pots_to_users::dsl::pots_to_users;
     use expense_tracker_db::schema::pots_to_users::{pot_id, user_id};
+    use expense_tracker_db::schema::settlements;
+    use expense_tracker_db::settlements::settlements::Settlement;
     use expense_tracker_db::setup::DbPool;
     use expense_tracker_db::splits::splits::{NewExpenseSplit, Split};
+    use std::collections::{BTreeMap, HashMap, HashSet};
     use uuid::Uuid;
 
     /// Represents a joined `Expense`, with a `Vec<Split>` and a `Currency`.
     pub type JoinedExpense = (Expense, Vec<Split>, Currency);
 
+    /// An expense that has been validated and is ready to be inserted, with its splits and
+    /// currency.
+    pub(crate) type PreparedExpense = (NewExpense, Vec<NewExpenseSplit>, Currency);
+
+    /// Maps an unpaid split, identified by its expense id and user id, to the amount of it that
+    /// has already been covered by settlements, in minor units.
+    pub type SettledAmounts = HashMap<(i32, Uuid), i64>;
+
+    /// The net balance of a user in a pot, in minor units.
+    pub struct PotBalance {
+        balances: Vec<(Currency, i64)>,
+        converted: Option<i64>,
+    }
+
+    impl PotBalance {
+        /// Constructor for PotBalance.
+        pub fn new(balances: Vec<(Currency, i64)>, converted: Option<i64>) -> Self {
+            Self {
+                balances,
+                converted,
+            }
+        }
+
+        /// The balance per currency. Currencies without an open balance are left out.
+        pub fn balances(&self) -> &Vec<(Currency, i64)> {
+            &self.balances
+        }
+
+        /// The balances of all currencies converted into the default currency of the pot.
+        /// None if an exchange rate needed for the conversion is unknown.
+        pub fn converted(&self) -> Option<i64> {
+            self.converted
+        }
+    }
+
+    /// The maximum number of entries on a single page, e.g. of expenses.
+    pub const MAX_PAGE_SIZE: i64 = 200;
+
+    /// Filters the expenses of a pot. Filters set to `None` are not applied.
+    pub struct ExpenseFilter {
+        owner_id: Option<Uuid>,
+        /// Only expenses the given user has a split of.
+        participant_id: Option<Uuid>,
+        currency_id: Option<i32>,
+        /// If true, only expenses whose splits are all paid. If false, only expenses with at
+        /// least one unpaid split.
+        paid: Option<bool>,
+        /// Only expenses whose description contains the given text, ignoring case.
+        description: Option<String>,
+        /// Only expenses spent at or after the given time.
+        spent_from: Option<DateTime<Utc>>,
+        /// Only expenses spent before the given time.
+        spent_to: Option<DateTime<Utc>>,
+    }
+
+    impl ExpenseFilter {
+        /// Constructor for ExpenseFilter.
+        #[allow(clippy::too_many_arguments)]
+        pub fn new(
+            owner_id: Option<Uuid>,
+            participant_id: Option<Uuid>,
+            currency_id: Option<i32>,
+            paid: Option<bool>,
+            description: Option<String>,
+            spent_from: Option<DateTime<Utc>>,
+            spent_to: Option<DateTime<Utc>>,
+        ) -> Self {
+            Self {
+                owner_id,
+                participant_id,
+                currency_id,
+                paid,
+                description,
+                spent_from,
+                spent_to,
+            }
+        }
+    }
+
+    /// The fields the expenses of a pot can be sorted by.
+    pub enum ExpenseSortField {
+        SpentAt,
+        CreatedAt,
+        TotalAmount,
+        Description,
+    }
+
+    /// The order of the expenses of a pot. Expenses with equal values are ordered by their id.
+    pub struct ExpenseSort {
+        field: ExpenseSortField,
+        descending: bool,
+    }
+
+    impl ExpenseSort {
+        /// Constructor for ExpenseSort.
+        pub fn new(field: ExpenseSortField, descending: bool) -> Self {
+            Self { field, descending }
+        }
+    }
+
+    /// Selects a page of a listing.
+    pub struct Pagination {
+        /// The number of the page, starting at 1.
+        page: i64,
+        per_page: i64,
+    }
+
+    impl Pagination {
+        /// Constructor for Pagination.
+        pub fn new(page: i64, per_page: i64) -> Self {
+            Self { page, per_page }
+        }
+
+        /// Checks that the page and its size are within bounds.
+        pub(crate) fn check(&self) -> Result<(), ExpenseError> {
+            if self.page < 1 || !(1..=MAX_PAGE_SIZE).contains(&self.per_page) {
+                return Err(BadRequest(format!(
+                    "The page must be at least 1 and the page size between 1 and {}",
+                    MAX_PAGE_SIZE
+                )));
+            }
+
+            if (self.page - 1).checked_mul(self.per_page).is_none() {
+                return Err(BadRequest(format!("Page {} is out of range", self.page)));
+            }
+
+            Ok(())
+        }
+
+        /// The number of entries on the pages before this one. Saturates for pages out of range,
+        /// which `check` rejects.
+        pub(crate) fn offset(&self) -> i64 {
+            (self.page - 1).saturating_mul(self.per_page)
+        }
+
+        /// Getter for per_page.
+        pub(crate) fn per_page(&self) -> i64 {
+            self.per_page
+        }
+    }
+
+    /// A single page of the expenses of a pot.
+    pub struct ExpensePage {
+        expenses: Vec<JoinedExpense>,
+        total_count: i64,
+    }
+
+    impl ExpensePage {
+        /// Getter for expenses.
+        pub fn expenses(&self) -> &Vec<JoinedExpense> {
+            &self.expenses
+        }
+
+        /// The number of expenses matching the filter on all pages.
+        pub fn total_count(&self) -> i64 {
+            self.total_count
+        }
+    }
+
+    /// Escapes the wildcards of a LIKE pattern in the given text, so it is matched literally.
+    pub(crate) fn escape_like(text: &str) -> String {
+        text.replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    }
+
+    /// The maximum length of an expense's description.
+    const MAX_DESCRIPTION_LENGTH: usize = 24;
+
+    /// The fields of an expense that can be updated. Fields set to `None` are left untouched.
+    #[derive(diesel::AsChangeset)]
+    #[diesel(table_name = expense_tracker_db::schema::expenses)]
+    pub struct ExpenseUpdate {
+        description: Option<String>,
+        currency_id: Option<i32>,
+        spent_at: Option<DateTime<Utc>>,
+        total_amount: Option<i64>,
+        updated_at: Option<DateTime<Utc>>,
+    }
+
+    impl ExpenseUpdate {
+        /// The total amount is updated along with the splits.
+        pub fn new(
+            description: Option<String>,
+            currency_id: Option<i32>,
+            spent_at: Option<DateTime<Utc>>,
+        ) -> Self {
+            Self {
+                description,
+                currency_id,
+                spent_at,
+                total_amount: None,
+                updated_at: None,
+            }
+        }
+    }
+
+    /// Creates an event of the given kind about the given expense, e.g. that it has been added.
+    fn expense_event(
+        expense: &Expense,
+        currency: &Currency,
+        actor_id: Uuid,
+        kind: PotEventKind,
+    ) -> NewPotEvent {
+        NewPotEvent::new(expense.pot_id(), Some(actor_id), kind)
+            .with_expense(expense.id())
+            .with_subject(expense.owner_id())
+            .with_details(format!(
+                "{} ({} {})",
+                expense.description(),
+                format_minor_units(expense.total_amount(), currency.minor_units()),
+                currency.symbol()
+            ))
+    }
+
+    /// Describes how the total amount of an expense is split among its users. All values are
+    /// given in major units, e.g. 10.05.
+    pub enum SplitMode {
+        /// Splits the total equally among the given users.
+        Equal(Vec<Uuid>),
+        /// Splits the total by the given percentages, which must add up to 100.
+        Percentage(Vec<(Uuid, f64)>),
+        /// Splits the total proportionally to the given shares.
+        Shares(Vec<(Uuid, f64)>),
+        /// Uses the given amounts. Users without an amount share the remainder of the total
+        /// equally. Without a total, all users need an amount and the total is their sum.
+        Exact(Vec<(Uuid, Option<f64>)>),
+    }
+
+    impl SplitMode {
+        /// Gets the users the total is split among.
+        fn users(&self) -> Vec<Uuid> {
+            match self {
+                SplitMode::Equal(users) => users.clone(),
+                SplitMode::Percentage(values) | SplitMode::Shares(values) => {
+                    values.iter().map(|(user, _)| *user).collect()
+                }
+                SplitMode::Exact(amounts) => amounts.iter().map(|(user, _)| *user).collect(),
+            }
+        }
+    }
+
     /// Struct working with Expense related logic.
     #[derive(Clone)]
     pub struct ExpenseService {
         db_pool: DbPool,
         currency_service: CurrencyService,
+        settlement_service: SettlementService,
+        pot_role_service: PotRoleService,
     }
 
     impl ExpenseService {
-        /// Creates a new Expense for the given Pot.
+        /// Creates a new Expense for the given Pot. The splits are computed from the given
+        /// `total_amount` and `split_mode`. The owner of the expense has to be at least a member
+        /// of the pot, as viewers can't add expenses.
         pub async fn create_expense(
             &self,
             new_expense: NewExpense,
-            splits: Vec<NewExpenseSplit>,
-        ) -> Result<(Expense, Vec<Split>, Currency), ExpenseError> {
+            total_amount: Option<f64>,
+            split_mode: SplitMode,
+        ) -> Result<JoinedExpense, ExpenseError> {
+            self.pot_role_service
+                .require_role(new_expense.pot_id(), new_expense.owner_id(), PotRole::Member)
+                .await?;
+
+            let currency = self
+                .currency_service
+                .get_currency_by_id(new_expense.currency_id())
+                .await?;
+
+            let member_ids = self.get_member_ids(new_expense.pot_id()).await?;
+
+            let actor_id = new_expense.owner_id();
+            let prepared = Self::prepare_expense(
+                new_expense,
+                total_amount,
+                &split_mode,
+                currency,
+                &member_ids,
+            )?;
+
+            self.insert_expenses(vec![prepared], actor_id)
+                .await?
+                .pop()
+                .ok_or_else(|| Internal("The expense has not been created".to_string()))
+        }
+
+        /// Validates the given expense and computes its splits from the given `total_amount` and
+        /// `split_mode`, so that it can be inserted with `insert_expenses`. All users of the
+        /// splits must be among the given members of the pot.
+        pub(crate) fn prepare_expense(
+            mut new_expense: NewExpense,
+            total_amount: Option<f64>,
+            split_mode: &SplitMode,
+            currency: Currency,
+            member_ids: &[Uuid],
+        ) -> Result<PreparedExpense, ExpenseError> {
+            if new_expense.description().chars().count() > MAX_DESCRIPTION_LENGTH {
+                return Err(BadRequest(format!(
+                    "The description must not be longer than {} characters",
+                    MAX_DESCRIPTION_LENGTH
+                )));
+            }
+
+            let splits = Self::compute_splits(
+                total_amount,
+                split_mode,
+                currency.minor_units(),
+                new_expense.owner_id(),
+                member_ids,
+            )?;
+
+            new_expense.set_total_amount(splits.iter().map(|split| split.amount()).sum());
+
+            Ok((new_expense, splits, currency))
+        }
+
+        /// Inserts the given prepared expenses with their splits in a single transaction, so
+        /// either all or none of them are created. Records that the given actor added them.
+        pub(crate) async fn insert_expenses(
+            &self,
+            prepared_expenses: Vec<PreparedExpense>,
+            actor_id: Uuid,
+        ) -> Result<Vec<JoinedExpense>, ExpenseError> {
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
-            let new_expense_clone = new_expense.clone();
+            conn.transaction::<_, Error, _>(|conn| {
+                async move {
+                    let mut created = vec![];
 
-            let result = conn
-                .transaction::<_, Error, _>(|conn| {
-                    async move {
+                    for (new_expense, splits, currency) in prepared_expenses {
                         let expense = diesel::insert_into(expenses)
-                            .values(new_expense_clone)
+                            .values(new_expense)
                             .returning(Expense::as_returning())
                             .get_result::<Expense>(conn)
                             .await?;
@@ -64,19 +397,38 @@ pub mod expense_service {
                             .get_results::<Split>(conn)
                             .await?;
 
-                        let currency = self
-                            .currency_service
-                            .get_currency_by_id(expense.currency_id())
-                            .await?;
+                        let event = expense_event(
+                            &expense,
+                            &currency,
+                            actor_id,
+                            PotEventKind::ExpenseAdded,
+                        );
+                        insert_events(conn, vec![event]).await?;
 
-                        Ok((expense, splits, currency))
+                        created.push((expense, splits, currency));
                     }
-                    .scope_boxed()
-                })
-                .await
-                .map_err(not_found_error)?;
 
-            Ok(result)
+                    Ok(created)
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(not_found_error)
+        }
+
+        /// Gets the ids of all users of the given pot, including guests and viewers.
+        pub(crate) async fn get_member_ids(
+            &self,
+            target_pot_id: i32,
+        ) -> Result<Vec<Uuid>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            pots_to_users
+                .filter(pot_id.eq(target_pot_id))
+                .select(user_id)
+                .load::<Uuid>(&mut conn)
+                .await
+                .map_err(internal_error)
         }
 
         /// Gets a single expense with all associated data by the given id.
@@ -87,11 +439,8 @@ pub mod expense_service {
         ) -> Result<JoinedExpense, ExpenseError> {
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
-            // I'm just not able to make a join work with diesel and bb8.
-            // I have to think about it a bit longer.
             let expense = expenses
-                .left_join(pots.on(pots_id.eq(expense_pot_id)))
-                .left_join(pots_to_users.on(pots_id.eq(pots_id)))
+                .inner_join(pots_to_users.on(pot_id.eq(expense_pot_id)))
                 .filter(expense_id.eq(target_id).and(user_id.eq(requester_id)))
                 .select(Expense::as_select())
                 .get_result::<Expense>(&mut conn)
@@ -133,26 +482,189 @@ pub mod expense_service {
                 .await
                 .map_err(internal_error)?;
 
+            Self::join_splits_and_currencies(&mut conn, pot_expenses).await
+        }
+
+        /// Gets a single page of the expenses of the given pot that match the given filter,
+        /// sorted as requested. Returns NotFound if the requester is not part of the pot.
+        pub async fn get_expense_page(
+            &self,
+            target_pot_id: i32,
+            requester_id: Uuid,
+            filter: &ExpenseFilter,
+            sort: &ExpenseSort,
+            page: &Pagination,
+        ) -> Result<ExpensePage, ExpenseError> {
+            page.check()?;
+
+            self.pot_role_service
+                .require_role(target_pot_id, requester_id, PotRole::Viewer)
+                .await?;
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let total_count = Self::filtered_expenses(target_pot_id, filter)
+                .count()
+                .get_result::<i64>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            let mut query = Self::filtered_expenses(target_pot_id, filter);
+
+            query = match (&sort.field, sort.descending) {
+                (ExpenseSortField::SpentAt, false) => query.order(expense_spent_at.asc()),
+                (ExpenseSortField::SpentAt, true) => query.order(expense_spent_at.desc()),
+                (ExpenseSortField::CreatedAt, false) => query.order(expense_created_at.asc()),
+                (ExpenseSortField::CreatedAt, true) => query.order(expense_created_at.desc()),
+                (ExpenseSortField::TotalAmount, false) => query.order(expense_total_amount.asc()),
+                (ExpenseSortField::TotalAmount, true) => query.order(expense_total_amount.desc()),
+                (ExpenseSortField::Description, false) => query.order(expense_description.asc()),
+                (ExpenseSortField::Description, true) => query.order(expense_description.desc()),
+            };
+
+            // the id keeps the order stable for expenses with equal values
+            query = if sort.descending {
+                query.then_order_by(expense_id.desc())
+            } else {
+                query.then_order_by(expense_id.asc())
+            };
+
+            let page_expenses = query
+                .offset(page.offset())
+                .limit(page.per_page())
+                .select(Expense::as_select())
+                .load::<Expense>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            Ok(ExpensePage {
+                expenses: Self::join_splits_and_currencies(&mut conn, page_expenses).await?,
+                total_count,
+            })
+        }
+
+        /// Gets up to `limit` expenses of the given pot with their splits and currencies, ordered
+        /// by the time they were spent at and their id, starting after the given position of
+        /// the previous batch. Doesn't check the requester.
+        pub(crate) async fn get_expense_batch(
+            &self,
+            target_pot_id: i32,
+            after: Option<(DateTime<Utc>, i32)>,
+            limit: i64,
+        ) -> Result<Vec<JoinedExpense>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let mut query = expenses.filter(expense_pot_id.eq(target_pot_id)).into_boxed();
+
+            if let Some((last_spent_at, last_id)) = after {
+                query = query.filter(
+                    expense_spent_at.gt(last_spent_at).or(expense_spent_at
+                        .eq(last_spent_at)
+                        .and(expense_id.gt(last_id))),
+                );
+            }
+
+            let batch = query
+                .order((expense_spent_at.asc(), expense_id.asc()))
+                .limit(limit)
+                .select(Expense::as_select())
+                .load::<Expense>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            Self::join_splits_and_currencies(&mut conn, batch).await
+        }
+
+        /// Builds the query for the expenses of the given pot that match the given filter.
+        fn filtered_expenses(
+            target_pot_id: i32,
+            filter: &ExpenseFilter,
+        ) -> expense_tracker_db::schema::expenses::BoxedQuery<'static, Pg> {
+            let mut query = expenses.filter(expense_pot_id.eq(target_pot_id)).into_boxed();
+
+            if let Some(owner) = filter.owner_id {
+                query = query.filter(expense_owner_id.eq(owner));
+            }
+
+            if let Some(participant) = filter.participant_id {
+                query = query.filter(
+                    expense_id.eq_any(
+                        expense_splits
+                            .filter(split_user_id.eq(participant))
+                            .select(split_expense_id),
+                    ),
+                );
+            }
+
+            if let Some(currency) = filter.currency_id {
+                query = query.filter(expense_currency_id.eq(currency));
+            }
+
+            // an expense is paid, once all of its splits are paid
+            let unpaid_expenses = expense_splits
+                .filter(split_is_paid.eq(false))
+                .select(split_expense_id);
+
+            query = match filter.paid {
+                Some(true) => query.filter(expense_id.ne_all(unpaid_expenses)),
+                Some(false) => query.filter(expense_id.eq_any(unpaid_expenses)),
+                None => query,
+            };
+
+            if let Some(text) = &filter.description {
+                query =
+                    query.filter(expense_description.ilike(format!("%{}%", escape_like(text))));
+            }
+
+            if let Some(from) = filter.spent_from {
+                query = query.filter(expense_spent_at.ge(from));
+            }
+
+            if let Some(to) = filter.spent_to {
+                query = query.filter(expense_spent_at.lt(to));
+            }
+
+            query
+        }
+
+        /// Loads the splits and currencies of the given expenses, using a single query for all
+        /// splits.
+        async fn join_splits_and_currencies(
+            conn: &mut AsyncPgConnection,
+            loaded_expenses: Vec<Expense>,
+        ) -> Result<Vec<JoinedExpense>, ExpenseError> {
+            let loaded_ids: Vec<i32> = loaded_expenses.iter().map(|e| e.id()).collect();
+
+            let all_splits = expense_splits
+                .filter(split_expense_id.eq_any(&loaded_ids))
+                .select(Split::as_select())
+                .load::<Split>(conn)
+                .await
+                .map_err(internal_error)?;
+
             let all_currencies = currencies
-                .get_results::<Currency>(&mut conn)
+                .get_results::<Currency>(conn)
                 .await
                 .map_err(internal_error)?;
 
+            let mut splits_by_expense: HashMap<i32, Vec<Split>> = HashMap::new();
+
+            for split in all_splits {
+                splits_by_expense
+                    .entry(split.expense_id())
+                    .or_default()
+                    .push(split);
+            }
+
             let mut result: Vec<JoinedExpense> = vec![];
 
-            for expense in pot_expenses {
+            for expense in loaded_expenses {
                 let currency = all_currencies
                     .iter()
-                    .filter(|c| c.id() == expense.currency_id())
-                    .next();
+                    .find(|c| c.id() == expense.currency_id());
 
                 if let Some(currency) = currency {
-                    let splits = expense_splits
-                        .filter(split_expense_id.eq(expense.id()))
-                        .get_results::<Split>(&mut conn)
-                        .await
-                        .map_err(internal_error)?;
-
+                    let splits = splits_by_expense.remove(&expense.id()).unwrap_or_default();
                     result.push((expense, splits, (*currency).clone()))
                 }
             }
@@ -160,36 +672,574 @@ pub mod expense_service {
             Ok(result)
         }
 
-        /// Gets the net_balance of the givne target_pot for the given requester_id.
-        /// If the balance returned is positive, the requester is owed money. If negative
-        /// they owe others money.
+        /// Gets the net balance of the given target_pot for the given requester_id, per currency
+        /// and converted into the default currency of the pot. See `get_pot_net_balances`.
         pub async fn get_pot_net_balance(&self, target_pot_id: i32, requester_id: Uuid)
-            -> Result<f64, ExpenseError> {
+            -> Result<PotBalance, ExpenseError> {
+            let target_pot = {
+                let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+                pots.filter(pots_id.eq(target_pot_id))
+                    .select(Pot::as_select())
+                    .first::<Pot>(&mut conn)
+                    .await
+                    .map_err(not_found_error)?
+            };
+
+            let all_currencies = self.currency_service.get_currencies().await?;
+
+            self.get_pot_net_balances(&[&target_pot], &all_currencies, requester_id)
+                .await?
+                .remove(&target_pot_id)
+                .ok_or_else(|| NotFound(format!("Pot {} could not be found", target_pot_id)))
+        }
+
+        /// Gets the net balances of the given loaded_pots for the given requester_id, per
+        /// currency and converted into the default currency of each pot, keyed by the id of the
+        /// pot. Expenses are converted at the rate of the day they were spent on, settlements at
+        /// the rate of the day they were made. If a balance is positive, the requester is owed
+        /// money. If negative they owe others money. All balances are given in minor units.
+        /// Runs four queries, regardless of the number of pots.
+        pub async fn get_pot_net_balances(
+            &self,
+            loaded_pots: &[&Pot],
+            all_currencies: &[Currency],
+            requester_id: Uuid,
+        ) -> Result<HashMap<i32, PotBalance>, ExpenseError> {
+            let loaded_pot_ids: Vec<i32> = loaded_pots.iter().map(|pot| pot.id()).collect();
+
+            let pot_settlements = self
+                .settlement_service
+                .get_open_settlements_by_pot_ids(&loaded_pot_ids)
+                .await?;
+
+            let (pot_expenses, all_splits) = {
+                let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+                let pot_expenses = expenses
+                    .left_join(pots_to_users.on(pot_id.eq(expense_pot_id)))
+                    .filter(
+                        expense_pot_id
+                            .eq_any(&loaded_pot_ids)
+                            .and(user_id.eq(requester_id)),
+                    )
+                    .select(Expense::as_select())
+                    .get_results::<Expense>(&mut conn)
+                    .await
+                    .map_err(internal_error)?;
+
+                let loaded_ids: Vec<i32> = pot_expenses.iter().map(|e| e.id()).collect();
+
+                let all_splits = expense_splits
+                    .filter(split_expense_id.eq_any(&loaded_ids))
+                    .select(Split::as_select())
+                    .load::<Split>(&mut conn)
+                    .await
+                    .map_err(internal_error)?;
+
+                (pot_expenses, all_splits)
+            };
+
+            let currency_ids = pot_expenses
+                .iter()
+                .map(|expense| expense.currency_id())
+                .chain(pot_settlements.iter().map(|settlement| settlement.currency_id()))
+                .chain(loaded_pots.iter().map(|pot| pot.default_currency_id()))
+                .collect::<HashSet<i32>>()
+                .into_iter()
+                .collect::<Vec<i32>>();
+
+            let rate_table = self
+                .currency_service
+                .get_exchange_rate_table(&currency_ids)
+                .await?;
+
+            let find_currency = |to_find: i32| {
+                all_currencies
+                    .iter()
+                    .find(|currency| currency.id() == to_find)
+                    .ok_or_else(|| NotFound(format!("Currency {} could not be found", to_find)))
+            };
+
+            let mut splits_by_expense: HashMap<i32, Vec<Split>> = HashMap::new();
+
+            for split in all_splits {
+                splits_by_expense
+                    .entry(split.expense_id())
+                    .or_default()
+                    .push(split);
+            }
+
+            let mut expenses_by_pot: HashMap<i32, Vec<Expense>> = HashMap::new();
+
+            for expense in pot_expenses {
+                expenses_by_pot
+                    .entry(expense.pot_id())
+                    .or_default()
+                    .push(expense);
+            }
+
+            let mut settlements_by_pot: HashMap<i32, Vec<Settlement>> = HashMap::new();
+
+            for settlement in pot_settlements {
+                settlements_by_pot
+                    .entry(settlement.pot_id())
+                    .or_default()
+                    .push(settlement);
+            }
+
+            let mut result = HashMap::new();
+
+            for pot in loaded_pots {
+                let pot_currency = find_currency(pot.default_currency_id())?;
+
+                let mut balances: BTreeMap<i32, (Currency, i64)> = BTreeMap::new();
+                let mut converted = Some(0);
+
+                // adds the given amount to the balance of its currency and to the converted
+                // balance
+                let mut add = |currency: &Currency, amount: i64, day: NaiveDate| {
+                    balances
+                        .entry(currency.id())
+                        .or_insert_with(|| (currency.clone(), 0))
+                        .1 += amount;
+
+                    converted = converted.and_then(|total: i64| {
+                        Some(total + rate_table.convert(amount, currency, pot_currency, day)?)
+                    });
+                };
+
+                for expense in expenses_by_pot.remove(&pot.id()).unwrap_or_default() {
+                    // expenses in unknown currencies are left out, as in the expense listing
+                    let Ok(currency) = find_currency(expense.currency_id()) else {
+                        continue;
+                    };
+
+                    let mut expense_balance = 0;
+
+                    for split in splits_by_expense.remove(&expense.id()).unwrap_or_default() {
+                        if !split.is_paid() {
+                            if expense.owner_id() == requester_id
+                                && split.user_id() != expense.owner_id() {
+                                expense_balance += split.amount();
+                            } else if expense.owner_id() != requester_id
+                                && split.user_id() == requester_id {
+                                expense_balance -= split.amount();
+                            }
+                        }
+                    }
+
+                    if expense_balance != 0 {
+                        add(currency, expense_balance, expense.spent_at().date_naive());
+                    }
+                }
+
+                // settlements the requester paid reduce their debt, received ones reduce what
+                // they are owed
+                for settlement in settlements_by_pot.remove(&pot.id()).unwrap_or_default() {
+                    let currency = find_currency(settlement.currency_id())?;
+                    let day = settlement.created_at().date_naive();
+
+                    if settlement.payer_id() == requester_id {
+                        add(currency, settlement.open_amount(), day);
+                    } else if settlement.receiver_id() == requester_id {
+                        add(currency, -settlement.open_amount(), day);
+                    }
+                }
+
+                result.insert(
+                    pot.id(),
+                    PotBalance {
+                        balances: balances
+                            .into_values()
+                            .filter(|(_, balance)| *balance != 0)
+                            .collect(),
+                        converted,
+                    },
+                );
+            }
+
+            Ok(result)
+        }
+
+        /// Gets the amounts of the unpaid splits of the given pot that are already covered by
+        /// settlements.
+        pub async fn get_settled_amounts(&self, target_pot_id: i32, requester_id: Uuid)
+            -> Result<SettledAmounts, ExpenseError> {
             let joined_expenses = self
                 .get_expenses_by_pot_id(target_pot_id, requester_id)
-                .await
-                .map_err(check_error)?;
+                .await?;
+
+            let pot_settlements = self
+                .settlement_service
+                .get_open_settlements_by_pot_id(target_pot_id)
+                .await?;
+
+            let expenses_with_splits = joined_expenses
+                .iter()
+                .map(|(expense, splits, _)| (expense, splits))
+                .collect();
+
+            Ok(Self::allocate_settlements(expenses_with_splits, &pot_settlements))
+        }
+
+        /// Distributes the open amounts of the given settlements onto the unpaid splits they can
+        /// cover. A settlement covers splits the payer owes the receiver in the settlement's
+        /// currency, starting with the oldest expense.
+        pub(crate) fn allocate_settlements(
+            mut expenses_with_splits: Vec<(&Expense, &Vec<Split>)>,
+            pot_settlements: &[Settlement],
+        ) -> SettledAmounts {
+            // maps payer, receiver and currency to the amount that is not yet allocated
+            let mut remaining: HashMap<(Uuid, Uuid, i32), i64> = HashMap::new();
+
+            for settlement in pot_settlements {
+                *remaining
+                    .entry((
+                        settlement.payer_id(),
+                        settlement.receiver_id(),
+                        settlement.currency_id(),
+                    ))
+                    .or_insert(0) += settlement.open_amount();
+            }
 
-            let mut net_balance = 0.0;
+            expenses_with_splits.sort_by_key(|(expense, _)| expense.id());
 
-            for joined_expense in joined_expenses {
-                let expense = joined_expense.0;
-                let splits = joined_expense.1;
+            let mut settled_amounts = SettledAmounts::new();
 
-                splits.iter().for_each(|split| {
-                    if !split.is_paid() {
-                        if expense.owner_id() == requester_id
-                            && split.user_id() != expense.owner_id() {
-                            net_balance += split.amount();
-                        } else if expense.owner_id() != requester_id
-                            && split.user_id() == requester_id {
-                            net_balance -= split.amount();
+            for (expense, splits) in expenses_with_splits {
+                for split in splits {
+                    if split.is_paid() || split.user_id() == expense.owner_id() {
+                        continue;
+                    }
+
+                    let key = (split.user_id(), expense.owner_id(), expense.currency_id());
+
+                    if let Some(open) = remaining.get_mut(&key) {
+                        let settled = (*open).min(split.amount());
+
+                        if settled > 0 {
+                            *open -= settled;
+                            settled_amounts.insert((expense.id(), split.user_id()), settled);
                         }
                     }
-                })
+                }
+            }
+
+            settled_amounts
+        }
+
+        /// Updates the expense with the given `target_id`. If a `split_mode` is given, the splits
+        /// computed from it and the `total_amount` replace the existing splits of the expense.
+        /// Only the owner can update an expense and only as long as none of the other users has
+        /// paid their split.
+        pub async fn update_expense(
+            &self,
+            target_id: i32,
+            mut expense_update: ExpenseUpdate,
+            total_amount: Option<f64>,
+            split_mode: Option<SplitMode>,
+            requester_id: Uuid,
+        ) -> Result<JoinedExpense, ExpenseError> {
+            let (expense, _, currency) = self
+                .get_modifiable_expense(target_id, requester_id)
+                .await?;
+
+            if expense_update
+                .description
+                .as_ref()
+                .is_some_and(|description| description.chars().count() > MAX_DESCRIPTION_LENGTH)
+            {
+                return Err(BadRequest(format!(
+                    "The description must not be longer than {} characters",
+                    MAX_DESCRIPTION_LENGTH
+                )));
+            }
+
+            let currency = match expense_update.currency_id {
+                Some(new_currency_id) => {
+                    let new_currency = self
+                        .currency_service
+                        .get_currency_by_id(new_currency_id)
+                        .await?;
+
+                    // the amounts of the existing splits are stored in the minor units of the old
+                    // currency, so they can only be kept if the minor units don't change
+                    if split_mode.is_none() && new_currency.minor_units() != currency.minor_units()
+                    {
+                        return Err(BadRequest(format!(
+                            "The splits must be given when changing the currency to {}",
+                            new_currency.symbol()
+                        )));
+                    }
+
+                    new_currency
+                }
+                None => currency,
+            };
+
+            let splits = match &split_mode {
+                Some(split_mode) => Some(Self::compute_splits(
+                    total_amount,
+                    split_mode,
+                    currency.minor_units(),
+                    expense.owner_id(),
+                    &self.get_member_ids(expense.pot_id()).await?,
+                )?),
+                None => None,
+            };
+
+            if let Some(splits) = &splits {
+                expense_update.total_amount = Some(splits.iter().map(|split| split.amount()).sum());
+            }
+
+            expense_update.updated_at = Some(Utc::now());
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            conn.transaction::<_, Error, _>(|conn| {
+                async move {
+                    let updated = diesel::update(expenses.filter(expense_id.eq(target_id)))
+                        .set(expense_update)
+                        .returning(Expense::as_returning())
+                        .get_result::<Expense>(conn)
+                        .await?;
+
+                    let event = expense_event(
+                        &updated,
+                        &currency,
+                        requester_id,
+                        PotEventKind::ExpenseUpdated,
+                    );
+                    insert_events(conn, vec![event]).await?;
+
+                    if let Some(splits) = splits {
+                        diesel::delete(expense_splits.filter(split_expense_id.eq(target_id)))
+                            .execute(conn)
+                            .await?;
+
+                        let splits =
+                            NewExpenseSplit::splits_from_vector_with_id(splits, &expense);
+
+                        diesel::insert_into(expense_splits)
+                            .values(&splits)
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(())
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(internal_error)?;
+
+            self.get_expense_by_id(target_id, requester_id).await
+        }
+
+        /// Deletes the expense with the given `target_id` including its splits. Only the owner
+        /// can delete an expense and only as long as none of the other users has paid their
+        /// split.
+        pub async fn delete_expense(
+            &self,
+            target_id: i32,
+            requester_id: Uuid,
+        ) -> Result<bool, ExpenseError> {
+            let (expense, _, currency) =
+                self.get_modifiable_expense(target_id, requester_id).await?;
+
+            let event =
+                expense_event(&expense, &currency, requester_id, PotEventKind::ExpenseDeleted);
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            conn.transaction::<_, Error, _>(|conn| {
+                async move {
+                    let deleted = diesel::delete(expenses.filter(expense_id.eq(target_id)))
+                        .execute(conn)
+                        .await?;
+
+                    insert_events(conn, vec![event]).await?;
+
+                    Ok(deleted == 1)
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(internal_error)
+        }
+
+        /// Computes the splits of an expense from the given `total_amount` and `split_mode` in
+        /// the given minor units. Leftover minor units are given to the owner of the expense.
+        /// Returns `BadRequest` if the split mode is invalid, one of its users is not among the
+        /// given members of the pot or the splits don't add up to the total.
+        pub(crate) fn compute_splits(
+            total_amount: Option<f64>,
+            split_mode: &SplitMode,
+            minor_units: i32,
+            owner_id: Uuid,
+            member_ids: &[Uuid],
+        ) -> Result<Vec<NewExpenseSplit>, ExpenseError> {
+            let users = split_mode.users();
+
+            if users.is_empty() {
+                return Err(BadRequest("An expense needs at least one split".to_string()));
+            }
+
+            if users.iter().collect::<HashSet<_>>().len() != users.len() {
+                return Err(BadRequest(
+                    "Each user can only be part of a split once".to_string(),
+                ));
+            }
+
+            if let Some(outsider) = users.iter().find(|user| !member_ids.contains(user)) {
+                return Err(BadRequest(format!("User {} is not part of the pot", outsider)));
+            }
+
+            let total = match total_amount {
+                Some(total_amount) if !total_amount.is_finite() || total_amount < 0.0 => {
+                    return Err(BadRequest(
+                        "The total amount must not be negative".to_string(),
+                    ));
+                }
+                Some(total_amount) => Some(to_minor_units(total_amount, minor_units)),
+                None => None,
+            };
+
+            let values = match split_mode {
+                SplitMode::Equal(_) => vec![],
+                SplitMode::Percentage(values) | SplitMode::Shares(values) => {
+                    values.iter().map(|(_, value)| *value).collect()
+                }
+                SplitMode::Exact(amounts) => amounts.iter().filter_map(|(_, value)| *value).collect(),
+            };
+
+            if values.iter().any(|value| !value.is_finite() || *value < 0.0) {
+                return Err(BadRequest(
+                    "The values of the splits must not be negative".to_string(),
+                ));
             }
 
-            Ok(net_balance)
+            let needs_total = || {
+                total.ok_or(BadRequest(
+                    "A total amount is needed for this split mode".to_string(),
+                ))
+            };
+
+            let splits = match split_mode {
+                SplitMode::Equal(users) => {
+                    let weights = users.iter().map(|user| (*user, 1.0)).collect::<Vec<_>>();
+                    allocate(needs_total()?, &weights, owner_id)
+                }
+                SplitMode::Percentage(percentages) => {
+                    let sum = percentages.iter().map(|(_, percentage)| percentage).sum::<f64>();
+
+                    if (sum - 100.0).abs() > 0.000_001 {
+                        return Err(BadRequest(format!(
+                            "The percentages must add up to 100, but add up to {}",
+                            sum
+                        )));
+                    }
+
+                    allocate(needs_total()?, percentages, owner_id)
+                }
+                SplitMode::Shares(shares) => {
+                    if shares.iter().all(|(_, share)| *share == 0.0) {
+                        return Err(BadRequest(
+                            "At least one user needs a positive share".to_string(),
+                        ));
+                    }
+
+                    allocate(needs_total()?, shares, owner_id)
+                }
+                SplitMode::Exact(amounts) => {
+                    let fixed = amounts
+                        .iter()
+                        .filter_map(|(user, amount)| amount.map(|amount| (*user, amount)))
+                        .collect::<Vec<_>>();
+                    let open = amounts
+                        .iter()
+                        .filter(|(_, amount)| amount.is_none())
+                        .map(|(user, _)| (*user, 1.0))
+                        .collect::<Vec<_>>();
+
+                    let mut splits = round_splits(&fixed, minor_units, owner_id);
+                    let fixed_sum = splits.iter().map(|(_, amount)| amount).sum::<i64>();
+                    let total = match total {
+                        Some(total) => total,
+                        None if open.is_empty() => fixed_sum,
+                        None => needs_total()?,
+                    };
+                    let remainder = total - fixed_sum;
+
+                    if remainder < 0 || (remainder > 0 && open.is_empty()) {
+                        return Err(BadRequest(format!(
+                            "The splits add up to {}, but the total is {}",
+                            from_minor_units(fixed_sum, minor_units),
+                            from_minor_units(total, minor_units)
+                        )));
+                    }
+
+                    splits.extend(allocate(remainder, &open, owner_id));
+                    splits
+                }
+            };
+
+            Ok(splits
+                .into_iter()
+                .map(|(user, amount)| NewExpenseSplit::new(user, amount))
+                .collect())
+        }
+
+        /// Gets the expense with the given `target_id`, if the requester is allowed to modify it.
+        /// That is only the case for the owner, unless they became a viewer, and for the admins of
+        /// the pot, as long as the pot is not archived and no split has been paid.
+        async fn get_modifiable_expense(
+            &self,
+            target_id: i32,
+            requester_id: Uuid,
+        ) -> Result<JoinedExpense, ExpenseError> {
+            let joined_expense = self.get_expense_by_id(target_id, requester_id).await?;
+            let expense = &joined_expense.0;
+
+            let requester_role = self
+                .pot_role_service
+                .require_role(expense.pot_id(), requester_id, PotRole::Member)
+                .await?;
+
+            if expense.owner_id() != requester_id && requester_role < PotRole::Admin {
+                return Err(Forbidden(format!(
+                    "The user does not own the expense with id {}",
+                    target_id
+                )));
+            }
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let is_archived = pots
+                .filter(pots_id.eq(expense.pot_id()))
+                .select(archived)
+                .first::<bool>(&mut conn)
+                .await
+                .map_err(not_found_error)?;
+
+            if is_archived {
+                return Err(Locked(format!("Pot {} is archived", expense.pot_id())));
+            }
+
+            // the owner's split is always paid, as they paid for the expense in the first place
+            if joined_expense
+                .1
+                .iter()
+                .any(|split| split.is_paid() && split.user_id() != expense.owner_id())
+            {
+                return Err(Conflict(format!(
+                    "The expense with id {} has splits that have already been paid",
+                    target_id
+                )));
+            }
+
+            Ok(joined_expense)
         }
 
         /// The user with the given `requester_id` sets the expense to paid.
@@ -204,25 +1254,28 @@ pub mod expense_service {
                 .await
                 .map_err(check_error)?;
 
-            let splits = expense
-                .1
-                .iter()
-                .collect::<Vec<_>>();
+            self.pot_role_service
+                .require_role(expense.0.pot_id(), requester_id, PotRole::Member)
+                .await?;
 
-            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+            if expense.1.iter().all(|split| split.is_paid()) {
+                return Ok(true);
+            }
 
-            for split in splits {
-                if !split.is_paid() {
-                    diesel::update(expense_splits)
-                        .filter(split_expense_id.eq(target_id))
-                        .set(expense_tracker_db::schema::expense_splits::is_paid.eq(true))
-                        .execute(&mut conn)
-                        .await
-                        .map_err(internal_error)?;
+            let event =
+                expense_event(&expense.0, &expense.2, requester_id, PotEventKind::ExpensePaid);
 
-                    return Ok(true);
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            conn.transaction::<_, Error, _>(|conn| {
+                async move {
+                    pay_expenses(conn, &[target_id]).await?;
+                    insert_events(conn, vec![event]).await
                 }
-            }
+                .scope_boxed()
+            })
+            .await
+            .map_err(internal_error)?;
 
             Ok(true)
         }
@@ -230,22 +1283,444 @@ pub mod expense_service {
         /// This function is used to pay an expense without checking the owner or if the expense is already paid.
         pub async fn pay_expense_no_check(&self, target_id: i32) -> Result<bool, ExpenseError> {
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
-            diesel::update(expense_splits)
-                .filter(split_expense_id.eq(target_id))
-                .set(expense_tracker_db::schema::expense_splits::is_paid.eq(true))
-                .execute(&mut conn)
-                .await
-                .map_err(internal_error)?;
+
+            conn.transaction::<_, Error, _>(|conn| {
+                async move { pay_expenses(conn, &[target_id]).await }.scope_boxed()
+            })
+            .await
+            .map_err(internal_error)?;
 
             Ok(true)
         }
     }
 
+    /// Marks all splits of the given expenses as paid on the given connection, so it can be done
+    /// in the same transaction as other changes. The parts of open settlements that covered
+    /// those splits are cleared first, as they are part of the payment. Must be run in a
+    /// transaction.
+    pub(crate) async fn pay_expenses(
+        conn: &mut AsyncPgConnection,
+        target_ids: &[i32],
+    ) -> Result<usize, Error> {
+        clear_covered_settlements(conn, target_ids).await?;
+
+        diesel::update(expense_splits)
+            .filter(split_expense_id.eq_any(target_ids))
+            .set(split_is_paid.eq(true))
+            .execute(conn)
+            .await
+    }
+
+    /// Clears the parts of the open settlements that cover unpaid splits of the given expenses,
+    /// distributed like in `get_settled_amounts`. The oldest settlements are cleared first and
+    /// a settlement only gets its `cleared_at` once nothing of it is open anymore.
+    async fn clear_covered_settlements(
+        conn: &mut AsyncPgConnection,
+        target_ids: &[i32],
+    ) -> Result<(), Error> {
+        let target_pot_ids: Vec<i32> = expenses
+            .filter(expense_id.eq_any(target_ids))
+            .select(expense_pot_id)
+            .distinct()
+            .load(conn)
+            .await?;
+
+        // locked, so concurrent payments can't clear the same part of a settlement twice
+        let open_settlements: Vec<Settlement> = settlements::table
+            .filter(
+                settlements::pot_id
+                    .eq_any(&target_pot_ids)
+                    .and(settlements::cleared_at.is_null()),
+            )
+            .order(settlements::id.asc())
+            .select(Settlement::as_select())
+            .for_update()
+            .load(conn)
+            .await?;
+
+        if open_settlements.is_empty() {
+            return Ok(());
+        }
+
+        let pot_expenses: Vec<Expense> = expenses
+            .filter(expense_pot_id.eq_any(&target_pot_ids))
+            .select(Expense::as_select())
+            .load(conn)
+            .await?;
+
+        let unpaid_splits: Vec<Split> = expense_splits
+            .filter(
+                split_expense_id
+                    .eq_any(pot_expenses.iter().map(Expense::id).collect::<Vec<i32>>())
+                    .and(split_is_paid.eq(false)),
+            )
+            .select(Split::as_select())
+            .load(conn)
+            .await?;
+
+        let mut splits_by_expense: HashMap<i32, Vec<Split>> = HashMap::new();
+
+        for split in unpaid_splits {
+            splits_by_expense
+                .entry(split.expense_id())
+                .or_default()
+                .push(split);
+        }
+
+        let no_splits = vec![];
+
+        // maps payer, receiver, currency and pot to the amount that is cleared
+        let mut to_clear: HashMap<(Uuid, Uuid, i32, i32), i64> = HashMap::new();
+
+        for target_pot_id in target_pot_ids {
+            let pot_settlements: Vec<Settlement> = open_settlements
+                .iter()
+                .filter(|settlement| settlement.pot_id() == target_pot_id)
+                .cloned()
+                .collect();
+
+            let expenses_with_splits = pot_expenses
+                .iter()
+                .filter(|expense| expense.pot_id() == target_pot_id)
+                .map(|expense| {
+                    (expense, splits_by_expense.get(&expense.id()).unwrap_or(&no_splits))
+                })
+                .collect();
+
+            let settled_amounts =
+                ExpenseService::allocate_settlements(expenses_with_splits, &pot_settlements);
+
+            for ((settled_expense_id, debtor), settled) in settled_amounts {
+                if !target_ids.contains(&settled_expense_id) {
+                    continue;
+                }
+
+                let Some(expense) = pot_expenses.iter().find(|e| e.id() == settled_expense_id)
+                else {
+                    continue;
+                };
+
+                *to_clear
+                    .entry((debtor, expense.owner_id(), expense.currency_id(), target_pot_id))
+                    .or_insert(0) += settled;
+            }
+        }
+
+        let now = Utc::now();
+
+        for settlement in open_settlements {
+            let key = (
+                settlement.payer_id(),
+                settlement.receiver_id(),
+                settlement.currency_id(),
+                settlement.pot_id(),
+            );
+
+            let Some(open) = to_clear.get_mut(&key) else {
+                continue;
+            };
+
+            let cleared = (*open).min(settlement.open_amount());
+
+            if cleared <= 0 {
+                continue;
+            }
+
+            *open -= cleared;
+            let cleared_amount = settlement.cleared_amount() + cleared;
+            let cleared_at = (cleared_amount == settlement.amount()).then_some(now);
+
+            diesel::update(settlements::table.filter(settlements::id.eq(settlement.id())))
+                .set((
+                    settlements::cleared_amount.eq(cleared_amount),
+                    settlements::cleared_at.eq(cleared_at),
+                ))
+                .execute(conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Creates a new ExpenseService with the given DbConnectionPool.
     pub fn new_service(pool: DbPool) -> ExpenseService {
         ExpenseService {
             db_pool: pool.clone(),
             currency_service: currency_service::new_service(pool.clone()),
+            settlement_service: settlement_service::new_service(pool.clone()),
+            pot_role_service: pot_role_service::new_service(pool.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::expense_service::expense_service;
+    use crate::expense_service::expense_service::{
+        escape_like, ExpenseService, Pagination, SplitMode, MAX_PAGE_SIZE,
+    };
+    use crate::settlement_service::settlement_service;
+    use crate::test_support::test_support::TestFixture;
+    use crate::ExpenseError;
+    use diesel::internal::derives::multiconnection::chrono::Utc;
+    use expense_tracker_db::expenses::expenses::{Expense, NewExpense};
+    use expense_tracker_db::settlements::settlements::{NewSettlement, Settlement};
+    use expense_tracker_db::splits::splits::Split;
+    use uuid::{uuid, Uuid};
+
+    const USER_ONE: Uuid = uuid!("e6be621a-ec2d-48f3-8027-0d34cf5cbe40");
+    const USER_TWO: Uuid = uuid!("729c270f-74d1-436f-aa46-4fe6a3dcb460");
+    const USER_THREE: Uuid = uuid!("7ec9119b-10a2-48b8-be5f-d0f8ba9aad8d");
+
+    fn expense(id: i32, description: &str) -> Expense {
+        let now = Utc::now();
+        Expense::new(id, 1, USER_ONE, description.to_string(), 1, 0, now, now, now)
+    }
+
+    /// Computes the splits in cents with USER_ONE as owner and returns the amount per user.
+    fn compute(
+        total_amount: Option<f64>,
+        split_mode: SplitMode,
+    ) -> Result<Vec<(Uuid, i64)>, ExpenseError> {
+        let splits = ExpenseService::compute_splits(
+            total_amount,
+            &split_mode,
+            2,
+            USER_ONE,
+            &[USER_ONE, USER_TWO, USER_THREE],
+        )?;
+
+        Ok(splits
+            .iter()
+            .map(|split| (split.user_id(), split.amount()))
+            .collect())
+    }
+
+    /// USER_TWO owes USER_ONE 30.00 and 43.40 for two expenses and paid 50.00. The older expense
+    /// must be covered entirely, the newer one partially.
+    #[test]
+    fn test_allocate_settlements_partial_payment() {
+        let first = expense(1, "Groceries");
+        let second = expense(2, "Dinner");
+        let first_splits = vec![
+            Split::new(1, USER_ONE, 3000, true),
+            Split::new(1, USER_TWO, 3000, false),
+        ];
+        let second_splits = vec![Split::new(2, USER_TWO, 4340, false)];
+        let settlements = vec![Settlement::new(1, 1, USER_TWO, USER_ONE, 5000, 1, Utc::now())];
+
+        let settled = ExpenseService::allocate_settlements(
+            vec![(&second, &second_splits), (&first, &first_splits)],
+            &settlements,
+        );
+
+        assert_eq!(settled.len(), 2);
+        assert_eq!(settled[&(1, USER_TWO)], 3000);
+        assert_eq!(settled[&(2, USER_TWO)], 2000);
+    }
+
+    #[test]
+    fn test_allocate_settlements_other_currency_is_ignored() {
+        let expense = expense(1, "Groceries");
+        let splits = vec![Split::new(1, USER_TWO, 3000, false)];
+        let settlements = vec![Settlement::new(1, 1, USER_TWO, USER_ONE, 5000, 2, Utc::now())];
+
+        let settled = ExpenseService::allocate_settlements(vec![(&expense, &splits)], &settlements);
+
+        assert!(settled.is_empty());
+    }
+
+    #[test]
+    fn test_allocate_settlements_wrong_direction_is_ignored() {
+        let expense = expense(1, "Groceries");
+        let splits = vec![Split::new(1, USER_TWO, 3000, false)];
+        let settlements = vec![Settlement::new(1, 1, USER_ONE, USER_TWO, 5000, 1, Utc::now())];
+
+        let settled = ExpenseService::allocate_settlements(vec![(&expense, &splits)], &settlements);
+
+        assert!(settled.is_empty());
+    }
+
+    #[test]
+    fn test_compute_splits_equal() {
+        let splits = compute(
+            Some(10.0),
+            SplitMode::Equal(vec![USER_ONE, USER_TWO, USER_THREE]),
+        )
+        .unwrap();
+
+        assert_eq!(splits, vec![(USER_ONE, 334), (USER_TWO, 333), (USER_THREE, 333)]);
+    }
+
+    #[test]
+    fn test_compute_splits_percentage() {
+        let splits = compute(
+            Some(20.0),
+            SplitMode::Percentage(vec![(USER_ONE, 50.0), (USER_TWO, 30.0), (USER_THREE, 20.0)]),
+        )
+        .unwrap();
+
+        assert_eq!(splits, vec![(USER_ONE, 1000), (USER_TWO, 600), (USER_THREE, 400)]);
+    }
+
+    #[test]
+    fn test_compute_splits_percentage_must_add_up_to_100() {
+        let result = compute(
+            Some(20.0),
+            SplitMode::Percentage(vec![(USER_ONE, 50.0), (USER_TWO, 30.0)]),
+        );
+
+        assert!(matches!(result, Err(ExpenseError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_compute_splits_shares() {
+        let splits = compute(
+            Some(10.0),
+            SplitMode::Shares(vec![(USER_ONE, 1.0), (USER_TWO, 2.0)]),
+        )
+        .unwrap();
+
+        assert_eq!(splits, vec![(USER_ONE, 334), (USER_TWO, 666)]);
+    }
+
+    /// USER_TWO pays 4.00 and the remaining 6.00 are shared by USER_ONE and USER_THREE.
+    #[test]
+    fn test_compute_splits_exact_with_remainder() {
+        let splits = compute(
+            Some(10.0),
+            SplitMode::Exact(vec![(USER_ONE, None), (USER_TWO, Some(4.0)), (USER_THREE, None)]),
+        )
+        .unwrap();
+
+        assert_eq!(splits, vec![(USER_TWO, 400), (USER_ONE, 300), (USER_THREE, 300)]);
+    }
+
+    #[test]
+    fn test_compute_splits_exact_without_total() {
+        let splits = compute(
+            None,
+            SplitMode::Exact(vec![
+                (USER_ONE, Some(3.3333)),
+                (USER_TWO, Some(3.3333)),
+                (USER_THREE, Some(3.3333)),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(splits, vec![(USER_ONE, 334), (USER_TWO, 333), (USER_THREE, 333)]);
+    }
+
+    #[test]
+    fn test_compute_splits_exact_must_add_up_to_total() {
+        let too_much = compute(
+            Some(10.0),
+            SplitMode::Exact(vec![(USER_ONE, Some(6.0)), (USER_TWO, Some(6.0))]),
+        );
+        let too_little = compute(
+            Some(10.0),
+            SplitMode::Exact(vec![(USER_ONE, Some(6.0)), (USER_TWO, Some(3.0))]),
+        );
+
+        assert!(matches!(too_much, Err(ExpenseError::BadRequest(_))));
+        assert!(matches!(too_little, Err(ExpenseError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_compute_splits_invalid_input() {
+        let without_total = compute(None, SplitMode::Equal(vec![USER_ONE, USER_TWO]));
+        let duplicate_user = compute(Some(10.0), SplitMode::Equal(vec![USER_ONE, USER_ONE]));
+        let negative = compute(Some(-10.0), SplitMode::Equal(vec![USER_ONE]));
+        let empty = compute(Some(10.0), SplitMode::Equal(vec![]));
+        let outsider = compute(Some(10.0), SplitMode::Equal(vec![USER_ONE, Uuid::new_v4()]));
+
+        assert!(matches!(without_total, Err(ExpenseError::BadRequest(_))));
+        assert!(matches!(duplicate_user, Err(ExpenseError::BadRequest(_))));
+        assert!(matches!(negative, Err(ExpenseError::BadRequest(_))));
+        assert!(matches!(empty, Err(ExpenseError::BadRequest(_))));
+        assert!(matches!(outsider, Err(ExpenseError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_pagination_check() {
+        assert!(Pagination::new(1, 20).check().is_ok());
+        assert_eq!(Pagination::new(3, 20).offset(), 40);
+        assert!(Pagination::new(0, 20).check().is_err());
+        assert!(Pagination::new(1, MAX_PAGE_SIZE + 1).check().is_err());
+
+        let out_of_range = Pagination::new(i64::MAX, MAX_PAGE_SIZE);
+        assert!(out_of_range.check().is_err());
+        assert_eq!(out_of_range.offset(), i64::MAX);
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("Lunch"), "Lunch");
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("taxi_1"), "taxi\\_1");
+        assert_eq!(escape_like("a\\b"), "a\\\\b");
+    }
+
+    /// The member owes the owner 10.00 and 20.00 and paid back 15.00. Paying the expenses one
+    /// by one clears the part of the settlement that covered them, so it isn't counted twice.
+    #[tokio::test]
+    #[ignore = "needs a database, see expense_tracker_db::test_support"]
+    async fn test_pay_expense_clears_covered_settlements() {
+        let fixture = TestFixture::new(2).await;
+        let service = expense_service::new_service(fixture.pool());
+        let settlement_service = settlement_service::new_service(fixture.pool());
+        let currency = fixture.currency();
+
+        let owner = fixture.user(0);
+        let member = fixture.user(1);
+
+        let pot = fixture.create_pot("Paid one by one", owner, &[member]).await;
+
+        let mut expense_ids = vec![];
+
+        for (description, total) in [("Dinner", 20.0), ("Hotel", 40.0)] {
+            let (expense, _, _) = service
+                .create_expense(
+                    NewExpense::new(owner, pot.id(), description.to_string(), currency.id(), None),
+                    Some(total),
+                    SplitMode::Equal(vec![owner, member]),
+                )
+                .await
+                .unwrap();
+            expense_ids.push(expense.id());
         }
+
+        settlement_service
+            .create_settlement(
+                NewSettlement::new(pot.id(), member, owner, 1500, currency.id()),
+                member,
+            )
+            .await
+            .unwrap();
+
+        let balance = service.get_pot_net_balance(pot.id(), owner).await.unwrap();
+        assert_eq!(balance.converted(), Some(1500));
+
+        // the settlement covered the dinner and 5.00 of the hotel
+        service.pay_expense(expense_ids[0], owner).await.unwrap();
+
+        let balance = service.get_pot_net_balance(pot.id(), owner).await.unwrap();
+        assert_eq!(balance.converted(), Some(1500));
+
+        let settlements = settlement_service.get_settlements(pot.id(), owner).await.unwrap();
+        assert_eq!(settlements[0].cleared_amount(), 1000);
+        assert!(settlements[0].cleared_at().is_none());
+
+        service.pay_expense(expense_ids[1], owner).await.unwrap();
+
+        for user in [owner, member] {
+            let balance = service.get_pot_net_balance(pot.id(), user).await.unwrap();
+            assert!(balance.balances().is_empty());
+            assert_eq!(balance.converted(), Some(0));
+        }
+
+        let settlements = settlement_service.get_settlements(pot.id(), owner).await.unwrap();
+        assert_eq!(settlements[0].cleared_amount(), 1500);
+        assert!(settlements[0].cleared_at().is_some());
     }
 }
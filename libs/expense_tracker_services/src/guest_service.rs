@@ -0,0 +1,631 @@
+pub mod guest_service {
+    use crate::invitation_service::invitation_service::generate_code;
+    use crate::pot_event_service::pot_event_service::insert_events;
+    use crate::pot_role_service::pot_role_service;
+    use crate::pot_role_service::pot_role_service::PotRoleService;
+    use crate::ExpenseError::{BadRequest, Forbidden, Locked, NotFound};
+    use crate::{internal_error, ExpenseError};
+    use diesel::dsl::exists;
+    use diesel::result::Error;
+    use diesel::{
+        BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper,
+    };
+    use diesel_async::scoped_futures::ScopedFutureExt;
+    use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+    use expense_tracker_db::pot_events::pot_events::{NewPotEvent, PotEventKind};
+    use expense_tracker_db::pots::pots::{Pot, PotRole, PotToUser};
+    use expense_tracker_db::schema::{
+        expense_splits, expenses, pot_events, pots, pots_to_users, settlements, users,
+    };
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::splits::splits::Split;
+    use expense_tracker_db::users::users::User;
+    use uuid::Uuid;
+
+    /// The maximum length of the name of a guest.
+    const MAX_GUEST_NAME_LENGTH: usize = 24;
+
+    /// A service managing guests, which are members of a single pot without an account. Guests
+    /// can be part of splits like any other member, until a user claims them.
+    #[derive(Clone)]
+    pub struct GuestService {
+        db_pool: DbPool,
+        pot_role_service: PotRoleService,
+    }
+
+    impl GuestService {
+        /// Adds a guest with the given name to the pot, if the requester is at least an admin of
+        /// it and the pot is not archived.
+        pub async fn add_guest(
+            &self,
+            target_pot_id: i32,
+            guest_name: &str,
+            requester_id: Uuid,
+        ) -> Result<User, ExpenseError> {
+            let guest_name = guest_name.trim();
+
+            if guest_name.is_empty() || guest_name.chars().count() > MAX_GUEST_NAME_LENGTH {
+                return Err(BadRequest(format!(
+                    "The name of a guest must have between 1 and {} characters",
+                    MAX_GUEST_NAME_LENGTH
+                )));
+            }
+
+            self.get_open_pot(target_pot_id, requester_id).await?;
+
+            let guest = User::new_guest(Uuid::new_v4(), guest_name.to_string(), target_pot_id);
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            conn.transaction::<_, Error, _>(|conn| {
+                async move {
+                    let guest = diesel::insert_into(users::table)
+                        .values(guest)
+                        .returning(User::as_returning())
+                        .get_result::<User>(conn)
+                        .await?;
+
+                    diesel::insert_into(pots_to_users::table)
+                        .values(PotToUser::new(target_pot_id, guest.id()))
+                        .execute(conn)
+                        .await?;
+
+                    let event =
+                        NewPotEvent::new(target_pot_id, Some(requester_id), PotEventKind::GuestAdded)
+                            .with_subject(guest.id())
+                            .with_details(guest.name().to_string());
+                    insert_events(conn, vec![event]).await?;
+
+                    Ok(guest)
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(internal_error)
+        }
+
+        /// Hands out a new code the given guest can be claimed with, if the requester is at least
+        /// an admin of the pot. A previous code of the guest stops working.
+        pub async fn create_claim_code(
+            &self,
+            target_pot_id: i32,
+            guest_id: Uuid,
+            requester_id: Uuid,
+        ) -> Result<String, ExpenseError> {
+            self.get_open_pot(target_pot_id, requester_id).await?;
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let new_code = generate_code();
+
+            let updated = diesel::update(
+                users::table.filter(
+                    users::id
+                        .eq(guest_id)
+                        .and(users::guest_pot_id.eq(target_pot_id)),
+                ),
+            )
+            .set(users::claim_code.eq(&new_code))
+            .execute(&mut conn)
+            .await
+            .map_err(internal_error)?;
+
+            if updated == 0 {
+                return Err(NotFound(format!(
+                    "Guest {} is not part of pot {}",
+                    guest_id, target_pot_id
+                )));
+            }
+
+            Ok(new_code)
+        }
+
+        /// Claims the guest with the given code for the requester, who takes over everything of
+        /// the guest in its pot: their membership, splits, expenses, settlements and activity.
+        /// The guest is deleted afterwards. See `merge_guest` for how overlaps and rows in other
+        /// pots are handled.
+        pub async fn claim_guest(
+            &self,
+            requester_id: Uuid,
+            claim_code: &str,
+        ) -> Result<Pot, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let guest = users::table
+                .filter(users::claim_code.eq(claim_code))
+                .select(User::as_select())
+                .first::<User>(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?
+                .ok_or_else(|| NotFound("The claim code could not be found".to_string()))?;
+
+            // the database only allows claim codes for guests
+            let guest_pot_id = guest.guest_pot_id().unwrap_or_default();
+
+            let pot = pots::table
+                .filter(pots::id.eq(guest_pot_id))
+                .select(Pot::as_select())
+                .first::<Pot>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            if pot.is_archived() {
+                return Err(Locked(format!("Pot {} is archived", pot.id())));
+            }
+
+            // users are created on their first call of `current_user`
+            let requester = users::table
+                .filter(users::id.eq(requester_id))
+                .select(User::as_select())
+                .first::<User>(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?
+                .ok_or_else(|| NotFound(format!("User {} could not be found", requester_id)))?;
+
+            if requester.is_guest() {
+                return Err(Forbidden("Guests can't claim other guests".to_string()));
+            }
+
+            conn.transaction::<_, Error, _>(|conn| {
+                async move { merge_guest(conn, &guest, guest_pot_id, requester_id).await }
+                    .scope_boxed()
+            })
+            .await
+            .map_err(internal_error)?;
+
+            Ok(pot)
+        }
+
+        /// Gets the pot, if the requester is at least an admin of it and it is not archived.
+        async fn get_open_pot(
+            &self,
+            target_pot_id: i32,
+            requester_id: Uuid,
+        ) -> Result<Pot, ExpenseError> {
+            self.pot_role_service
+                .require_role(target_pot_id, requester_id, PotRole::Admin)
+                .await?;
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let pot = pots::table
+                .filter(pots::id.eq(target_pot_id))
+                .select(Pot::as_select())
+                .first::<Pot>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            if pot.is_archived() {
+                return Err(Locked(format!("Pot {} is archived", target_pot_id)));
+            }
+
+            Ok(pot)
+        }
+    }
+
+    /// Moves everything of the guest in the given pot over to the user and deletes the guest.
+    /// Splits of both in the same expense are merged into the split of the user, which is only
+    /// paid if both were. Settlements between the two are dropped, since they would be from the
+    /// user to themselves. If the user is already part of the pot, they keep their role.
+    /// Rows of the guest in other pots are left untouched, in which case the guest is kept
+    /// without its claim code instead of being deleted.
+    async fn merge_guest(
+        conn: &mut AsyncPgConnection,
+        guest: &User,
+        target_pot_id: i32,
+        new_user_id: Uuid,
+    ) -> Result<(), Error> {
+        let guest_id = guest.id();
+
+        let pot_expense_ids = expenses::table
+            .filter(expenses::pot_id.eq(target_pot_id))
+            .select(expenses::id);
+
+        let guest_splits: Vec<Split> = expense_splits::table
+            .filter(expense_splits::user_id.eq(guest_id))
+            .filter(expense_splits::expense_id.eq_any(pot_expense_ids))
+            .select(Split::as_select())
+            .load(conn)
+            .await?;
+
+        let shared_splits: Vec<Split> = expense_splits::table
+            .filter(expense_splits::user_id.eq(new_user_id))
+            .filter(
+                expense_splits::expense_id
+                    .eq_any(guest_splits.iter().map(Split::expense_id).collect::<Vec<i32>>()),
+            )
+            .select(Split::as_select())
+            .load(conn)
+            .await?;
+
+        for split in shared_splits {
+            let Some(guest_split) = guest_splits
+                .iter()
+                .find(|guest_split| guest_split.expense_id() == split.expense_id())
+            else {
+                continue;
+            };
+
+            diesel::update(
+                expense_splits::table.filter(
+                    expense_splits::expense_id
+                        .eq(split.expense_id())
+                        .and(expense_splits::user_id.eq(new_user_id)),
+                ),
+            )
+            .set((
+                expense_splits::amount.eq(split.amount() + guest_split.amount()),
+                expense_splits::is_paid.eq(split.is_paid() && guest_split.is_paid()),
+            ))
+            .execute(conn)
+            .await?;
+
+            diesel::delete(
+                expense_splits::table.filter(
+                    expense_splits::expense_id
+                        .eq(split.expense_id())
+                        .and(expense_splits::user_id.eq(guest_id)),
+                ),
+            )
+            .execute(conn)
+            .await?;
+        }
+
+        diesel::update(
+            expense_splits::table
+                .filter(expense_splits::user_id.eq(guest_id))
+                .filter(expense_splits::expense_id.eq_any(pot_expense_ids)),
+        )
+        .set(expense_splits::user_id.eq(new_user_id))
+        .execute(conn)
+        .await?;
+
+        diesel::update(
+            expenses::table.filter(
+                expenses::owner_id
+                    .eq(guest_id)
+                    .and(expenses::pot_id.eq(target_pot_id)),
+            ),
+        )
+        .set(expenses::owner_id.eq(new_user_id))
+        .execute(conn)
+        .await?;
+
+        let pot_settlements = settlements::table.filter(settlements::pot_id.eq(target_pot_id));
+
+        diesel::delete(
+            pot_settlements.filter(
+                settlements::payer_id
+                    .eq(guest_id)
+                    .and(settlements::receiver_id.eq(new_user_id))
+                    .or(settlements::payer_id
+                        .eq(new_user_id)
+                        .and(settlements::receiver_id.eq(guest_id))),
+            ),
+        )
+        .execute(conn)
+        .await?;
+
+        diesel::update(pot_settlements.filter(settlements::payer_id.eq(guest_id)))
+            .set(settlements::payer_id.eq(new_user_id))
+            .execute(conn)
+            .await?;
+
+        diesel::update(pot_settlements.filter(settlements::receiver_id.eq(guest_id)))
+            .set(settlements::receiver_id.eq(new_user_id))
+            .execute(conn)
+            .await?;
+
+        let is_member = diesel::select(exists(
+            pots_to_users::table.filter(
+                pots_to_users::pot_id
+                    .eq(target_pot_id)
+                    .and(pots_to_users::user_id.eq(new_user_id)),
+            ),
+        ))
+        .get_result::<bool>(conn)
+        .await?;
+
+        let guest_membership = pots_to_users::table.filter(
+            pots_to_users::pot_id
+                .eq(target_pot_id)
+                .and(pots_to_users::user_id.eq(guest_id)),
+        );
+
+        if is_member {
+            diesel::delete(guest_membership).execute(conn).await?;
+        } else {
+            diesel::update(guest_membership)
+                .set(pots_to_users::user_id.eq(new_user_id))
+                .execute(conn)
+                .await?;
+        }
+
+        // the activity of the guest is kept and attributed to the user
+        let pot_activity = pot_events::table.filter(pot_events::pot_id.eq(target_pot_id));
+
+        diesel::update(pot_activity.filter(pot_events::actor_id.eq(guest_id)))
+            .set(pot_events::actor_id.eq(Some(new_user_id)))
+            .execute(conn)
+            .await?;
+
+        diesel::update(pot_activity.filter(pot_events::subject_id.eq(guest_id)))
+            .set(pot_events::subject_id.eq(Some(new_user_id)))
+            .execute(conn)
+            .await?;
+
+        let event = NewPotEvent::new(target_pot_id, Some(new_user_id), PotEventKind::GuestClaimed)
+            .with_subject(new_user_id)
+            .with_details(guest.name().to_string());
+        insert_events(conn, vec![event]).await?;
+
+        // deleting the guest would cascade to the rows it still has in other pots
+        let is_referenced = diesel::select(
+            exists(expense_splits::table.filter(expense_splits::user_id.eq(guest_id)))
+                .or(exists(expenses::table.filter(expenses::owner_id.eq(guest_id))))
+                .or(exists(settlements::table.filter(
+                    settlements::payer_id
+                        .eq(guest_id)
+                        .or(settlements::receiver_id.eq(guest_id)),
+                ))),
+        )
+        .get_result::<bool>(conn)
+        .await?;
+
+        let guest_row = users::table.filter(users::id.eq(guest_id));
+
+        if is_referenced {
+            diesel::update(guest_row)
+                .set(users::claim_code.eq(None::<String>))
+                .execute(conn)
+                .await?;
+        } else {
+            diesel::delete(guest_row).execute(conn).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates a new GuestService.
+    pub fn new_service(pool: DbPool) -> GuestService {
+        GuestService {
+            db_pool: pool.clone(),
+            pot_role_service: pot_role_service::new_service(pool),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expense_service::expense_service;
+    use crate::expense_service::expense_service::SplitMode;
+    use crate::guest_service::guest_service;
+    use crate::pot_service::pot_service;
+    use crate::settlement_service::settlement_service;
+    use crate::test_support::test_support::TestFixture;
+    use crate::ExpenseError;
+    use diesel::{ExpressionMethods, QueryDsl};
+    use diesel_async::RunQueryDsl;
+    use expense_tracker_db::expenses::expenses::NewExpense;
+    use expense_tracker_db::pots::pots::{PotRole, PotToUser};
+    use expense_tracker_db::schema::{expense_splits, settlements, users};
+    use expense_tracker_db::settlements::settlements::NewSettlement;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    #[ignore = "needs a database, see expense_tracker_db::test_support"]
+    async fn test_claim_guest() {
+        let fixture = TestFixture::new(3).await;
+        let service = guest_service::new_service(fixture.pool());
+        let pot_service = pot_service::new_service(fixture.pool());
+        let expense_service = expense_service::new_service(fixture.pool());
+        let settlement_service = settlement_service::new_service(fixture.pool());
+        let currency = fixture.currency();
+
+        let owner = fixture.user(0);
+        let member = fixture.user(1);
+        let newcomer = fixture.user(2);
+
+        let pot = fixture.create_pot("Guests", owner, &[member]).await;
+
+        let result = service.add_guest(pot.id(), " ", owner).await;
+        assert!(matches!(result, Err(ExpenseError::BadRequest(_))));
+
+        let result = service.add_guest(pot.id(), "Visitor", member).await;
+        assert!(matches!(result, Err(ExpenseError::Forbidden(_))));
+
+        let guest = service.add_guest(pot.id(), " Visitor ", owner).await.unwrap();
+        let other_guest = service.add_guest(pot.id(), "Friend", owner).await.unwrap();
+        assert_eq!(guest.name(), "Visitor");
+        assert_eq!(guest.guest_pot_id(), Some(pot.id()));
+
+        // guests can't get another role or join other pots
+        let result = pot_service
+            .set_member_role(pot.id(), owner, guest.id(), PotRole::Viewer)
+            .await;
+        assert!(matches!(result, Err(ExpenseError::BadRequest(_))));
+
+        let other_pot = fixture.create_pot("Other", owner, &[]).await;
+        let result = pot_service
+            .add_users_to_pot(vec![PotToUser::new(other_pot.id(), guest.id())], owner)
+            .await;
+        assert!(matches!(result, Err(ExpenseError::BadRequest(_))));
+
+        expense_service
+            .create_expense(
+                NewExpense::new(owner, pot.id(), "Dinner".to_string(), currency.id(), None),
+                Some(30.0),
+                SplitMode::Equal(vec![owner, member, guest.id()]),
+            )
+            .await
+            .unwrap();
+
+        expense_service
+            .create_expense(
+                NewExpense::new(member, pot.id(), "Taxi".to_string(), currency.id(), None),
+                Some(20.0),
+                SplitMode::Equal(vec![member, guest.id(), other_guest.id()]),
+            )
+            .await
+            .unwrap();
+
+        settlement_service
+            .create_settlement(
+                NewSettlement::new(pot.id(), guest.id(), member, 500, currency.id()),
+                member,
+            )
+            .await
+            .unwrap();
+
+        let result = service.create_claim_code(pot.id(), member, owner).await;
+        assert!(matches!(result, Err(ExpenseError::NotFound(_))));
+
+        let code = service.create_claim_code(pot.id(), guest.id(), owner).await.unwrap();
+        let other_code = service
+            .create_claim_code(pot.id(), other_guest.id(), owner)
+            .await
+            .unwrap();
+
+        let result = service.claim_guest(member, "unknown").await;
+        assert!(matches!(result, Err(ExpenseError::NotFound(_))));
+
+        assert_eq!(service.claim_guest(member, &code).await.unwrap().id(), pot.id());
+        service.claim_guest(newcomer, &other_code).await.unwrap();
+
+        // the code only works once, since the guest is gone
+        let result = service.claim_guest(newcomer, &code).await;
+        assert!(matches!(result, Err(ExpenseError::NotFound(_))));
+
+        let members: Vec<(Uuid, PotRole)> = pot_service
+            .get_members(pot.id(), owner)
+            .await
+            .unwrap()
+            .iter()
+            .map(|(user, role)| (user.id(), *role))
+            .collect();
+        assert_eq!(members.len(), 3);
+        assert!(members.contains(&(member, PotRole::Member)));
+        assert!(members.contains(&(newcomer, PotRole::Member)));
+
+        let expenses = expense_service
+            .get_expenses_by_pot_id(pot.id(), owner)
+            .await
+            .unwrap();
+
+        for (expense, splits, _) in &expenses {
+            let mut amounts: Vec<(Uuid, i64)> = splits
+                .iter()
+                .map(|split| (split.user_id(), split.amount()))
+                .collect();
+            amounts.sort();
+
+            let mut expected = if expense.description() == "Dinner" {
+                vec![(owner, 1000), (member, 2000)]
+            } else {
+                vec![(member, 1334), (newcomer, 666)]
+            };
+            expected.sort();
+
+            assert_eq!(amounts, expected);
+        }
+
+        // the settlement between the guest and the member is gone
+        let settlements = settlement_service.get_settlements(pot.id(), owner).await.unwrap();
+        assert!(settlements.is_empty());
+    }
+
+    /// Splits and settlements from before split users had to be part of the pot can refer to
+    /// a guest in another pot. Claiming the guest leaves those untouched.
+    #[tokio::test]
+    #[ignore = "needs a database, see expense_tracker_db::test_support"]
+    async fn test_claim_guest_keeps_other_pots() {
+        let fixture = TestFixture::new(2).await;
+        let service = guest_service::new_service(fixture.pool());
+        let expense_service = expense_service::new_service(fixture.pool());
+        let settlement_service = settlement_service::new_service(fixture.pool());
+        let currency = fixture.currency();
+
+        let owner = fixture.user(0);
+        let newcomer = fixture.user(1);
+
+        let pot = fixture.create_pot("Guests", owner, &[]).await;
+        let other_pot = fixture.create_pot("Other", owner, &[]).await;
+
+        let guest = service.add_guest(pot.id(), "Visitor", owner).await.unwrap();
+
+        expense_service
+            .create_expense(
+                NewExpense::new(owner, pot.id(), "Dinner".to_string(), currency.id(), None),
+                Some(20.0),
+                SplitMode::Equal(vec![owner, guest.id()]),
+            )
+            .await
+            .unwrap();
+
+        let (other_expense, _, _) = expense_service
+            .create_expense(
+                NewExpense::new(owner, other_pot.id(), "Taxi".to_string(), currency.id(), None),
+                Some(10.0),
+                SplitMode::Equal(vec![owner]),
+            )
+            .await
+            .unwrap();
+
+        let mut conn = fixture.pool().get_owned().await.unwrap();
+
+        diesel::insert_into(expense_splits::table)
+            .values((
+                expense_splits::expense_id.eq(other_expense.id()),
+                expense_splits::user_id.eq(guest.id()),
+                expense_splits::amount.eq(700),
+                expense_splits::is_paid.eq(false),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        diesel::insert_into(settlements::table)
+            .values(NewSettlement::new(other_pot.id(), guest.id(), owner, 300, currency.id()))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let code = service.create_claim_code(pot.id(), guest.id(), owner).await.unwrap();
+        service.claim_guest(newcomer, &code).await.unwrap();
+
+        let (_, splits, _) = expense_service
+            .get_expenses_by_pot_id(pot.id(), owner)
+            .await
+            .unwrap()
+            .remove(0);
+        assert!(splits.iter().any(|split| split.user_id() == newcomer));
+
+        let (_, other_splits, _) = expense_service
+            .get_expense_by_id(other_expense.id(), owner)
+            .await
+            .unwrap();
+        let other_users: Vec<Uuid> = other_splits.iter().map(|split| split.user_id()).collect();
+        assert!(other_users.contains(&guest.id()));
+        assert!(!other_users.contains(&newcomer));
+
+        let other_settlements = settlement_service
+            .get_settlements(other_pot.id(), owner)
+            .await
+            .unwrap();
+        assert_eq!(other_settlements[0].payer_id(), guest.id());
+
+        // the guest is kept for those rows, but can't be claimed again
+        let claim_code: Option<String> = users::table
+            .filter(users::id.eq(guest.id()))
+            .select(users::claim_code)
+            .first(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(claim_code, None);
+
+        let result = service.claim_guest(newcomer, &code).await;
+        assert!(matches!(result, Err(ExpenseError::NotFound(_))));
+    }
+}
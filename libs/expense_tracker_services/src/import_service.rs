@@ -0,0 +1,441 @@
+pub mod import_service {
+    use crate::currency_service::currency_service;
+    use crate::currency_service::currency_service::CurrencyService;
+    use crate::expense_service::expense_service;
+    use crate::expense_service::expense_service::{
+        ExpenseService, JoinedExpense, PreparedExpense, SplitMode,
+    };
+    use crate::pot_role_service::pot_role_service;
+    use crate::pot_role_service::pot_role_service::PotRoleService;
+    use crate::pot_service::pot_service;
+    use crate::pot_service::pot_service::PotService;
+    use crate::ExpenseError::{BadRequest, Locked};
+    use crate::{internal_error, ExpenseError};
+    use diesel::internal::derives::multiconnection::chrono::{DateTime, NaiveDate, Utc};
+    use diesel::{ExpressionMethods, JoinOnDsl, QueryDsl, SelectableHelper};
+    use diesel_async::RunQueryDsl;
+    use expense_tracker_db::currencies::currencies::Currency;
+    use expense_tracker_db::expenses::expenses::NewExpense;
+    use expense_tracker_db::pots::pots::PotRole;
+    use expense_tracker_db::schema::pots_to_users::dsl::pots_to_users;
+    use expense_tracker_db::schema::pots_to_users::{pot_id, user_id};
+    use expense_tracker_db::schema::users::dsl::users;
+    use expense_tracker_db::schema::users::id as db_user_id;
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::users::users::User;
+    use uuid::Uuid;
+
+    /// Separates the users in the participants column, e.g. `alice;bob`.
+    const PARTICIPANT_SEPARATOR: char = ';';
+
+    /// Maps the fields of an expense to the columns of a CSV file, by the names in its header.
+    /// Only the description and the amount are required.
+    pub struct ColumnMapping {
+        /// The day the money has been spent. If not mapped, the time of the import is used.
+        date: Option<String>,
+        description: String,
+        /// The total amount in major units, e.g. 10.05.
+        amount: String,
+        /// The code or symbol of the currency. If not mapped, the default currency of the pot is
+        /// used.
+        currency: Option<String>,
+        /// The name or id of the user who paid. If not mapped, the importing user is the payer.
+        payer: Option<String>,
+        /// The names or ids of the users the amount is split equally among, separated by `;`.
+        /// If not mapped, the amount is split among all users of the pot.
+        participants: Option<String>,
+        delimiter: u8,
+        /// The format of the dates, e.g. `%Y-%m-%d`.
+        date_format: String,
+    }
+
+    impl ColumnMapping {
+        /// Constructor for ColumnMapping.
+        #[allow(clippy::too_many_arguments)]
+        pub fn new(
+            date: Option<String>,
+            description: String,
+            amount: String,
+            currency: Option<String>,
+            payer: Option<String>,
+            participants: Option<String>,
+            delimiter: u8,
+            date_format: String,
+        ) -> Self {
+            Self {
+                date,
+                description,
+                amount,
+                currency,
+                payer,
+                participants,
+                delimiter,
+                date_format,
+            }
+        }
+    }
+
+    /// A row of an import that could not be turned into an expense.
+    pub struct RowError {
+        row: u64,
+        message: String,
+    }
+
+    impl RowError {
+        /// The line of the row in the CSV, the header being line 1.
+        pub fn row(&self) -> u64 {
+            self.row
+        }
+
+        /// Getter for message.
+        pub fn message(&self) -> &str {
+            &self.message
+        }
+    }
+
+    /// The result of an import.
+    pub struct ImportResult {
+        valid_rows: usize,
+        errors: Vec<RowError>,
+        created: Vec<JoinedExpense>,
+    }
+
+    impl ImportResult {
+        /// The number of rows that can be imported.
+        pub fn valid_rows(&self) -> usize {
+            self.valid_rows
+        }
+
+        /// The rows that can't be imported. If there are any, no expense is created.
+        pub fn errors(&self) -> &Vec<RowError> {
+            &self.errors
+        }
+
+        /// The expenses that have been created. Empty for a dry run or if there are errors.
+        pub fn created(&self) -> &Vec<JoinedExpense> {
+            &self.created
+        }
+    }
+
+    /// The indices of the mapped columns in the header of a CSV file.
+    struct ColumnIndices {
+        date: Option<usize>,
+        description: usize,
+        amount: usize,
+        currency: Option<usize>,
+        payer: Option<usize>,
+        participants: Option<usize>,
+    }
+
+    /// A service importing expenses into pots.
+    #[derive(Clone)]
+    pub struct ImportService {
+        db_pool: DbPool,
+        currency_service: CurrencyService,
+        expense_service: ExpenseService,
+        pot_service: PotService,
+        pot_role_service: PotRoleService,
+    }
+
+    impl ImportService {
+        /// Imports the expenses in the given CSV into the given pot, using the given mapping of
+        /// columns. Each row is validated first and all rows that can't be imported are
+        /// reported. Only if all rows are valid and it is not a `dry_run`, the expenses are
+        /// created in a single transaction. Each expense is split equally among its participants.
+        pub async fn import_expenses(
+            &self,
+            target_pot_id: i32,
+            csv: &str,
+            mapping: &ColumnMapping,
+            dry_run: bool,
+            requester_id: Uuid,
+        ) -> Result<ImportResult, ExpenseError> {
+            self.pot_role_service
+                .require_role(target_pot_id, requester_id, PotRole::Member)
+                .await?;
+
+            let pot = self
+                .pot_service
+                .get_pot_by_id(target_pot_id, requester_id)
+                .await?;
+
+            if pot.is_archived() {
+                return Err(Locked(format!("Pot {} is archived", target_pot_id)));
+            }
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let members = users
+                .inner_join(pots_to_users.on(user_id.eq(db_user_id)))
+                .filter(pot_id.eq(target_pot_id))
+                .select(User::as_select())
+                .load::<User>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            let known_currencies = self.currency_service.get_currencies().await?;
+
+            let mut reader = csv::ReaderBuilder::new()
+                .delimiter(mapping.delimiter)
+                .trim(csv::Trim::All)
+                .flexible(true)
+                .from_reader(csv.as_bytes());
+
+            let header = reader
+                .headers()
+                .map_err(|err| BadRequest(format!("The CSV can't be read: {}", err)))?
+                .clone();
+
+            let indices = Self::column_indices(&header, mapping)?;
+
+            let mut prepared_expenses: Vec<PreparedExpense> = vec![];
+            let mut errors = vec![];
+
+            for record in reader.records() {
+                let record =
+                    record.map_err(|err| BadRequest(format!("The CSV can't be read: {}", err)))?;
+                let row = record.position().map(|position| position.line()).unwrap_or_default();
+
+                let prepared = Self::parse_row(
+                    &record,
+                    &indices,
+                    mapping,
+                    pot.default_currency_id(),
+                    target_pot_id,
+                    &members,
+                    &known_currencies,
+                    requester_id,
+                );
+
+                match prepared {
+                    Ok(prepared) => prepared_expenses.push(prepared),
+                    Err(BadRequest(message)) => errors.push(RowError { row, message }),
+                    Err(err) => return Err(err),
+                }
+            }
+
+            let valid_rows = prepared_expenses.len();
+
+            if dry_run || !errors.is_empty() {
+                return Ok(ImportResult {
+                    valid_rows,
+                    errors,
+                    created: vec![],
+                });
+            }
+
+            let created = self
+                .expense_service
+                .insert_expenses(prepared_expenses, requester_id)
+                .await?;
+
+            Ok(ImportResult {
+                valid_rows,
+                errors,
+                created,
+            })
+        }
+
+        /// Finds the mapped columns in the given header.
+        fn column_indices(
+            header: &csv::StringRecord,
+            mapping: &ColumnMapping,
+        ) -> Result<ColumnIndices, ExpenseError> {
+            let find = |column: &str| {
+                header
+                    .iter()
+                    .position(|name| name.eq_ignore_ascii_case(column))
+                    .ok_or_else(|| BadRequest(format!("The CSV has no column {}", column)))
+            };
+
+            let find_optional = |column: &Option<String>| column.as_deref().map(find).transpose();
+
+            Ok(ColumnIndices {
+                date: find_optional(&mapping.date)?,
+                description: find(&mapping.description)?,
+                amount: find(&mapping.amount)?,
+                currency: find_optional(&mapping.currency)?,
+                payer: find_optional(&mapping.payer)?,
+                participants: find_optional(&mapping.participants)?,
+            })
+        }
+
+        /// Turns the given row into an expense that is ready to be inserted. Returns a
+        /// `BadRequest` describing the problem, if the row is invalid.
+        #[allow(clippy::too_many_arguments)]
+        fn parse_row(
+            record: &csv::StringRecord,
+            indices: &ColumnIndices,
+            mapping: &ColumnMapping,
+            default_currency_id: i32,
+            target_pot_id: i32,
+            members: &[User],
+            known_currencies: &[Currency],
+            requester_id: Uuid,
+        ) -> Result<PreparedExpense, ExpenseError> {
+            let value = |index: usize| record.get(index).unwrap_or_default();
+            let optional_value = |index: Option<usize>| {
+                index.map(value).filter(|value| !value.is_empty())
+            };
+            let required_value = |index: usize, column: &str| {
+                optional_value(Some(index))
+                    .ok_or_else(|| BadRequest(format!("The value of column {} is missing", column)))
+            };
+
+            let spent_at = optional_value(indices.date)
+                .map(|date| Self::parse_date(date, &mapping.date_format))
+                .transpose()?;
+
+            let description = required_value(indices.description, &mapping.description)?;
+            let amount = required_value(indices.amount, &mapping.amount)?;
+            let total_amount = amount
+                .parse::<f64>()
+                .map_err(|_| BadRequest(format!("{} is not a valid amount", amount)))?;
+
+            let currency = match optional_value(indices.currency) {
+                Some(currency) => known_currencies
+                    .iter()
+                    .find(|known| {
+                        known
+                            .code()
+                            .is_some_and(|code| code.eq_ignore_ascii_case(currency))
+                            || known.symbol() == currency
+                    })
+                    .ok_or_else(|| BadRequest(format!("Unknown currency {}", currency)))?,
+                None => known_currencies
+                    .iter()
+                    .find(|known| known.id() == default_currency_id)
+                    .ok_or_else(|| BadRequest("The pot has no default currency".to_string()))?,
+            };
+
+            let payer = match optional_value(indices.payer) {
+                Some(payer) => Self::find_member(members, payer)?,
+                None => requester_id,
+            };
+
+            let participants = match optional_value(indices.participants) {
+                Some(participants) => participants
+                    .split(PARTICIPANT_SEPARATOR)
+                    .map(str::trim)
+                    .filter(|participant| !participant.is_empty())
+                    .map(|participant| Self::find_member(members, participant))
+                    .collect::<Result<Vec<Uuid>, ExpenseError>>()?,
+                None => members.iter().map(User::id).collect(),
+            };
+
+            let new_expense = NewExpense::new(
+                payer,
+                target_pot_id,
+                description.to_string(),
+                currency.id(),
+                spent_at,
+            );
+
+            ExpenseService::prepare_expense(
+                new_expense,
+                Some(total_amount),
+                &SplitMode::Equal(participants),
+                currency.clone(),
+                &members.iter().map(User::id).collect::<Vec<Uuid>>(),
+            )
+        }
+
+        /// Parses the given date with the given format. The expense is considered to be spent at
+        /// midnight UTC of that day.
+        pub(crate) fn parse_date(date: &str, date_format: &str) -> Result<DateTime<Utc>, ExpenseError> {
+            NaiveDate::parse_from_str(date, date_format)
+                .ok()
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+                .map(|midnight| midnight.and_utc())
+                .ok_or_else(|| {
+                    BadRequest(format!("{} does not match the date format {}", date, date_format))
+                })
+        }
+
+        /// Finds the member of the pot with the given id or name. Names are compared ignoring
+        /// case and must be unique.
+        pub(crate) fn find_member(members: &[User], to_find: &str) -> Result<Uuid, ExpenseError> {
+            if let Ok(member_id) = Uuid::parse_str(to_find) {
+                return members
+                    .iter()
+                    .find(|member| member.id() == member_id)
+                    .map(User::id)
+                    .ok_or_else(|| BadRequest(format!("{} is not part of the pot", to_find)));
+            }
+
+            let matches = members
+                .iter()
+                .filter(|member| member.name().eq_ignore_ascii_case(to_find))
+                .collect::<Vec<_>>();
+
+            match matches.as_slice() {
+                [member] => Ok(member.id()),
+                [] => Err(BadRequest(format!("{} is not part of the pot", to_find))),
+                _ => Err(BadRequest(format!(
+                    "There are multiple users named {}, use their id instead",
+                    to_find
+                ))),
+            }
+        }
+    }
+
+    /// Creates a new ImportService with the given DbConnectionPool.
+    pub fn new_service(pool: DbPool) -> ImportService {
+        ImportService {
+            db_pool: pool.clone(),
+            currency_service: currency_service::new_service(pool.clone()),
+            expense_service: expense_service::new_service(pool.clone()),
+            pot_service: pot_service::new_service(pool.clone()),
+            pot_role_service: pot_role_service::new_service(pool),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::import_service::import_service::ImportService;
+    use expense_tracker_db::users::users::User;
+    use uuid::{uuid, Uuid};
+
+    const USER_ONE: Uuid = uuid!("e6be621a-ec2d-48f3-8027-0d34cf5cbe40");
+    const USER_TWO: Uuid = uuid!("729c270f-74d1-436f-aa46-4fe6a3dcb460");
+    const USER_THREE: Uuid = uuid!("7ec9119b-10a2-48b8-be5f-d0f8ba9aad8d");
+
+    fn members() -> Vec<User> {
+        vec![
+            User::new(USER_ONE, "Alice".to_string()),
+            User::new(USER_TWO, "Bob".to_string()),
+            User::new(USER_THREE, "bob".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_find_member_by_id_or_name() {
+        let members = members();
+
+        assert_eq!(ImportService::find_member(&members, "alice").unwrap(), USER_ONE);
+        assert_eq!(
+            ImportService::find_member(&members, &USER_THREE.to_string()).unwrap(),
+            USER_THREE
+        );
+    }
+
+    #[test]
+    fn test_find_member_unknown_or_ambiguous() {
+        let members = members();
+
+        assert!(ImportService::find_member(&members, "carol").is_err());
+        assert!(ImportService::find_member(&members, "BOB").is_err());
+        assert!(
+            ImportService::find_member(&members, "01913042-053a-4cb2-846d-4b58153185b8").is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let spent_at = ImportService::parse_date("17.10.2026", "%d.%m.%Y").unwrap();
+
+        assert_eq!(spent_at.to_rfc3339(), "2026-10-17T00:00:00+00:00");
+        assert!(ImportService::parse_date("2026-10-17", "%d.%m.%Y").is_err());
+    }
+}
@@ -0,0 +1,415 @@
+pub mod invitation_service {
+    use crate::pot_event_service::pot_event_service::insert_events;
+    use crate::pot_role_service::pot_role_service;
+    use crate::pot_role_service::pot_role_service::PotRoleService;
+    use crate::ExpenseError::{BadRequest, Conflict, Locked, NotFound};
+    use crate::{internal_error, ExpenseError};
+    use argon2::password_hash::rand_core::{OsRng, RngCore};
+    use diesel::dsl::now;
+    use diesel::internal::derives::multiconnection::chrono::{DateTime, Duration, Utc};
+    use diesel::result::{DatabaseErrorKind, Error};
+    use diesel::upsert::excluded;
+    use diesel::{
+        BoolExpressionMethods, ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl,
+        SelectableHelper,
+    };
+    use diesel_async::scoped_futures::ScopedFutureExt;
+    use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+    use expense_tracker_db::pot_events::pot_events::{NewPotEvent, PotEventKind};
+    use expense_tracker_db::pot_invitations::pot_invitations::{NewPotInvitation, PotInvitation};
+    use expense_tracker_db::pots::pots::{Pot, PotRole, PotToUser};
+    use expense_tracker_db::schema::pot_invitations::dsl::{
+        code, created_at, expires_at, id, invitee_id, inviter_id, pot_id, pot_invitations,
+    };
+    use expense_tracker_db::schema::pots::dsl::pots;
+    use expense_tracker_db::schema::pots_to_users::dsl::pots_to_users;
+    use expense_tracker_db::schema::{pots as pots_table, pots_to_users as members, users as users_table};
+    use expense_tracker_db::schema::users::dsl::users;
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::users::users::User;
+    use uuid::Uuid;
+
+    /// The number of days an invitation is valid for, unless another expiry is given.
+    const INVITATION_LIFETIME_DAYS: i64 = 14;
+
+    /// The characters invite codes consist of. Characters that are easily confused, like `l`
+    /// and `1` or `o` and `0`, are left out, since codes may be typed by hand.
+    const CODE_ALPHABET: &[u8; 32] = b"abcdefghijkmnpqrstuvwxyz23456789";
+
+    /// The number of characters of an invite code.
+    const CODE_LENGTH: usize = 12;
+
+    /// A service managing the invitations of users to pots.
+    #[derive(Clone)]
+    pub struct InvitationService {
+        db_pool: DbPool,
+        pot_role_service: PotRoleService,
+    }
+
+    impl InvitationService {
+        /// Invites the given users to the pot, if the requester is at least an admin of it. Users
+        /// that are already invited get their invitation renewed.
+        pub async fn invite_users(
+            &self,
+            target_pot_id: i32,
+            requester_id: Uuid,
+            invitee_ids: Vec<Uuid>,
+        ) -> Result<Vec<PotInvitation>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            self.get_open_pot(&mut conn, target_pot_id, requester_id).await?;
+
+            // guests never log in, so they can't accept invitations
+            let found_users: Vec<Uuid> = users
+                .filter(users_table::id.eq_any(&invitee_ids))
+                .filter(users_table::guest_pot_id.is_null())
+                .select(users_table::id)
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            if let Some(missing) = invitee_ids.iter().find(|invitee| !found_users.contains(invitee)) {
+                return Err(NotFound(format!("User {} could not be found", missing)));
+            }
+
+            let existing_members: Vec<Uuid> = pots_to_users
+                .filter(members::pot_id.eq(target_pot_id))
+                .filter(members::user_id.eq_any(&invitee_ids))
+                .select(members::user_id)
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            if let [member, ..] = existing_members.as_slice() {
+                return Err(Conflict(format!(
+                    "User {} is already part of pot {}",
+                    member, target_pot_id
+                )));
+            }
+
+            let expiry = Utc::now() + Duration::days(INVITATION_LIFETIME_DAYS);
+            let new_invitations: Vec<NewPotInvitation> = invitee_ids
+                .into_iter()
+                .map(|invitee| NewPotInvitation::for_user(target_pot_id, requester_id, invitee, expiry))
+                .collect();
+
+            diesel::insert_into(pot_invitations)
+                .values(new_invitations)
+                .on_conflict((pot_id, invitee_id))
+                .do_update()
+                .set((
+                    inviter_id.eq(excluded(inviter_id)),
+                    created_at.eq(now),
+                    expires_at.eq(excluded(expires_at)),
+                ))
+                .returning(PotInvitation::as_returning())
+                .get_results(&mut conn)
+                .await
+                .map_err(internal_error)
+        }
+
+        /// Creates an invitation to the pot with a code that can be shared with anyone, if the
+        /// requester is at least an admin of the pot. The code can be used by any number of users,
+        /// until it expires or is revoked.
+        pub async fn create_invite_code(
+            &self,
+            target_pot_id: i32,
+            requester_id: Uuid,
+            expiry: Option<DateTime<Utc>>,
+        ) -> Result<PotInvitation, ExpenseError> {
+            if expiry.is_some_and(|expiry| expiry <= Utc::now()) {
+                return Err(BadRequest("The expiry must be in the future".to_string()));
+            }
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            self.get_open_pot(&mut conn, target_pot_id, requester_id).await?;
+
+            let expiry = expiry.unwrap_or_else(|| Utc::now() + Duration::days(INVITATION_LIFETIME_DAYS));
+
+            diesel::insert_into(pot_invitations)
+                .values(NewPotInvitation::with_code(
+                    target_pot_id,
+                    requester_id,
+                    generate_code(),
+                    expiry,
+                ))
+                .returning(PotInvitation::as_returning())
+                .get_result(&mut conn)
+                .await
+                .map_err(internal_error)
+        }
+
+        /// Gets all invitations to the pot that did not expire yet, if the requester is at least an
+        /// admin of the pot.
+        pub async fn get_pot_invitations(
+            &self,
+            target_pot_id: i32,
+            requester_id: Uuid,
+        ) -> Result<Vec<PotInvitation>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            self.get_managed_pot(&mut conn, target_pot_id, requester_id).await?;
+
+            pot_invitations
+                .filter(pot_id.eq(target_pot_id).and(expires_at.gt(now)))
+                .order(created_at.asc())
+                .select(PotInvitation::as_select())
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)
+        }
+
+        /// Revokes the given invitation to the pot, if the requester is at least an admin of the
+        /// pot.
+        pub async fn revoke_invitation(
+            &self,
+            target_pot_id: i32,
+            requester_id: Uuid,
+            invitation_id: i32,
+        ) -> Result<(), ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            self.get_managed_pot(&mut conn, target_pot_id, requester_id).await?;
+
+            let deleted = diesel::delete(
+                pot_invitations.filter(id.eq(invitation_id).and(pot_id.eq(target_pot_id))),
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(internal_error)?;
+
+            if deleted == 0 {
+                return Err(NotFound(format!("Invitation {} could not be found", invitation_id)));
+            }
+
+            Ok(())
+        }
+
+        /// Gets all invitations of the given user that did not expire yet, together with the pot
+        /// and the user that sent the invitation.
+        pub async fn get_pending_invitations(
+            &self,
+            requester_id: Uuid,
+        ) -> Result<Vec<(PotInvitation, Pot, User)>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            pot_invitations
+                .inner_join(pots)
+                .inner_join(users.on(users_table::id.eq(inviter_id)))
+                .filter(invitee_id.eq(requester_id).and(expires_at.gt(now)))
+                .order(created_at.asc())
+                .select((PotInvitation::as_select(), Pot::as_select(), User::as_select()))
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)
+        }
+
+        /// Accepts the given invitation of the requester, adding them to the pot.
+        pub async fn accept_invitation(
+            &self,
+            requester_id: Uuid,
+            invitation_id: i32,
+        ) -> Result<Pot, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let invitation = pot_invitations
+                .filter(id.eq(invitation_id).and(invitee_id.eq(requester_id)))
+                .filter(expires_at.gt(now))
+                .select(PotInvitation::as_select())
+                .first(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?
+                .ok_or_else(|| {
+                    NotFound(format!(
+                        "Invitation {} could not be found or has expired",
+                        invitation_id
+                    ))
+                })?;
+
+            Self::join_pot(&mut conn, invitation.pot_id(), requester_id).await
+        }
+
+        /// Declines the given invitation of the requester.
+        pub async fn decline_invitation(
+            &self,
+            requester_id: Uuid,
+            invitation_id: i32,
+        ) -> Result<(), ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let deleted = diesel::delete(
+                pot_invitations.filter(id.eq(invitation_id).and(invitee_id.eq(requester_id))),
+            )
+            .execute(&mut conn)
+            .await
+            .map_err(internal_error)?;
+
+            if deleted == 0 {
+                return Err(NotFound(format!("Invitation {} could not be found", invitation_id)));
+            }
+
+            Ok(())
+        }
+
+        /// Adds the requester to the pot of the invitation with the given code.
+        pub async fn join_with_code(
+            &self,
+            requester_id: Uuid,
+            invite_code: &str,
+        ) -> Result<Pot, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let invitation = pot_invitations
+                .filter(code.eq(invite_code).and(expires_at.gt(now)))
+                .select(PotInvitation::as_select())
+                .first(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?
+                .ok_or_else(|| {
+                    NotFound("The invite code could not be found or has expired".to_string())
+                })?;
+
+            Self::join_pot(&mut conn, invitation.pot_id(), requester_id).await
+        }
+
+        /// Adds the user to the pot, unless it is archived or the user is already part of it.
+        /// Invitations of the user to the pot are removed, since they are not needed anymore.
+        async fn join_pot(
+            conn: &mut AsyncPgConnection,
+            target_pot_id: i32,
+            new_user_id: Uuid,
+        ) -> Result<Pot, ExpenseError> {
+            let pot = pots
+                .filter(pots_table::id.eq(target_pot_id))
+                .select(Pot::as_select())
+                .first(conn)
+                .await
+                .map_err(internal_error)?;
+
+            if pot.is_archived() {
+                return Err(Locked(format!("Pot {} is archived", target_pot_id)));
+            }
+
+            let joined = conn
+                .transaction::<_, Error, _>(|conn| {
+                    async move {
+                        let joined = diesel::insert_into(pots_to_users)
+                            .values(PotToUser::new(target_pot_id, new_user_id))
+                            .on_conflict_do_nothing()
+                            .execute(conn)
+                            .await?;
+
+                        if joined > 0 {
+                            let event = NewPotEvent::new(
+                                target_pot_id,
+                                Some(new_user_id),
+                                PotEventKind::MemberJoined,
+                            );
+                            insert_events(conn, vec![event]).await?;
+                        }
+
+                        diesel::delete(
+                            pot_invitations.filter(
+                                pot_id.eq(target_pot_id).and(invitee_id.eq(new_user_id)),
+                            ),
+                        )
+                        .execute(conn)
+                        .await?;
+
+                        Ok(joined)
+                    }
+                    .scope_boxed()
+                })
+                .await
+                .map_err(|e| match e {
+                    // users are created on their first call of `current_user`
+                    Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+                        NotFound(format!("User {} could not be found", new_user_id))
+                    }
+                    e => internal_error(e),
+                })?;
+
+            if joined == 0 {
+                return Err(Conflict(format!(
+                    "User {} is already part of pot {}",
+                    new_user_id, target_pot_id
+                )));
+            }
+
+            Ok(pot)
+        }
+
+        /// Gets the pot, if the requester is at least an admin of it.
+        async fn get_managed_pot(
+            &self,
+            conn: &mut AsyncPgConnection,
+            target_pot_id: i32,
+            requester_id: Uuid,
+        ) -> Result<Pot, ExpenseError> {
+            self.pot_role_service
+                .require_role(target_pot_id, requester_id, PotRole::Admin)
+                .await?;
+
+            pots.filter(pots_table::id.eq(target_pot_id))
+                .select(Pot::as_select())
+                .first(conn)
+                .await
+                .optional()
+                .map_err(internal_error)?
+                .ok_or_else(|| NotFound(format!("Pot {} could not be found", target_pot_id)))
+        }
+
+        /// Gets the pot, if the requester is at least an admin of it and it is not archived.
+        async fn get_open_pot(
+            &self,
+            conn: &mut AsyncPgConnection,
+            target_pot_id: i32,
+            requester_id: Uuid,
+        ) -> Result<Pot, ExpenseError> {
+            let pot = self.get_managed_pot(conn, target_pot_id, requester_id).await?;
+
+            if pot.is_archived() {
+                return Err(Locked(format!("Pot {} is archived", target_pot_id)));
+            }
+
+            Ok(pot)
+        }
+    }
+
+    /// Generates a new random invite code.
+    pub(crate) fn generate_code() -> String {
+        let mut bytes = [0u8; CODE_LENGTH];
+        OsRng.fill_bytes(&mut bytes);
+
+        bytes
+            .iter()
+            .map(|byte| CODE_ALPHABET[(byte % 32) as usize] as char)
+            .collect()
+    }
+
+    /// Creates a new InvitationService.
+    pub fn new_service(pool: DbPool) -> InvitationService {
+        InvitationService {
+            db_pool: pool.clone(),
+            pot_role_service: pot_role_service::new_service(pool),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::invitation_service::invitation_service::generate_code;
+
+    #[test]
+    fn test_generate_code() {
+        let code = generate_code();
+
+        assert_eq!(code.len(), 12);
+        assert!(code.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+        assert!(!code.contains(['l', 'o', '0', '1']));
+        assert_ne!(code, generate_code());
+    }
+}
@@ -1,16 +1,31 @@
+// Every file wraps its content in a module named like the file itself.
+#![allow(clippy::module_inception)]
+
 use std::fmt::{Display, Formatter};
 use std::sync::{Arc, LazyLock};
 use tokio::sync::Mutex;
 use diesel::result::Error;
 use crate::cron_manager_service::cron_manager_service::CronManagerService;
 
+pub mod api_token_service;
 pub mod currency_service;
 pub mod expense_service;
+pub mod guest_service;
 pub mod health_service;
+pub mod import_service;
+pub mod invitation_service;
+pub mod local_account_service;
+pub mod money;
+pub mod pot_event_service;
+pub mod pot_role_service;
 pub mod pot_service;
+pub mod settlement_service;
 pub mod user_service;
 pub mod template_service;
 pub mod cron_manager_service;
+/// Fixtures for tests that need a database, shared with the tests of the api.
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 
 /// We only need one instance of the cron manager service, so we use a static variable.
 /// Making it pseudo singleton. Usinc Arc to ensure that only one instance exists at any time.
@@ -32,6 +47,8 @@ pub enum ExpenseError {
     Locked(String),
     /// Indicates that there was an error with the configuration of a cron job.
     CronConfigError(String),
+    /// Indicates that the given input is invalid and the request can't be processed.
+    BadRequest(String),
 }
 
 /// Produces a `NotFound` from the given `err`.
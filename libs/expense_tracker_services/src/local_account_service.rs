@@ -0,0 +1,199 @@
+pub mod local_account_service {
+    use crate::ExpenseError::{BadRequest, Conflict};
+    use crate::{internal_error, ExpenseError};
+    use argon2::password_hash::rand_core::OsRng;
+    use argon2::password_hash::SaltString;
+    use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+    use diesel::result::{DatabaseErrorKind, Error};
+    use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper};
+    use diesel_async::scoped_futures::ScopedFutureExt;
+    use diesel_async::{AsyncConnection, RunQueryDsl};
+    use expense_tracker_db::local_accounts::local_accounts::{LocalAccount, NewLocalAccount};
+    use expense_tracker_db::schema::local_accounts::dsl::{local_accounts, user_id, username};
+    use expense_tracker_db::schema::users::dsl::users;
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::users::users::User;
+    use uuid::Uuid;
+
+    /// The minimum length of a password of a local account.
+    const MIN_PASSWORD_LENGTH: usize = 8;
+
+    /// An argon2 hash with the default parameters that no password is checked against for real.
+    /// Verifying against it when there is no account takes as long as for an existing one, so
+    /// the response time doesn't tell whether a username exists.
+    pub(crate) const DUMMY_PASSWORD_HASH: &str =
+        "$argon2id$v=19$m=19456,t=2,p=1$UPYvu/R1VcVw3pIb7JIdCw$5PtghiB1DUlMFF8b5oC0v7gu+kMQbngFuQMA31tMz4U";
+
+    /// A service managing the accounts used by the built-in authentication.
+    #[derive(Clone)]
+    pub struct LocalAccountService {
+        db_pool: DbPool,
+    }
+
+    impl LocalAccountService {
+        /// Creates a new user together with a local account to log in with the given username
+        /// and password. The username is also used as the name of the user.
+        pub async fn create_account(
+            &self,
+            new_username: &str,
+            password: &str,
+            is_admin: bool,
+        ) -> Result<(LocalAccount, User), ExpenseError> {
+            let new_username = new_username.trim().to_string();
+
+            if new_username.is_empty() {
+                return Err(BadRequest("The username must not be empty".to_string()));
+            }
+
+            if password.chars().count() < MIN_PASSWORD_LENGTH {
+                return Err(BadRequest(format!(
+                    "The password must have at least {} characters",
+                    MIN_PASSWORD_LENGTH
+                )));
+            }
+
+            let password_hash = hash_password(password.to_string()).await?;
+            let new_user = User::new(Uuid::new_v4(), new_username.clone());
+            let new_account =
+                NewLocalAccount::new(new_user.id(), new_username.clone(), password_hash, is_admin);
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            // the user and their account are created together, so there are no users without
+            // an account to log in with
+            conn.transaction::<_, Error, _>(|conn| {
+                async move {
+                    let user = diesel::insert_into(users)
+                        .values(new_user)
+                        .get_result::<User>(conn)
+                        .await?;
+
+                    let account = diesel::insert_into(local_accounts)
+                        .values(new_account)
+                        .returning(LocalAccount::as_returning())
+                        .get_result::<LocalAccount>(conn)
+                        .await?;
+
+                    Ok((account, user))
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(|err| match err {
+                Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                    Conflict(format!("The username {} is already taken", new_username))
+                }
+                err => internal_error(err),
+            })
+        }
+
+        /// Gets the account and user with the given username, if the given password matches.
+        /// Returns `None` if there is no such account or the password is wrong, without telling
+        /// which one it is.
+        pub async fn authenticate(
+            &self,
+            to_find: &str,
+            password: &str,
+        ) -> Result<Option<(LocalAccount, User)>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let account = local_accounts
+                .inner_join(users)
+                .filter(username.eq(to_find.trim()))
+                .select((LocalAccount::as_select(), User::as_select()))
+                .first::<(LocalAccount, User)>(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?;
+
+            let Some((account, user)) = account else {
+                verify_password(password.to_string(), DUMMY_PASSWORD_HASH.to_string()).await?;
+                return Ok(None);
+            };
+
+            if verify_password(password.to_string(), account.password_hash().to_string()).await? {
+                Ok(Some((account, user)))
+            } else {
+                Ok(None)
+            }
+        }
+
+        /// Gets the account and user of the user with the given id, if they have an account.
+        pub async fn get_account(
+            &self,
+            account_user_id: Uuid,
+        ) -> Result<Option<(LocalAccount, User)>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            local_accounts
+                .inner_join(users)
+                .filter(user_id.eq(account_user_id))
+                .select((LocalAccount::as_select(), User::as_select()))
+                .first::<(LocalAccount, User)>(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)
+        }
+    }
+
+    /// Hashes the given password with argon2 and a random salt. Hashing is slow on purpose, so
+    /// it is done on a blocking thread.
+    pub(crate) async fn hash_password(password: String) -> Result<String, ExpenseError> {
+        tokio::task::spawn_blocking(move || {
+            let salt = SaltString::generate(&mut OsRng);
+
+            Argon2::default()
+                .hash_password(password.as_bytes(), &salt)
+                .map(|hash| hash.to_string())
+                .map_err(|err| ExpenseError::Internal(err.to_string()))
+        })
+        .await
+        .map_err(internal_error)?
+    }
+
+    /// Checks if the given password matches the given argon2 hash.
+    pub(crate) async fn verify_password(password: String, password_hash: String) -> Result<bool, ExpenseError> {
+        tokio::task::spawn_blocking(move || {
+            let password_hash = PasswordHash::new(&password_hash)
+                .map_err(|err| ExpenseError::Internal(err.to_string()))?;
+
+            Ok(Argon2::default()
+                .verify_password(password.as_bytes(), &password_hash)
+                .is_ok())
+        })
+        .await
+        .map_err(internal_error)?
+    }
+
+    /// Creates a new LocalAccountService.
+    pub fn new_service(pool: DbPool) -> LocalAccountService {
+        LocalAccountService { db_pool: pool }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::local_account_service::local_account_service::{
+        hash_password, verify_password, DUMMY_PASSWORD_HASH,
+    };
+
+    #[tokio::test]
+    async fn test_hash_and_verify_password() {
+        let hash = hash_password("correct horse".to_string()).await.unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse".to_string(), hash.clone()).await.unwrap());
+        assert!(!verify_password("wrong horse".to_string(), hash.clone()).await.unwrap());
+
+        // salts are random, so the same password never has the same hash
+        assert_ne!(hash, hash_password("correct horse".to_string()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_dummy_password_hash_is_valid() {
+        // an invalid hash would fail fast and give unknown usernames away again
+        assert!(!verify_password("correct horse".to_string(), DUMMY_PASSWORD_HASH.to_string())
+            .await
+            .unwrap());
+    }
+}
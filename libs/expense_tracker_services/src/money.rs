@@ -0,0 +1,262 @@
+pub mod money {
+    use uuid::Uuid;
+
+    /// Amounts entered by users are rounded to this many decimal places of the minor unit
+    /// first, so that e.g. 1.005 is not treated as 100.49999999999999 cents.
+    const NORMALIZATION_FACTOR: f64 = 1_000_000.0;
+
+    /// Converts the given amount in major units (e.g. 10.05) into an amount in minor units
+    /// (e.g. 1005). Rounds half away from zero.
+    pub fn to_minor_units(amount: f64, minor_units: i32) -> i64 {
+        scale(amount, minor_units).round() as i64
+    }
+
+    /// Converts the given amount in minor units (e.g. 1005) into an amount in major units
+    /// (e.g. 10.05). Only meant to present amounts, never calculate with the result!
+    pub fn from_minor_units(amount: i64, minor_units: i32) -> f64 {
+        amount as f64 / 10f64.powi(minor_units)
+    }
+
+    /// Formats the given amount in minor units as a decimal number in major units with exactly
+    /// `minor_units` digits after the decimal point, e.g. 1050 as "10.50". Unlike
+    /// `from_minor_units`, the result is exact.
+    pub fn format_minor_units(amount: i64, minor_units: i32) -> String {
+        let sign = if amount < 0 { "-" } else { "" };
+        let amount = amount.unsigned_abs();
+
+        if minor_units <= 0 {
+            return format!("{}{}", sign, amount);
+        }
+
+        let factor = 10u64.pow(minor_units as u32);
+
+        format!(
+            "{}{}.{:0width$}",
+            sign,
+            amount / factor,
+            amount % factor,
+            width = minor_units as usize
+        )
+    }
+
+    /// Converts the given amount in minor units of one currency into minor units of another
+    /// currency, using the given exchange rate. Rounds half away from zero.
+    ///
+    /// E.g. 10.00 EUR (1000) at a rate of 0.94 and 2 minor units each results in 940.
+    pub fn convert(amount: i64, rate: f64, from_minor_units: i32, to_minor_units: i32) -> i64 {
+        let factor = 10f64.powi(to_minor_units - from_minor_units);
+
+        normalize(amount as f64 * rate * factor).round() as i64
+    }
+
+    /// Rounds the given amounts in major units to minor units, so that they add up to the
+    /// rounded total of all amounts. Each amount is rounded down and the leftover minor units
+    /// are given to the `remainder_receiver`. If they are not part of the given amounts, the
+    /// user with the smallest id receives them instead.
+    ///
+    /// E.g. 10.00 split three ways as 3.3333 each results in 3.34 for the remainder receiver
+    /// and 3.33 for everyone else.
+    pub fn round_splits(
+        amounts: &[(Uuid, f64)],
+        minor_units: i32,
+        remainder_receiver: Uuid,
+    ) -> Vec<(Uuid, i64)> {
+        let scaled = amounts
+            .iter()
+            .map(|(user, amount)| (*user, scale(*amount, minor_units)))
+            .collect::<Vec<_>>();
+
+        let total = scaled.iter().map(|(_, amount)| amount).sum::<f64>().round() as i64;
+
+        let mut rounded = scaled
+            .iter()
+            .map(|(user, amount)| (*user, amount.floor() as i64))
+            .collect::<Vec<_>>();
+
+        assign_leftover(&mut rounded, total, remainder_receiver);
+
+        rounded
+    }
+
+    /// Distributes the given total in minor units proportionally to the given weights. Each
+    /// part is rounded down and the leftover minor units are given to the `remainder_receiver`,
+    /// the same way `round_splits` does.
+    pub fn allocate(
+        total: i64,
+        weights: &[(Uuid, f64)],
+        remainder_receiver: Uuid,
+    ) -> Vec<(Uuid, i64)> {
+        let weight_sum = weights.iter().map(|(_, weight)| weight).sum::<f64>();
+
+        if weight_sum <= 0.0 {
+            return weights.iter().map(|(user, _)| (*user, 0)).collect();
+        }
+
+        let mut allocated = weights
+            .iter()
+            .map(|(user, weight)| {
+                let exact = normalize(total as f64 * weight / weight_sum);
+                (*user, exact.floor() as i64)
+            })
+            .collect::<Vec<_>>();
+
+        assign_leftover(&mut allocated, total, remainder_receiver);
+
+        allocated
+    }
+
+    /// Adds the difference between the given total and the sum of the given amounts to the
+    /// amount of the `remainder_receiver`, or the user with the smallest id if they are not part
+    /// of the amounts.
+    fn assign_leftover(amounts: &mut [(Uuid, i64)], total: i64, remainder_receiver: Uuid) {
+        let leftover = total - amounts.iter().map(|(_, amount)| amount).sum::<i64>();
+
+        let receiver = amounts
+            .iter()
+            .position(|(user, _)| *user == remainder_receiver)
+            .or_else(|| {
+                amounts
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, (user, _))| *user)
+                    .map(|(index, _)| index)
+            });
+
+        if let Some(receiver) = receiver {
+            amounts[receiver].1 += leftover;
+        }
+    }
+
+    /// Scales the given amount in major units to minor units without rounding it to an integer.
+    fn scale(amount: f64, minor_units: i32) -> f64 {
+        normalize(amount * 10f64.powi(minor_units))
+    }
+
+    /// Rounds the given amount in minor units to the precision given by `NORMALIZATION_FACTOR`.
+    fn normalize(amount: f64) -> f64 {
+        (amount * NORMALIZATION_FACTOR).round() / NORMALIZATION_FACTOR
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::money::money::{
+        allocate, convert, format_minor_units, from_minor_units, round_splits, to_minor_units,
+    };
+    use uuid::{uuid, Uuid};
+
+    const USER_ONE: Uuid = uuid!("e6be621a-ec2d-48f3-8027-0d34cf5cbe40");
+    const USER_TWO: Uuid = uuid!("729c270f-74d1-436f-aa46-4fe6a3dcb460");
+    const USER_THREE: Uuid = uuid!("7ec9119b-10a2-48b8-be5f-d0f8ba9aad8d");
+    const OTHER_USER: Uuid = uuid!("01913042-053a-4cb2-846d-4b58153185b8");
+
+    #[test]
+    fn test_to_minor_units_rounds_half_away_from_zero() {
+        assert_eq!(to_minor_units(10.0, 2), 1000);
+        assert_eq!(to_minor_units(1.005, 2), 101);
+        assert_eq!(to_minor_units(0.1 + 0.2, 2), 30);
+        assert_eq!(to_minor_units(-1.005, 2), -101);
+        assert_eq!(to_minor_units(1234.5, 0), 1235);
+    }
+
+    #[test]
+    fn test_from_minor_units() {
+        assert_eq!(from_minor_units(1005, 2), 10.05);
+        assert_eq!(from_minor_units(1235, 0), 1235.0);
+    }
+
+    #[test]
+    fn test_format_minor_units() {
+        assert_eq!(format_minor_units(1050, 2), "10.50");
+        assert_eq!(format_minor_units(5, 2), "0.05");
+        assert_eq!(format_minor_units(-5, 2), "-0.05");
+        assert_eq!(format_minor_units(-1234, 3), "-1.234");
+        assert_eq!(format_minor_units(1605, 0), "1605");
+    }
+
+    #[test]
+    fn test_convert_between_minor_units() {
+        assert_eq!(convert(1000, 0.94, 2, 2), 940);
+        assert_eq!(convert(1000, 160.5, 2, 0), 1605);
+        assert_eq!(convert(1605, 1.0 / 160.5, 0, 2), 1000);
+        assert_eq!(convert(-1000, 1.10005, 2, 2), -1100);
+        assert_eq!(convert(333, 0.5, 2, 2), 167);
+    }
+
+    /// 10.00 split three ways must add up to 10.00 again, the remainder receiver gets the
+    /// leftover cent.
+    #[test]
+    fn test_round_splits_leftover_goes_to_remainder_receiver() {
+        let third = 10.0 / 3.0;
+        let amounts = vec![(USER_ONE, third), (USER_TWO, third), (USER_THREE, third)];
+
+        let rounded = round_splits(&amounts, 2, USER_TWO);
+
+        assert_eq!(
+            rounded,
+            vec![(USER_ONE, 333), (USER_TWO, 334), (USER_THREE, 333)]
+        );
+    }
+
+    #[test]
+    fn test_round_splits_leftover_goes_to_smallest_id_without_receiver() {
+        let third = 10.0 / 3.0;
+        let amounts = vec![(USER_ONE, third), (USER_TWO, third), (USER_THREE, third)];
+
+        let rounded = round_splits(&amounts, 2, OTHER_USER);
+
+        assert_eq!(
+            rounded,
+            vec![(USER_ONE, 333), (USER_TWO, 334), (USER_THREE, 333)]
+        );
+        assert_eq!(round_splits(&amounts, 2, USER_THREE)[2], (USER_THREE, 334));
+    }
+
+    #[test]
+    fn test_round_splits_exact_amounts_are_kept() {
+        let amounts = vec![(USER_ONE, 12.34), (USER_TWO, 0.1), (USER_THREE, 0.2)];
+
+        let rounded = round_splits(&amounts, 2, USER_ONE);
+
+        assert_eq!(
+            rounded,
+            vec![(USER_ONE, 1234), (USER_TWO, 10), (USER_THREE, 20)]
+        );
+    }
+
+    #[test]
+    fn test_allocate_equal_weights() {
+        let weights = vec![(USER_ONE, 1.0), (USER_TWO, 1.0), (USER_THREE, 1.0)];
+
+        let allocated = allocate(1000, &weights, USER_THREE);
+
+        assert_eq!(
+            allocated,
+            vec![(USER_ONE, 333), (USER_TWO, 333), (USER_THREE, 334)]
+        );
+    }
+
+    #[test]
+    fn test_allocate_by_weights() {
+        let weights = vec![(USER_ONE, 2.0), (USER_TWO, 1.0), (USER_THREE, 1.0)];
+
+        let allocated = allocate(1001, &weights, OTHER_USER);
+
+        assert_eq!(
+            allocated,
+            vec![(USER_ONE, 500), (USER_TWO, 251), (USER_THREE, 250)]
+        );
+    }
+
+    #[test]
+    fn test_allocate_percentages_without_float_drift() {
+        let weights = vec![(USER_ONE, 70.0), (USER_TWO, 20.0), (USER_THREE, 10.0)];
+
+        let allocated = allocate(1000, &weights, USER_ONE);
+
+        assert_eq!(
+            allocated,
+            vec![(USER_ONE, 700), (USER_TWO, 200), (USER_THREE, 100)]
+        );
+    }
+}
@@ -0,0 +1,114 @@
+pub mod pot_event_service {
+    use crate::expense_service::expense_service::Pagination;
+    use crate::pot_role_service::pot_role_service;
+    use crate::pot_role_service::pot_role_service::PotRoleService;
+    use crate::{internal_error, ExpenseError};
+    use diesel::result::Error;
+    use diesel::{ExpressionMethods, JoinOnDsl, NullableExpressionMethods, QueryDsl, SelectableHelper};
+    use diesel_async::{AsyncPgConnection, RunQueryDsl};
+    use expense_tracker_db::pot_events::pot_events::{NewPotEvent, PotEvent};
+    use expense_tracker_db::pots::pots::PotRole;
+    use expense_tracker_db::schema::pot_events::dsl::{actor_id, id, pot_events, pot_id};
+    use expense_tracker_db::schema::users::dsl::users;
+    use expense_tracker_db::schema::users::id as db_user_id;
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::users::users::User;
+    use uuid::Uuid;
+
+    /// A single page of the activity of a pot.
+    pub struct PotEventPage {
+        events: Vec<(PotEvent, Option<User>)>,
+        total_count: i64,
+    }
+
+    impl PotEventPage {
+        /// The events together with the users that triggered them, newest first.
+        pub fn events(&self) -> &Vec<(PotEvent, Option<User>)> {
+            &self.events
+        }
+
+        /// Consumes the page and returns its events, newest first.
+        pub fn into_events(self) -> Vec<(PotEvent, Option<User>)> {
+            self.events
+        }
+
+        /// The number of events on all pages.
+        pub fn total_count(&self) -> i64 {
+            self.total_count
+        }
+    }
+
+    /// A service recording the events of pots and reading them back as their activity.
+    #[derive(Clone)]
+    pub struct PotEventService {
+        db_pool: DbPool,
+        pot_role_service: PotRoleService,
+    }
+
+    impl PotEventService {
+        /// Gets a single page of the events of the given pot, newest first, if the requester is
+        /// part of the pot.
+        pub async fn get_events(
+            &self,
+            target_pot_id: i32,
+            requester_id: Uuid,
+            page: &Pagination,
+        ) -> Result<PotEventPage, ExpenseError> {
+            page.check()?;
+
+            self.pot_role_service
+                .require_role(target_pot_id, requester_id, PotRole::Viewer)
+                .await?;
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let total_count = pot_events
+                .filter(pot_id.eq(target_pot_id))
+                .count()
+                .get_result::<i64>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            let events = pot_events
+                .left_join(users.on(actor_id.eq(db_user_id.nullable())))
+                .filter(pot_id.eq(target_pot_id))
+                .order(id.desc())
+                .offset(page.offset())
+                .limit(page.per_page())
+                .select((PotEvent::as_select(), Option::<User>::as_select()))
+                .load::<(PotEvent, Option<User>)>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            Ok(PotEventPage {
+                events,
+                total_count,
+            })
+        }
+    }
+
+    /// Inserts the given events on the given connection, so they can be recorded in the same
+    /// transaction as the change they describe.
+    pub(crate) async fn insert_events(
+        conn: &mut AsyncPgConnection,
+        events: Vec<NewPotEvent>,
+    ) -> Result<(), Error> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        diesel::insert_into(pot_events)
+            .values(events)
+            .execute(conn)
+            .await
+            .map(|_| ())
+    }
+
+    /// Creates a new PotEventService.
+    pub fn new_service(pool: DbPool) -> PotEventService {
+        PotEventService {
+            db_pool: pool.clone(),
+            pot_role_service: pot_role_service::new_service(pool),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+pub mod pot_role_service {
+    use crate::ExpenseError::{Forbidden, NotFound};
+    use crate::{internal_error, ExpenseError};
+    use diesel::{BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl};
+    use diesel_async::RunQueryDsl;
+    use expense_tracker_db::pots::pots::PotRole;
+    use expense_tracker_db::schema::pots_to_users::dsl::{pot_id, pots_to_users, role, user_id};
+    use expense_tracker_db::setup::DbPool;
+    use uuid::Uuid;
+
+    /// A service checking the roles of the members of pots.
+    #[derive(Clone)]
+    pub struct PotRoleService {
+        db_pool: DbPool,
+    }
+
+    impl PotRoleService {
+        /// Gets the role of the given user in the pot, `None` if they are not part of it.
+        pub async fn get_role(
+            &self,
+            target_pot_id: i32,
+            member_id: Uuid,
+        ) -> Result<Option<PotRole>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            pots_to_users
+                .filter(pot_id.eq(target_pot_id).and(user_id.eq(member_id)))
+                .select(role)
+                .first::<PotRole>(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)
+        }
+
+        /// Checks that the given user has at least the `required` role in the pot and returns
+        /// their role. Returns `NotFound` if the user is not part of the pot, so pots of others
+        /// stay hidden, and `Forbidden` if their role is too low.
+        pub async fn require_role(
+            &self,
+            target_pot_id: i32,
+            member_id: Uuid,
+            required: PotRole,
+        ) -> Result<PotRole, ExpenseError> {
+            let member_role = self
+                .get_role(target_pot_id, member_id)
+                .await?
+                .ok_or_else(|| NotFound(format!("Pot {} could not be found", target_pot_id)))?;
+
+            if member_role < required {
+                return Err(Forbidden(format!(
+                    "The role {} or higher is needed in pot {}",
+                    required.as_str(),
+                    target_pot_id
+                )));
+            }
+
+            Ok(member_role)
+        }
+    }
+
+    /// Creates a new PotRoleService.
+    pub fn new_service(pool: DbPool) -> PotRoleService {
+        PotRoleService { db_pool: pool }
+    }
+}
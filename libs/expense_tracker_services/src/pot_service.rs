@@ -2,31 +2,370 @@ pub mod pot_service {
     use crate::currency_service::currency_service;
     use crate::currency_service::currency_service::CurrencyService;
     use crate::expense_service::expense_service;
-    use crate::expense_service::expense_service::ExpenseService;
-    use crate::ExpenseError::{Conflict, Forbidden, Locked, NotFound};
+    use crate::expense_service::expense_service::{pay_expenses, ExpenseService, JoinedExpense};
+    use crate::pot_event_service::pot_event_service::insert_events;
+    use crate::pot_role_service::pot_role_service;
+    use crate::pot_role_service::pot_role_service::PotRoleService;
+    use crate::settlement_service::settlement_service;
+    use crate::settlement_service::settlement_service::{clear_settlements, SettlementService};
+    use crate::ExpenseError::{BadRequest, Conflict, Forbidden, Locked, NotFound};
     use crate::{check_error, internal_error, not_found_error, ExpenseError};
+    use diesel::dsl::exists;
     use diesel::{BoolExpressionMethods, ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl, SelectableHelper};
-    use diesel::internal::derives::multiconnection::chrono::{DateTime, Utc};
-    use diesel_async::RunQueryDsl;
+    use diesel::internal::derives::multiconnection::chrono::{DateTime, NaiveDate, Utc};
+    use diesel::{sql_types, QueryableByName};
+    use diesel_async::scoped_futures::ScopedFutureExt;
+    use diesel_async::{AsyncConnection, RunQueryDsl};
     use expense_tracker_db::currencies::currencies::Currency;
-    use expense_tracker_db::pots::pots::{NewPot, Pot, PotToUser};
+    use expense_tracker_db::pot_events::pot_events::{NewPotEvent, PotEventKind};
+    use expense_tracker_db::pots::pots::{NewPot, Pot, PotRole, PotToUser};
     use expense_tracker_db::schema::pots::dsl::pots;
+    use expense_tracker_db::schema::{expense_splits, expenses};
     use expense_tracker_db::schema::pots::{archived, archived_at, id as pots_id, id, owner_id};
     use expense_tracker_db::schema::pots_to_users::dsl::pots_to_users;
-    use expense_tracker_db::schema::pots_to_users::{pot_id, user_id};
+    use expense_tracker_db::schema::pots_to_users::{pot_id, role, user_id};
     use expense_tracker_db::schema::users::dsl::users;
-    use expense_tracker_db::schema::users::id as db_user_id;
+    use expense_tracker_db::schema::users::{guest_pot_id, id as db_user_id};
+    use expense_tracker_db::settlements::settlements::Settlement;
     use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::splits::splits::Split;
     use expense_tracker_db::users::users::User;
+    use std::collections::btree_map::Entry;
+    use std::collections::{BTreeMap, HashMap, HashSet};
     use uuid::Uuid;
     use log::warn;
 
+    /// A single transfer of a settlement plan, meaning that `from` has to pay `to` the given
+    /// `amount`, given in minor units.
+    #[derive(Clone)]
+    pub struct SettlementTransfer {
+        from: Uuid,
+        to: Uuid,
+        amount: i64,
+        splits: Vec<Split>,
+    }
+
+    impl SettlementTransfer {
+        /// Getter for from.
+        pub fn from(&self) -> Uuid {
+            self.from
+        }
+
+        /// Getter for to.
+        pub fn to(&self) -> Uuid {
+            self.to
+        }
+
+        /// Getter for amount.
+        pub fn amount(&self) -> i64 {
+            self.amount
+        }
+
+        /// The unpaid splits that are considered cleared once this transfer has been made.
+        pub fn splits(&self) -> &Vec<Split> {
+            &self.splits
+        }
+    }
+
+    /// The settlement plan of a pot for a single currency.
+    pub struct CurrencySettlement {
+        currency: Currency,
+        transfers: Vec<SettlementTransfer>,
+        netted_splits: Vec<Split>,
+    }
+
+    impl CurrencySettlement {
+        /// Getter for currency.
+        pub fn currency(&self) -> &Currency {
+            &self.currency
+        }
+
+        /// Getter for transfers.
+        pub fn transfers(&self) -> &Vec<SettlementTransfer> {
+            &self.transfers
+        }
+
+        /// Splits without a transfer from their debtor to their creditor, because their debts
+        /// cancel each other out, have already been covered by settlements or are paid through
+        /// the transfers of others.
+        pub fn netted_splits(&self) -> &Vec<Split> {
+            &self.netted_splits
+        }
+    }
+
+    /// The settlement plan of a pot, per currency and converted into the default currency of
+    /// the pot.
+    pub struct SettlementPlan {
+        currencies: Vec<CurrencySettlement>,
+        converted: Option<CurrencySettlement>,
+    }
+
+    impl SettlementPlan {
+        /// The settlement plans per currency used in the pot.
+        pub fn currencies(&self) -> &Vec<CurrencySettlement> {
+            &self.currencies
+        }
+
+        /// A single settlement plan for all currencies, converted into the default currency of
+        /// the pot. The amounts of its splits are converted as well. None if an exchange rate
+        /// needed for the conversion is unknown.
+        pub fn converted(&self) -> Option<&CurrencySettlement> {
+            self.converted.as_ref()
+        }
+    }
+
+    /// The activity of a single member of a pot in a single currency, in minor units.
+    #[derive(QueryableByName)]
+    pub struct MemberSummary {
+        #[diesel(sql_type = sql_types::Uuid, column_name = user_id)]
+        member_id: Uuid,
+        #[diesel(sql_type = sql_types::Int4)]
+        currency_id: i32,
+        #[diesel(sql_type = sql_types::Int8)]
+        paid: i64,
+        #[diesel(sql_type = sql_types::Int8)]
+        consumed: i64,
+        #[diesel(sql_type = sql_types::Int8)]
+        outstanding: i64,
+    }
+
+    impl MemberSummary {
+        /// Getter for member_id.
+        pub fn member_id(&self) -> Uuid {
+            self.member_id
+        }
+
+        /// The total amount of the expenses the member paid for.
+        pub fn paid(&self) -> i64 {
+            self.paid
+        }
+
+        /// The total amount of the splits of the member, meaning what they consumed.
+        pub fn consumed(&self) -> i64 {
+            self.consumed
+        }
+
+        /// The open balance of the member, taking settlements into account. If positive, the
+        /// member is owed money. If negative, they owe others money.
+        pub fn outstanding(&self) -> i64 {
+            self.outstanding
+        }
+    }
+
+    /// The expenses of a pot in a single currency and month, in minor units.
+    #[derive(QueryableByName)]
+    pub struct MonthSummary {
+        /// The first day of the month.
+        #[diesel(sql_type = sql_types::Date)]
+        month: NaiveDate,
+        #[diesel(sql_type = sql_types::Int4)]
+        currency_id: i32,
+        #[diesel(sql_type = sql_types::Int8)]
+        total: i64,
+        #[diesel(sql_type = sql_types::Int8)]
+        expense_count: i64,
+    }
+
+    impl MonthSummary {
+        /// The first day of the month.
+        pub fn month(&self) -> NaiveDate {
+            self.month
+        }
+
+        /// Getter for total.
+        pub fn total(&self) -> i64 {
+            self.total
+        }
+
+        /// Getter for expense_count.
+        pub fn expense_count(&self) -> i64 {
+            self.expense_count
+        }
+    }
+
+    /// The summary of a pot for a single currency, in minor units.
+    pub struct CurrencySummary {
+        currency: Currency,
+        total: i64,
+        expense_count: i64,
+        members: Vec<MemberSummary>,
+        months: Vec<MonthSummary>,
+    }
+
+    impl CurrencySummary {
+        /// Getter for currency.
+        pub fn currency(&self) -> &Currency {
+            &self.currency
+        }
+
+        /// The total amount of all expenses in this currency.
+        pub fn total(&self) -> i64 {
+            self.total
+        }
+
+        /// Getter for expense_count.
+        pub fn expense_count(&self) -> i64 {
+            self.expense_count
+        }
+
+        /// The members that spent, consumed or settled anything in this currency.
+        pub fn members(&self) -> &Vec<MemberSummary> {
+            &self.members
+        }
+
+        /// The months with expenses in this currency, the oldest first.
+        pub fn months(&self) -> &Vec<MonthSummary> {
+            &self.months
+        }
+    }
+
+    /// Aggregates what each member paid for and consumed, as well as their open balance, per
+    /// currency. The open balance is made up of the unpaid splits of the expenses the member paid
+    /// for, their own unpaid splits of the expenses of others and their settlements.
+    const MEMBER_SUMMARY_QUERY: &str = "
+        SELECT user_id,
+               currency_id,
+               SUM(paid)::BIGINT AS paid,
+               SUM(consumed)::BIGINT AS consumed,
+               SUM(outstanding)::BIGINT AS outstanding
+        FROM (
+            SELECT owner_id AS user_id, currency_id, total_amount AS paid,
+                   0::BIGINT AS consumed, 0::BIGINT AS outstanding
+            FROM expenses
+            WHERE pot_id = $1
+            UNION ALL
+            SELECT s.user_id, e.currency_id, 0, s.amount,
+                   CASE WHEN s.is_paid OR s.user_id = e.owner_id THEN 0 ELSE -s.amount END
+            FROM expense_splits s
+            JOIN expenses e ON e.id = s.expense_id
+            WHERE e.pot_id = $1
+            UNION ALL
+            SELECT e.owner_id, e.currency_id, 0, 0, s.amount
+            FROM expense_splits s
+            JOIN expenses e ON e.id = s.expense_id
+            WHERE e.pot_id = $1 AND NOT s.is_paid AND s.user_id <> e.owner_id
+            UNION ALL
+            SELECT payer_id, currency_id, 0, 0, amount
+            FROM settlements
+            WHERE pot_id = $1 AND cleared_at IS NULL
+            UNION ALL
+            SELECT receiver_id, currency_id, 0, 0, -amount
+            FROM settlements
+            WHERE pot_id = $1 AND cleared_at IS NULL
+        ) AS contributions
+        GROUP BY user_id, currency_id
+        ORDER BY currency_id, user_id";
+
+    /// Aggregates the expenses per currency and month they were spent in.
+    const MONTH_SUMMARY_QUERY: &str = "
+        SELECT date_trunc('month', spent_at AT TIME ZONE 'UTC')::DATE AS month,
+               currency_id,
+               SUM(total_amount)::BIGINT AS total,
+               COUNT(*) AS expense_count
+        FROM expenses
+        WHERE pot_id = $1
+        GROUP BY 1, 2
+        ORDER BY 1, 2";
+
+    /// The number of expenses loaded at once by `PotService::export_pot_batch`.
+    pub const EXPORT_BATCH_SIZE: i64 = 500;
+
+    /// All data of a pot needed to export its expenses, or a single batch of them.
+    pub struct PotExport {
+        pot: Pot,
+        expenses: Vec<JoinedExpense>,
+        users: HashMap<Uuid, User>,
+        next_position: Option<(DateTime<Utc>, i32)>,
+    }
+
+    impl PotExport {
+        /// Getter for pot.
+        pub fn pot(&self) -> &Pot {
+            &self.pot
+        }
+
+        /// All expenses of the pot, ordered by the date they were spent on.
+        pub fn expenses(&self) -> &Vec<JoinedExpense> {
+            &self.expenses
+        }
+
+        /// Gets the name of the given user. Contains all owners of expenses and users of splits,
+        /// even if they are no longer part of the pot.
+        pub fn user_name(&self, to_find: Uuid) -> Option<&str> {
+            self.users.get(&to_find).map(|user| user.name())
+        }
+
+        /// The position to load the next batch of a batched export at. None if there are no
+        /// more expenses.
+        pub fn next_position(&self) -> Option<(DateTime<Utc>, i32)> {
+            self.next_position
+        }
+    }
+
+    /// The maximum length of a pot's name.
+    const MAX_NAME_LENGTH: usize = 24;
+
+    /// The maximum length of a pot's description.
+    const MAX_DESCRIPTION_LENGTH: usize = 500;
+
+    /// The settings of a pot that can be updated. Fields set to `None` are left untouched, while
+    /// a description set to `Some(None)` is removed.
+    #[derive(diesel::AsChangeset)]
+    #[diesel(table_name = expense_tracker_db::schema::pots)]
+    pub struct PotUpdate {
+        name: Option<String>,
+        default_currency_id: Option<i32>,
+        description: Option<Option<String>>,
+    }
+
+    impl PotUpdate {
+        /// Creates a new PotUpdate.
+        pub fn new(
+            name: Option<String>,
+            default_currency_id: Option<i32>,
+            description: Option<Option<String>>,
+        ) -> Self {
+            Self {
+                name,
+                default_currency_id,
+                description,
+            }
+        }
+    }
+
+    /// Checks the name and description of a pot against the constraints of the database.
+    pub(crate) fn validate_settings(
+        name: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<(), ExpenseError> {
+        if name.is_some_and(|name| name.trim().is_empty() || name.chars().count() > MAX_NAME_LENGTH)
+        {
+            return Err(BadRequest(format!(
+                "The name must not be empty or longer than {} characters",
+                MAX_NAME_LENGTH
+            )));
+        }
+
+        if description
+            .is_some_and(|description| description.chars().count() > MAX_DESCRIPTION_LENGTH)
+        {
+            return Err(BadRequest(format!(
+                "The description must not be longer than {} characters",
+                MAX_DESCRIPTION_LENGTH
+            )));
+        }
+
+        Ok(())
+    }
+
     /// A service offering interfaces related to Pots.
     #[derive(Clone)]
     pub struct PotService {
         db_pool: DbPool,
         currency_service: CurrencyService,
         expense_service: ExpenseService,
+        settlement_service: SettlementService,
+        pot_role_service: PotRoleService,
     }
 
     impl PotService {
@@ -35,9 +374,31 @@ pub mod pot_service {
             &self,
             new_pot: NewPot,
         ) -> Result<(Pot, Currency, Vec<User>), ExpenseError> {
+            self.create_pot_with_details(new_pot, None).await
+        }
+
+        /// Creates a pot from the template with the given id.
+        pub(crate) async fn create_pot_from_template(
+            &self,
+            new_pot: NewPot,
+            template_id: i32,
+        ) -> Result<(Pot, Currency, Vec<User>), ExpenseError> {
+            let details = format!("Created from template {}", template_id);
+
+            self.create_pot_with_details(new_pot, Some(details)).await
+        }
+
+        /// Creates a pot, recording its creation with the given details.
+        async fn create_pot_with_details(
+            &self,
+            new_pot: NewPot,
+            details: Option<String>,
+        ) -> Result<(Pot, Currency, Vec<User>), ExpenseError> {
+            validate_settings(Some(new_pot.name()), new_pot.description())?;
+
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
-            let loaded_pot_currency_id = new_pot.default_currency_id().clone();
+            let loaded_pot_currency_id = new_pot.default_currency_id();
 
             let currency = self
                 .currency_service
@@ -45,13 +406,45 @@ pub mod pot_service {
                 .await
                 .map_err(check_error)?;
 
-            let pot = diesel::insert_into(pots)
-                .values(new_pot)
-                .returning(Pot::as_returning())
-                .get_result::<Pot>(&mut conn)
+            let (pot, result) = conn
+                .transaction::<_, diesel::result::Error, _>(|conn| {
+                    async move {
+                        let pot = diesel::insert_into(pots)
+                            .values(new_pot)
+                            .returning(Pot::as_returning())
+                            .get_result::<Pot>(conn)
+                            .await?;
+
+                        let pot_to_user =
+                            PotToUser::with_role(pot.id(), pot.owner_id(), PotRole::Owner);
+                        let result = diesel::insert_into(pots_to_users)
+                            .values(pot_to_user)
+                            .execute(conn)
+                            .await?;
+
+                        let mut event = NewPotEvent::new(
+                            pot.id(),
+                            Some(pot.owner_id()),
+                            PotEventKind::PotCreated,
+                        );
+
+                        if let Some(details) = details {
+                            event = event.with_details(details);
+                        }
+
+                        insert_events(conn, vec![event]).await?;
+
+                        Ok((pot, result))
+                    }
+                    .scope_boxed()
+                })
                 .await
                 .map_err(not_found_error)?;
 
+            if result == 0 {
+                warn!("Could not add user '{}' to joined table", pot.owner_id().clone());
+            }
+
             let pot_users = users
                 .filter(db_user_id.eq(pot.owner_id()))
                 .select(User::as_returning())
@@ -59,18 +452,92 @@ pub mod pot_service {
                 .await
                 .map_err(internal_error)?;
 
-            let pot_to_user = PotToUser::new(pot.id().clone(), pot.owner_id().clone());
-            let result = self.add_user_to_joined_table(pot_to_user).await?;
+                Ok((pot, currency, pot_users))
+        }
 
-            if result == 0 {
-                warn!("Could not add user '{}' to joined table", pot.owner_id().clone());
+        /// Updates the settings of the given pot, if the requester owns it. Changing the default
+        /// currency only changes the currency the balances are converted into, the expenses keep
+        /// their currencies.
+        pub async fn update_pot(
+            &self,
+            the_pot_id: i32,
+            pot_update: PotUpdate,
+            requester_id: Uuid,
+        ) -> Result<(Pot, Currency, Vec<User>), ExpenseError> {
+            self.pot_role_service
+                .require_role(the_pot_id, requester_id, PotRole::Owner)
+                .await?;
+
+            validate_settings(
+                pot_update.name.as_deref(),
+                pot_update.description.as_ref().and_then(Option::as_deref),
+            )?;
+
+            let pot = self.get_pot_by_id(the_pot_id, requester_id).await?;
+
+            if pot.is_archived() {
+                return Err(Locked(format!("Pot {} is archived", the_pot_id)));
             }
 
-                Ok((pot, currency, pot_users))
+            let currency_id = pot_update
+                .default_currency_id
+                .unwrap_or(pot.default_currency_id());
+
+            let currency = self.currency_service.get_currency_by_id(currency_id).await?;
+
+            let mut changes = vec![];
+
+            if let Some(name) = &pot_update.name {
+                changes.push(format!("name: {}", name));
+            }
+
+            if pot_update.default_currency_id.is_some() {
+                let currency_name = currency.code().unwrap_or(currency.name());
+                changes.push(format!("default currency: {}", currency_name));
+            }
+
+            match &pot_update.description {
+                Some(Some(_)) => changes.push("description changed".to_string()),
+                Some(None) => changes.push("description removed".to_string()),
+                None => {}
+            }
+
+            let event = NewPotEvent::new(the_pot_id, Some(requester_id), PotEventKind::PotUpdated)
+                .with_details(changes.join(", "));
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let pot = conn
+                .transaction::<_, diesel::result::Error, _>(|conn| {
+                    async move {
+                        let pot = diesel::update(pots.filter(id.eq(the_pot_id)))
+                            .set(pot_update)
+                            .returning(Pot::as_returning())
+                            .get_result::<Pot>(conn)
+                            .await?;
+
+                        insert_events(conn, vec![event]).await?;
+
+                        Ok(pot)
+                    }
+                    .scope_boxed()
+                })
+                .await
+                .map_err(internal_error)?;
+
+            let pot_users = users
+                .inner_join(pots_to_users.on(user_id.eq(db_user_id)))
+                .filter(pot_id.eq(the_pot_id))
+                .select(User::as_select())
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            Ok((pot, currency, pot_users))
         }
 
         /// Adds `new_user_id` to the pot with the given `the_pot_id` if the user with the `requester_id`
-        /// is the owner of that given pot.
+        /// is at least an admin of that given pot.
         pub async fn add_users_to_pot(
             &self,
             pots_to_user: Vec<PotToUser>,
@@ -81,14 +548,11 @@ pub mod pot_service {
             for pot_to_user in pots_to_user {
                 let the_pot_id = pot_to_user.pot_id();
 
-                let get_pot_by_id_and_owner =
-                    Self::get_pot_by_id_and_owner(self, the_pot_id, requester_id).await?;
+                self.pot_role_service
+                    .require_role(the_pot_id, requester_id, PotRole::Admin)
+                    .await?;
 
-                if get_pot_by_id_and_owner.is_none() {
-                    return Ok(false);
-                }
-
-                let pot = get_pot_by_id_and_owner.unwrap();
+                let pot = self.get_pot_by_id(the_pot_id, requester_id).await?;
 
                 if pot.is_archived() {
                     return Err(Locked(format!("Pot {} is archived", the_pot_id)))
@@ -96,6 +560,8 @@ pub mod pot_service {
 
                 let new_user_id = pot_to_user.user_id();
 
+                self.reject_guest(new_user_id).await?;
+
                 let pots_containing_user =
                     Self::get_pots_containing_user(self, the_pot_id, new_user_id).await?;
 
@@ -106,83 +572,386 @@ pub mod pot_service {
                     )));
                 }
 
-                result = self.add_user_to_joined_table(pot_to_user).await?;
+                let event =
+                    NewPotEvent::new(the_pot_id, Some(requester_id), PotEventKind::MemberAdded)
+                        .with_subject(new_user_id);
+
+                result = self.add_user_to_joined_table(pot_to_user, event).await?;
             }
 
             Ok(result > 0)
         }
 
+        /// Returns `BadRequest` if the given user is a guest, since guests are members of a
+        /// single pot and can't be given another role.
+        async fn reject_guest(&self, member_id: Uuid) -> Result<(), ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let is_guest = diesel::select(exists(
+                users.filter(db_user_id.eq(member_id).and(guest_pot_id.is_not_null())),
+            ))
+            .get_result::<bool>(&mut conn)
+            .await
+            .map_err(internal_error)?;
+
+            if is_guest {
+                return Err(BadRequest(format!(
+                    "User {} is a guest and can only be a member of their own pot",
+                    member_id
+                )));
+            }
+
+            Ok(())
+        }
+
+        /// Adds the user to the pot and records the given event about it.
         async fn add_user_to_joined_table(
             &self,
-            pot_to_user: PotToUser
+            pot_to_user: PotToUser,
+            event: NewPotEvent,
         ) -> Result<usize, ExpenseError> {
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
-            diesel::insert_into(pots_to_users)
-                .values(pot_to_user)
-                .execute(&mut conn)
-                .await
-                .map_err(internal_error)
+            conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                async move {
+                    let result = diesel::insert_into(pots_to_users)
+                        .values(pot_to_user)
+                        .execute(conn)
+                        .await?;
+
+                    insert_events(conn, vec![event]).await?;
+
+                    Ok(result)
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(internal_error)
         }
 
         /// Removes `user_id` from the pot with the given `the_pot_id` if the user with the `requester_id`
-        /// is the owner of that given pot.
+        /// is at least an admin of that given pot and has a higher role than the removed user. See
+        /// `remove_member` for when users can't be removed.
         pub async fn remove_user_from_pot(
             &self,
             pot_to_user: PotToUser,
             requester_id: Uuid,
         ) -> Result<bool, ExpenseError> {
-            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
             let the_pot_id = pot_to_user.pot_id();
 
-            let get_pot_by_id_and_owner =
-                Self::get_pot_by_id_and_owner(self, the_pot_id, requester_id).await?;
+            let requester_role = self
+                .pot_role_service
+                .require_role(the_pot_id, requester_id, PotRole::Admin)
+                .await?;
+
+            let pot = self.get_pot_by_id(the_pot_id, requester_id).await?;
 
-            if get_pot_by_id_and_owner.is_none() {
-                return Ok(false);
-            }
+            let event =
+                NewPotEvent::new(the_pot_id, Some(requester_id), PotEventKind::MemberRemoved)
+                    .with_subject(pot_to_user.user_id());
+
+            self.remove_member(&pot, pot_to_user.user_id(), Some(requester_role), event)
+                .await?;
+
+            Ok(true)
+        }
+
+        /// Removes the requester from the given pot. See `remove_member` for when users can't
+        /// leave a pot.
+        pub async fn leave_pot(&self, the_pot_id: i32, requester_id: Uuid) -> Result<(), ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let pot = pots
+                .filter(id.eq(the_pot_id))
+                .select(Pot::as_select())
+                .first(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?
+                .ok_or_else(|| NotFound(format!("Pot {} could not be found", the_pot_id)))?;
+
+            let event = NewPotEvent::new(the_pot_id, Some(requester_id), PotEventKind::MemberLeft);
+
+            self.remove_member(&pot, requester_id, None, event).await
+        }
 
-            let pot = get_pot_by_id_and_owner.unwrap();
+        /// Removes the given member from the pot. The owner can't be removed, and neither can
+        /// members with an open balance, meaning unpaid splits or expenses of theirs that others
+        /// did not pay yet, unless it was evened out by settlements. If the member is removed by
+        /// someone else, their `remover_role` has to be higher than the role of the member. The
+        /// given event is recorded together with the removal.
+        async fn remove_member(
+            &self,
+            pot: &Pot,
+            member_id: Uuid,
+            remover_role: Option<PotRole>,
+            event: NewPotEvent,
+        ) -> Result<(), ExpenseError> {
+            let the_pot_id = pot.id();
 
             if pot.is_archived() {
                 return Err(Locked(format!("Pot {} is archived", the_pot_id)))
             }
 
-            let to_delete_user_id = pot_to_user.user_id();
+            let member_role = self
+                .pot_role_service
+                .get_role(the_pot_id, member_id)
+                .await?
+                .ok_or_else(|| {
+                    NotFound(format!("User {} was not part of pot {}", member_id, the_pot_id))
+                })?;
+
+            if member_role == PotRole::Owner {
+                return Err(Conflict(format!(
+                    "User {} is the owner of pot {} and can't leave it",
+                    member_id, the_pot_id
+                )));
+            }
+
+            if remover_role.is_some_and(|remover_role| remover_role <= member_role) {
+                return Err(Forbidden(format!(
+                    "User {} can only be removed from pot {} by members with a higher role",
+                    member_id, the_pot_id
+                )));
+            }
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
-            let pots_containing_user =
-                Self::get_pots_containing_user(self, the_pot_id, to_delete_user_id).await?;
+            // the membership and the splits of the member in the pot are locked before the
+            // balance is checked, so paying or changing those splits waits for the removal
+            let removed = conn
+                .transaction::<_, diesel::result::Error, _>(|conn| {
+                    async move {
+                        pots_to_users
+                            .filter(pot_id.eq(the_pot_id).and(user_id.eq(member_id)))
+                            .select(user_id)
+                            .for_update()
+                            .load::<Uuid>(conn)
+                            .await?;
+
+                        expense_splits::table
+                            .filter(expense_splits::user_id.eq(member_id))
+                            .filter(
+                                expense_splits::expense_id.eq_any(
+                                    expenses::table
+                                        .filter(expenses::pot_id.eq(the_pot_id))
+                                        .select(expenses::id),
+                                ),
+                            )
+                            .select(expense_splits::expense_id)
+                            .for_update()
+                            .load::<i32>(conn)
+                            .await?;
+
+                        let has_open_balance = diesel::sql_query(MEMBER_SUMMARY_QUERY)
+                            .bind::<sql_types::Int4, _>(the_pot_id)
+                            .load::<MemberSummary>(conn)
+                            .await?
+                            .iter()
+                            .any(|summary| {
+                                summary.member_id() == member_id && summary.outstanding() != 0
+                            });
+
+                        if has_open_balance {
+                            return Ok(false);
+                        }
+
+                        diesel::delete(pots_to_users)
+                            .filter(pot_id.eq(the_pot_id).and(user_id.eq(member_id)))
+                            .execute(conn)
+                            .await?;
+
+                        insert_events(conn, vec![event]).await?;
+
+                        Ok(true)
+                    }
+                    .scope_boxed()
+                })
+                .await
+                .map_err(internal_error)?;
 
-            if pots_containing_user == 0 {
-                return Err(NotFound(format!(
-                    "User {} was not part of pot {}",
-                    to_delete_user_id, the_pot_id
+            if !removed {
+                return Err(Conflict(format!(
+                    "User {} still has an open balance in pot {}, which must be settled first",
+                    member_id, the_pot_id
                 )));
             }
 
-            let result = diesel::delete(pots_to_users)
-                .filter(user_id.eq(to_delete_user_id))
-                .execute(&mut conn)
+            Ok(())
+        }
+
+        /// Gets the members of the given pot together with their roles, ordered by role, starting
+        /// with the owner.
+        pub async fn get_members(
+            &self,
+            the_pot_id: i32,
+            requester_id: Uuid,
+        ) -> Result<Vec<(User, PotRole)>, ExpenseError> {
+            self.pot_role_service
+                .require_role(the_pot_id, requester_id, PotRole::Viewer)
+                .await?;
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let mut members = users
+                .inner_join(pots_to_users.on(user_id.eq(db_user_id)))
+                .filter(pot_id.eq(the_pot_id))
+                .select((User::as_select(), role))
+                .load::<(User, PotRole)>(&mut conn)
                 .await
                 .map_err(internal_error)?;
 
-            Ok(result > 0)
+            members.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+            Ok(members)
         }
 
-        async fn get_pot_by_id_and_owner(
+        /// Changes the role of the given member. Admins can only hand out and change roles below
+        /// their own, while the owner can change all other roles. A pot only ever has one owner,
+        /// so ownership is handed over via `transfer_ownership` instead.
+        pub async fn set_member_role(
             &self,
             the_pot_id: i32,
             requester_id: Uuid,
-        ) -> Result<Option<Pot>, ExpenseError> {
+            member_id: Uuid,
+            new_role: PotRole,
+        ) -> Result<(User, PotRole), ExpenseError> {
+            if new_role == PotRole::Owner {
+                return Err(BadRequest(format!(
+                    "The ownership of pot {} has to be transferred instead",
+                    the_pot_id
+                )));
+            }
+
+            let requester_role = self
+                .pot_role_service
+                .require_role(the_pot_id, requester_id, PotRole::Admin)
+                .await?;
+
+            let member_role = self
+                .pot_role_service
+                .get_role(the_pot_id, member_id)
+                .await?
+                .ok_or_else(|| {
+                    NotFound(format!("User {} is not part of pot {}", member_id, the_pot_id))
+                })?;
+
+            if member_role >= requester_role || new_role >= requester_role {
+                return Err(Forbidden(format!(
+                    "Only roles below your own can be changed in pot {}",
+                    the_pot_id
+                )));
+            }
+
+            self.reject_guest(member_id).await?;
+
+            let event = NewPotEvent::new(the_pot_id, Some(requester_id), PotEventKind::RoleChanged)
+                .with_subject(member_id)
+                .with_details(new_role.as_str().to_string());
+
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
-            pots
-                .filter(id.eq(the_pot_id).and(owner_id.eq(requester_id)))
-                .select(Pot::as_select())
-                .first(&mut conn)
+            conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                async move {
+                    diesel::update(
+                        pots_to_users.filter(pot_id.eq(the_pot_id).and(user_id.eq(member_id))),
+                    )
+                    .set(role.eq(new_role))
+                    .execute(conn)
+                    .await?;
+
+                    insert_events(conn, vec![event]).await
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(internal_error)?;
+
+            let member = users
+                .filter(db_user_id.eq(member_id))
+                .select(User::as_select())
+                .first::<User>(&mut conn)
                 .await
-                .optional()
-                .map_err(internal_error)
+                .map_err(internal_error)?;
+
+            Ok((member, new_role))
+        }
+
+        /// Hands the ownership of the given pot over to another member. The previous owner stays
+        /// part of the pot as an admin. Only the owner can do this.
+        pub async fn transfer_ownership(
+            &self,
+            the_pot_id: i32,
+            requester_id: Uuid,
+            new_owner_id: Uuid,
+        ) -> Result<Vec<(User, PotRole)>, ExpenseError> {
+            self.pot_role_service
+                .require_role(the_pot_id, requester_id, PotRole::Owner)
+                .await?;
+
+            if new_owner_id == requester_id {
+                return Err(BadRequest(format!(
+                    "User {} already owns pot {}",
+                    new_owner_id, the_pot_id
+                )));
+            }
+
+            self.pot_role_service
+                .get_role(the_pot_id, new_owner_id)
+                .await?
+                .ok_or_else(|| {
+                    NotFound(format!("User {} is not part of pot {}", new_owner_id, the_pot_id))
+                })?;
+
+            self.reject_guest(new_owner_id).await?;
+
+            self.set_owner(the_pot_id, new_owner_id, Some(requester_id)).await?;
+
+            self.get_members(the_pot_id, requester_id).await
+        }
+
+        /// Makes the given user the owner of the pot, adding them to it if they are not part of it
+        /// yet. The previous owner is demoted to an admin, so the pot never has two owners.
+        async fn set_owner(
+            &self,
+            the_pot_id: i32,
+            new_owner_id: Uuid,
+            actor_id: Option<Uuid>,
+        ) -> Result<Pot, ExpenseError> {
+            let event = NewPotEvent::new(the_pot_id, actor_id, PotEventKind::OwnershipTransferred)
+                .with_subject(new_owner_id);
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                async move {
+                    diesel::update(
+                        pots_to_users.filter(pot_id.eq(the_pot_id).and(role.eq(PotRole::Owner))),
+                    )
+                    .set(role.eq(PotRole::Admin))
+                    .execute(conn)
+                    .await?;
+
+                    diesel::insert_into(pots_to_users)
+                        .values(PotToUser::with_role(the_pot_id, new_owner_id, PotRole::Owner))
+                        .on_conflict((pot_id, user_id))
+                        .do_update()
+                        .set(role.eq(PotRole::Owner))
+                        .execute(conn)
+                        .await?;
+
+                    insert_events(conn, vec![event]).await?;
+
+                    diesel::update(pots.filter(id.eq(the_pot_id)))
+                        .set(owner_id.eq(new_owner_id))
+                        .returning(Pot::as_returning())
+                        .get_result::<Pot>(conn)
+                        .await
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(internal_error)
         }
 
         async fn get_pots_containing_user(
@@ -200,47 +969,129 @@ pub mod pot_service {
                 .map_err(internal_error)
         }
 
-        /// Gets a Vector of all Pots.
+        /// Gets a Vector of all Pots the user behind `user_uuid` owns or is a part of, together
+        /// with their users. Runs two queries, regardless of the number of pots.
         pub async fn get_pots(
             &self,
             user_uuid: Uuid,
         ) -> Result<Vec<(Pot, Vec<User>)>, ExpenseError> {
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
-            // getting the pot ids where the user behind user_uuid is only a part of (not the owner)
-            let pot_ids: Vec<i32> = pots_to_users
-                .filter(user_id.eq(user_uuid))
-                .select(pot_id)
-                .load(&mut conn)
-                .await
-                .map_err(internal_error)?;
-
-            // getting all pods where the user behind user_uuid is a part of OR the owner
+            // getting all pots where the user behind user_uuid is a part of OR the owner
             let loaded_pots = pots
-                .filter(owner_id.eq(user_uuid).or(id.eq_any(pot_ids)))
+                .filter(
+                    owner_id
+                        .eq(user_uuid)
+                        .or(id.eq_any(pots_to_users.filter(user_id.eq(user_uuid)).select(pot_id))),
+                )
+                .order(id.asc())
                 .select(Pot::as_select())
                 .load(&mut conn)
                 .await
                 .map_err(not_found_error)?;
 
-            let mut pots_with_users = vec![];
-
-            for pot in loaded_pots {
-                let loaded_users = users
-                    .left_join(pots_to_users.on(user_id.eq(db_user_id)))
-                    .filter(pot_id.eq(pot.id()))
-                    .select(User::as_select())
-                    .get_results(&mut conn)
-                    .await
-                    .map_err(internal_error)?;
+            let loaded_pot_ids: Vec<i32> = loaded_pots.iter().map(|pot| pot.id()).collect();
 
-                pots_with_users.push((pot, loaded_users))
+            // getting the users of all loaded pots at once
+            let loaded_users: Vec<(i32, User)> = users
+                .inner_join(pots_to_users.on(user_id.eq(db_user_id)))
+                .filter(pot_id.eq_any(&loaded_pot_ids))
+                .select((pot_id, User::as_select()))
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            Ok(Self::group_users_by_pot(loaded_pots, loaded_users))
+        }
+
+        /// Gets all pots with their users, regardless of who owns them. Only meant for admins.
+        pub async fn get_all_pots(&self) -> Result<Vec<(Pot, Vec<User>)>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let loaded_pots = pots
+                .order(id.asc())
+                .select(Pot::as_select())
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            let loaded_users: Vec<(i32, User)> = users
+                .inner_join(pots_to_users.on(user_id.eq(db_user_id)))
+                .select((pot_id, User::as_select()))
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            Ok(Self::group_users_by_pot(loaded_pots, loaded_users))
+        }
+
+        /// Makes the user with the given `new_owner_id` the owner of the pot with the given
+        /// `the_pot_id` and adds them to the pot, if they are not part of it yet. The previous
+        /// owner stays part of the pot as an admin. Only meant for admins of the application, so
+        /// the current owner is not checked.
+        pub async fn reassign_owner(
+            &self,
+            the_pot_id: i32,
+            new_owner_id: Uuid,
+        ) -> Result<(Pot, Vec<User>), ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let new_owner = users
+                .filter(db_user_id.eq(new_owner_id))
+                .select(User::as_select())
+                .first::<User>(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?
+                .ok_or_else(|| NotFound(format!("User {} could not be found", new_owner_id)))?;
+
+            if new_owner.is_guest() {
+                return Err(BadRequest(format!("Guest {} can't own pots", new_owner_id)));
+            }
+
+            pots
+                .filter(id.eq(the_pot_id))
+                .select(Pot::as_select())
+                .first::<Pot>(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?
+                .ok_or_else(|| NotFound(format!("Pot {} could not be found", the_pot_id)))?;
+
+            let pot = self.set_owner(the_pot_id, new_owner_id, None).await?;
+
+            let pot_users = users
+                .inner_join(pots_to_users.on(user_id.eq(db_user_id)))
+                .filter(pot_id.eq(the_pot_id))
+                .select(User::as_select())
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            Ok((pot, pot_users))
+        }
+
+        /// Groups the given users, each tagged with the id of a pot, by the given pots.
+        pub(crate) fn group_users_by_pot(
+            loaded_pots: Vec<Pot>,
+            loaded_users: Vec<(i32, User)>,
+        ) -> Vec<(Pot, Vec<User>)> {
+            let mut users_by_pot: HashMap<i32, Vec<User>> = HashMap::new();
+
+            for (the_pot_id, user) in loaded_users {
+                users_by_pot.entry(the_pot_id).or_default().push(user);
             }
 
-            Ok(pots_with_users)
+            loaded_pots
+                .into_iter()
+                .map(|pot| {
+                    let pot_users = users_by_pot.remove(&pot.id()).unwrap_or_default();
+                    (pot, pot_users)
+                })
+                .collect()
         }
 
-        /// Gets a Pot with the given id if it exists.
+        /// Gets a Pot with the given id if it exists and the requester owns it or is a part of it.
         pub async fn get_pot_by_id(
             &self,
             to_search: i32,
@@ -248,21 +1099,15 @@ pub mod pot_service {
         ) -> Result<Pot, ExpenseError> {
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
-            // getting the pot ids where the user behind user_uuid is only a part of (not the owner)
-            let pot_ids: Vec<i32> = pots_to_users
-                .filter(user_id.eq(requester_id))
-                .select(pot_id)
-                .load(&mut conn)
-                .await
-                .map_err(internal_error)?;
-
             pots.filter(
-                pots_id
-                    .eq(to_search)
-                    .and(owner_id.eq(requester_id).or(pots_id.eq_any(pot_ids))),
+                pots_id.eq(to_search).and(
+                    owner_id.eq(requester_id).or(pots_id.eq_any(
+                        pots_to_users.filter(user_id.eq(requester_id)).select(pot_id),
+                    )),
+                ),
             )
-                .select(Pot::as_select())
-                .get_result::<Pot>(&mut conn)
+            .select(Pot::as_select())
+            .get_result::<Pot>(&mut conn)
             .await
             .map_err(not_found_error)
         }
@@ -274,23 +1119,12 @@ pub mod pot_service {
             to_delete: i32,
             requester_id: Uuid,
         ) -> Result<bool, ExpenseError> {
-            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
-
             // check if the user is even allowed to try to delete the pot
-            let is_allowed_to_delete = pots
-                .filter(id.eq(to_delete).and(owner_id.eq(requester_id)))
-                .count()
-                .get_result::<i64>(&mut conn)
-                .await
-                .map_err(internal_error)?
-                == 1;
+            self.pot_role_service
+                .require_role(to_delete, requester_id, PotRole::Owner)
+                .await?;
 
-            if !is_allowed_to_delete {
-                return Err(Forbidden(format!(
-                    "The user does not own the pot with id {}",
-                    to_delete
-                )));
-            }
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
             let is_archived = pots
                 .filter(id.eq(to_delete))
@@ -325,7 +1159,7 @@ pub mod pot_service {
             }
 
             let deleted =
-                diesel::delete(pots.filter(id.eq(to_delete).and(owner_id.eq(requester_id))))
+                diesel::delete(pots.filter(id.eq(to_delete)))
                     .execute(&mut conn)
                     .await
                     .map_err(not_found_error)?;
@@ -333,9 +1167,13 @@ pub mod pot_service {
             Ok(deleted == 1)
         }
 
-        /// Mark this pot as archived.
+        /// Mark this pot as archived. Requires the requester to be at least an admin of the pot.
         pub async fn archive(&self, pot_id_to_archive: i32, requester_id: Uuid)
             -> Result<bool, ExpenseError> {
+            self.pot_role_service
+                .require_role(pot_id_to_archive, requester_id, PotRole::Admin)
+                .await?;
+
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
             let pot = pots
@@ -349,18 +1187,34 @@ pub mod pot_service {
                 return Err(Conflict(format!("Pot {} is already archived", pot_id_to_archive)));
             }
 
-            diesel::update(pots.filter(id.eq(pot_id_to_archive).and(owner_id.eq(requester_id))))
-                .set((archived.eq(true), archived_at.eq(Utc::now())))
-                .execute(&mut conn)
-                .await
-                .map_err(not_found_error)?;
+            let event =
+                NewPotEvent::new(pot_id_to_archive, Some(requester_id), PotEventKind::PotArchived);
+
+            conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                async move {
+                    diesel::update(pots.filter(id.eq(pot_id_to_archive)))
+                        .set((archived.eq(true), archived_at.eq(Utc::now())))
+                        .execute(conn)
+                        .await?;
+
+                    insert_events(conn, vec![event]).await
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(not_found_error)?;
 
             Ok(true)
         }
 
         /// Unarchives a pot. Meaning that the archived flag will be set to false and the archived_at field will be set to null/None.
+        /// Requires the requester to be at least an admin of the pot.
         pub async fn unarchive(&self, pot_id_to_unarchive: i32, requester_id: Uuid)
             -> Result<bool, ExpenseError> {
+            self.pot_role_service
+                .require_role(pot_id_to_unarchive, requester_id, PotRole::Admin)
+                .await?;
+
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
 
             let pot = pots
@@ -374,31 +1228,432 @@ pub mod pot_service {
                 return Err(Conflict(format!("Pot {} is not archived", pot_id_to_unarchive)));
             }
 
-            diesel::update(pots.filter(id.eq(pot_id_to_unarchive).and(owner_id.eq(requester_id))))
-                .set((archived.eq(false), archived_at.eq(None::<DateTime<Utc>>)))
-                .execute(&mut conn)
-                .await
-                .map_err(not_found_error)?;
+            let event = NewPotEvent::new(
+                pot_id_to_unarchive,
+                Some(requester_id),
+                PotEventKind::PotUnarchived,
+            );
+
+            conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                async move {
+                    diesel::update(pots.filter(id.eq(pot_id_to_unarchive)))
+                        .set((archived.eq(false), archived_at.eq(None::<DateTime<Utc>>)))
+                        .execute(conn)
+                        .await?;
+
+                    insert_events(conn, vec![event]).await
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(not_found_error)?;
 
             Ok(true)
         }
 
-        /// Used to mark all expenses of the given pot as paid.
+        /// Used to mark all expenses of the given pot as paid. Requires the requester to be at
+        /// least an admin of the pot.
         pub async fn pay_pot(&self, pot_id_to_pay: i32, requester_id: Uuid)
             -> Result<bool, ExpenseError> {
-            let expenses = self
+            self.pot_role_service
+                .require_role(pot_id_to_pay, requester_id, PotRole::Admin)
+                .await?;
+
+            let expense_ids: Vec<i32> = self
                 .expense_service
                 .get_expenses_by_pot_id(pot_id_to_pay, requester_id)
+                .await?
+                .iter()
+                .map(|(expense, _, _)| expense.id())
+                .collect();
+
+            let event = NewPotEvent::new(pot_id_to_pay, Some(requester_id), PotEventKind::PotPaid);
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                async move {
+                    // the pot admins are always allowed to mark all expenses as paid, so the
+                    // expenses are not checked one by one
+                    pay_expenses(conn, &expense_ids).await?;
+
+                    // all debts are cleared now, so the settlements recorded so far must not
+                    // count towards the balances anymore, but are kept as payment history
+                    clear_settlements(conn, pot_id_to_pay).await?;
+
+                    insert_events(conn, vec![event]).await
+                }
+                .scope_boxed()
+            })
+            .await
+            .map_err(internal_error)?;
+
+            Ok(true)
+        }
+
+        /// Computes the set of transfers needed to clear all unpaid splits of the given pot,
+        /// taking the settlements that have already been made into account. The plan is computed
+        /// per currency, as amounts in different currencies can't be offset against each other.
+        /// Additionally, a plan for all currencies converted into the default currency of the pot
+        /// is computed, using the rate of the day the expense was spent on or the settlement made.
+        pub async fn get_settlement_plan(&self, target_pot_id: i32, requester_id: Uuid)
+            -> Result<SettlementPlan, ExpenseError> {
+            // ensures that the requester is allowed to view the pot at all
+            let pot = self.get_pot_by_id(target_pot_id, requester_id).await?;
+
+            let joined_expenses = self
+                .expense_service
+                .get_expenses_by_pot_id(target_pot_id, requester_id)
                 .await?;
 
-            for joined_expense in expenses {
-                let expense_id = joined_expense.0.id();
-                // calling pay_expense_no_check to avoid checking, because the pot
-                // owner is always allowed to mark all expenses as paid
-                self.expense_service.pay_expense_no_check(expense_id).await?;
+            let pot_settlements = self
+                .settlement_service
+                .get_open_settlements_by_pot_id(target_pot_id)
+                .await?;
+
+            let pot_currency = self
+                .currency_service
+                .get_currency_by_id(pot.default_currency_id())
+                .await?;
+
+            let mut currency_ids = joined_expenses
+                .iter()
+                .map(|(expense, _, _)| expense.currency_id())
+                .chain(pot_settlements.iter().map(|settlement| settlement.currency_id()))
+                .collect::<Vec<i32>>();
+            currency_ids.push(pot_currency.id());
+
+            let rate_table = self
+                .currency_service
+                .get_exchange_rate_table(&currency_ids)
+                .await?;
+
+            // maps the currency id to the currency and its debts, a debt being the creditor
+            // (the owner of the expense) and the unpaid split of the debtor
+            let mut debts_by_currency: BTreeMap<i32, (Currency, Vec<(Uuid, Split)>)> =
+                BTreeMap::new();
+
+            // the same debts and payments converted into the default currency of the pot, None
+            // as soon as one of them can't be converted
+            let mut converted_debts: Option<Vec<(Uuid, Split)>> = Some(vec![]);
+            let mut converted_payments: Option<Vec<Settlement>> = Some(vec![]);
+
+            for (expense, splits, currency) in joined_expenses {
+                let day = expense.spent_at().date_naive();
+
+                let debts = &mut debts_by_currency
+                    .entry(currency.id())
+                    .or_insert_with(|| (currency.clone(), vec![]))
+                    .1;
+
+                for split in splits {
+                    if !split.is_paid() && split.user_id() != expense.owner_id() {
+                        converted_debts = converted_debts.and_then(|mut converted| {
+                            let amount =
+                                rate_table.convert(split.amount(), &currency, &pot_currency, day)?;
+
+                            converted.push((
+                                expense.owner_id(),
+                                Split::new(split.expense_id(), split.user_id(), amount, false),
+                            ));
+
+                            Some(converted)
+                        });
+
+                        debts.push((expense.owner_id(), split));
+                    }
+                }
             }
 
-            Ok(true)
+            let mut payments_by_currency: BTreeMap<i32, Vec<Settlement>> = BTreeMap::new();
+
+            for settlement in pot_settlements {
+                let currency_id = settlement.currency_id();
+
+                // settlements can be made in currencies that are not used by any expense
+                if let Entry::Vacant(entry) = debts_by_currency.entry(currency_id) {
+                    let currency = self.currency_service.get_currency_by_id(currency_id).await?;
+                    entry.insert((currency, vec![]));
+                }
+
+                let currency = &debts_by_currency[&currency_id].0;
+
+                converted_payments = converted_payments.and_then(|mut converted| {
+                    let amount = rate_table.convert(
+                        settlement.open_amount(),
+                        currency,
+                        &pot_currency,
+                        settlement.created_at().date_naive(),
+                    )?;
+
+                    converted.push(Settlement::new(
+                        settlement.id(),
+                        settlement.pot_id(),
+                        settlement.payer_id(),
+                        settlement.receiver_id(),
+                        amount,
+                        pot_currency.id(),
+                        settlement.created_at(),
+                    ));
+
+                    Some(converted)
+                });
+
+                payments_by_currency
+                    .entry(currency_id)
+                    .or_default()
+                    .push(settlement);
+            }
+
+            let mut currency_settlements = vec![];
+
+            for (currency_id, (currency, debts)) in debts_by_currency {
+                let payments = payments_by_currency.remove(&currency_id).unwrap_or_default();
+
+                if debts.is_empty() && payments.is_empty() {
+                    continue;
+                }
+
+                let (transfers, netted_splits) = Self::settle(debts, &payments);
+
+                currency_settlements.push(CurrencySettlement {
+                    currency,
+                    transfers,
+                    netted_splits,
+                });
+            }
+
+            let converted = match (converted_debts, converted_payments) {
+                (Some(debts), Some(payments)) => {
+                    let (transfers, netted_splits) = Self::settle(debts, &payments);
+
+                    Some(CurrencySettlement {
+                        currency: pot_currency,
+                        transfers,
+                        netted_splits,
+                    })
+                }
+                _ => None,
+            };
+
+            Ok(SettlementPlan {
+                currencies: currency_settlements,
+                converted,
+            })
+        }
+
+        /// Gets the summary of the given pot per currency, meaning the total spent, what each member
+        /// paid for and consumed, their open balances and the totals per month. All values are
+        /// aggregated by the database.
+        pub async fn get_pot_summary(&self, target_pot_id: i32, requester_id: Uuid)
+            -> Result<Vec<CurrencySummary>, ExpenseError> {
+            // ensures that the requester is allowed to view the pot at all
+            self.get_pot_by_id(target_pot_id, requester_id).await?;
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let member_summaries = diesel::sql_query(MEMBER_SUMMARY_QUERY)
+                .bind::<sql_types::Int4, _>(target_pot_id)
+                .load::<MemberSummary>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            let month_summaries = diesel::sql_query(MONTH_SUMMARY_QUERY)
+                .bind::<sql_types::Int4, _>(target_pot_id)
+                .load::<MonthSummary>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            // settlements can be made in currencies that are not used by any expense
+            let mut summaries: BTreeMap<i32, CurrencySummary> = BTreeMap::new();
+
+            for currency in self.currency_service.get_currencies().await? {
+                summaries.insert(
+                    currency.id(),
+                    CurrencySummary {
+                        currency,
+                        total: 0,
+                        expense_count: 0,
+                        members: vec![],
+                        months: vec![],
+                    },
+                );
+            }
+
+            for member_summary in member_summaries {
+                if let Some(summary) = summaries.get_mut(&member_summary.currency_id) {
+                    summary.members.push(member_summary);
+                }
+            }
+
+            for month_summary in month_summaries {
+                if let Some(summary) = summaries.get_mut(&month_summary.currency_id) {
+                    summary.total += month_summary.total;
+                    summary.expense_count += month_summary.expense_count;
+                    summary.months.push(month_summary);
+                }
+            }
+
+            Ok(summaries
+                .into_values()
+                .filter(|summary| !summary.members.is_empty())
+                .collect())
+        }
+
+        /// Gets all expenses of the given pot together with the names of the users involved, to
+        /// export them. Archived pots can be exported as well.
+        pub async fn export_pot(&self, target_pot_id: i32, requester_id: Uuid)
+            -> Result<PotExport, ExpenseError> {
+            // ensures that the requester is allowed to view the pot at all
+            let pot = self.get_pot_by_id(target_pot_id, requester_id).await?;
+
+            let mut pot_expenses = self
+                .expense_service
+                .get_expenses_by_pot_id(target_pot_id, requester_id)
+                .await?;
+
+            pot_expenses.sort_by_key(|(expense, _, _)| (expense.spent_at(), expense.id()));
+
+            self.to_export(pot, pot_expenses, None).await
+        }
+
+        /// Exports a single batch of up to `EXPORT_BATCH_SIZE` expenses of the given pot, as in
+        /// `export_pot`, starting after the given position of the previous batch. This way
+        /// large pots can be exported without loading all of their expenses at once.
+        pub async fn export_pot_batch(
+            &self,
+            target_pot_id: i32,
+            requester_id: Uuid,
+            after: Option<(DateTime<Utc>, i32)>,
+        ) -> Result<PotExport, ExpenseError> {
+            // ensures that the requester is allowed to view the pot at all
+            let pot = self.get_pot_by_id(target_pot_id, requester_id).await?;
+
+            let batch = self
+                .expense_service
+                .get_expense_batch(target_pot_id, after, EXPORT_BATCH_SIZE)
+                .await?;
+
+            let next_position = match batch.last() {
+                Some((expense, _, _)) if batch.len() as i64 == EXPORT_BATCH_SIZE => {
+                    Some((expense.spent_at(), expense.id()))
+                }
+                _ => None,
+            };
+
+            self.to_export(pot, batch, next_position).await
+        }
+
+        /// Creates the export of the given expenses, loading the users they refer to.
+        async fn to_export(
+            &self,
+            pot: Pot,
+            pot_expenses: Vec<JoinedExpense>,
+            next_position: Option<(DateTime<Utc>, i32)>,
+        ) -> Result<PotExport, ExpenseError> {
+            let user_ids = pot_expenses
+                .iter()
+                .flat_map(|(expense, splits, _)| {
+                    std::iter::once(expense.owner_id()).chain(splits.iter().map(Split::user_id))
+                })
+                .collect::<HashSet<Uuid>>();
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let loaded_users = users
+                .filter(db_user_id.eq_any(user_ids))
+                .select(User::as_select())
+                .load::<User>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            Ok(PotExport {
+                pot,
+                expenses: pot_expenses,
+                users: loaded_users
+                    .into_iter()
+                    .map(|user| (user.id(), user))
+                    .collect(),
+                next_position,
+            })
+        }
+
+        /// Simplifies the given debts, each being the creditor and the unpaid split of the debtor,
+        /// into a list of transfers. The given payments are counted against the debts.
+        /// The largest debtor always pays the largest creditor, which results in at most one
+        /// transfer less than there are users with an open balance.
+        /// Splits are assigned to the transfer from their debtor to their creditor. Splits without
+        /// such a transfer, because they were netted out or simplified into other transfers, are
+        /// returned separately.
+        pub(crate) fn settle(
+            debts: Vec<(Uuid, Split)>,
+            payments: &[Settlement],
+        ) -> (Vec<SettlementTransfer>, Vec<Split>) {
+            let mut balances: BTreeMap<Uuid, i64> = BTreeMap::new();
+
+            for (creditor, split) in &debts {
+                *balances.entry(*creditor).or_insert(0) += split.amount();
+                *balances.entry(split.user_id()).or_insert(0) -= split.amount();
+            }
+
+            for payment in payments {
+                *balances.entry(payment.payer_id()).or_insert(0) += payment.open_amount();
+                *balances.entry(payment.receiver_id()).or_insert(0) -= payment.open_amount();
+            }
+
+            let mut debtors: Vec<(Uuid, i64)> = balances
+                .iter()
+                .filter(|(_, balance)| **balance < 0)
+                .map(|(user, balance)| (*user, -balance))
+                .collect();
+
+            let mut creditors: Vec<(Uuid, i64)> = balances
+                .iter()
+                .filter(|(_, balance)| **balance > 0)
+                .map(|(user, balance)| (*user, *balance))
+                .collect();
+
+            let mut transfers: Vec<SettlementTransfer> = vec![];
+
+            while !debtors.is_empty() && !creditors.is_empty() {
+                // sorting by amount first and by user second keeps the plan deterministic
+                debtors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+                creditors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+                let amount = debtors[0].1.min(creditors[0].1);
+
+                transfers.push(SettlementTransfer {
+                    from: debtors[0].0,
+                    to: creditors[0].0,
+                    amount,
+                    splits: vec![],
+                });
+
+                debtors[0].1 -= amount;
+                creditors[0].1 -= amount;
+
+                debtors.retain(|(_, open)| *open > 0);
+                creditors.retain(|(_, open)| *open > 0);
+            }
+
+            let mut netted_splits = vec![];
+
+            for (creditor, split) in debts {
+                let debtor = split.user_id();
+
+                // a split only belongs to a transfer from its debtor to its creditor, debts that
+                // were simplified into other transfers have no transfer of their own
+                let transfer_index = transfers
+                    .iter()
+                    .position(|t| t.from == debtor && t.to == creditor);
+
+                match transfer_index {
+                    Some(index) => transfers[index].splits.push(split),
+                    None => netted_splits.push(split),
+                }
+            }
+
+            (transfers, netted_splits)
         }
     }
 
@@ -408,6 +1663,430 @@ pub mod pot_service {
             db_pool: pool.clone(),
             currency_service: currency_service::new_service(pool.clone()),
             expense_service: expense_service::new_service(pool.clone()),
+            settlement_service: settlement_service::new_service(pool.clone()),
+            pot_role_service: pot_role_service::new_service(pool.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::expense_service::expense_service;
+    use crate::expense_service::expense_service::{Pagination, SplitMode};
+    use crate::pot_event_service::pot_event_service;
+    use crate::pot_service::pot_service;
+    use crate::pot_service::pot_service::PotService;
+    use crate::settlement_service::settlement_service;
+    use crate::test_support::test_support::TestFixture;
+    use crate::ExpenseError;
+    use diesel::internal::derives::multiconnection::chrono::Utc;
+    use expense_tracker_db::expenses::expenses::NewExpense;
+    use expense_tracker_db::pot_events::pot_events::PotEventKind;
+    use expense_tracker_db::pots::pots::PotRole;
+    use expense_tracker_db::settlements::settlements::{NewSettlement, Settlement};
+    use expense_tracker_db::splits::splits::Split;
+    use expense_tracker_db::users::users::User;
+    use uuid::{uuid, Uuid};
+
+    const USER_ONE: Uuid = uuid!("e6be621a-ec2d-48f3-8027-0d34cf5cbe40");
+    const USER_TWO: Uuid = uuid!("729c270f-74d1-436f-aa46-4fe6a3dcb460");
+    const USER_THREE: Uuid = uuid!("7ec9119b-10a2-48b8-be5f-d0f8ba9aad8d");
+    const USER_FOUR: Uuid = uuid!("01913042-053a-4cb2-846d-4b58153185b8");
+
+    #[test]
+    fn test_settle_single_debt() {
+        let debts = vec![(USER_ONE, Split::new(1, USER_TWO, 4200, false))];
+
+        let (transfers, netted_splits) = PotService::settle(debts, &[]);
+
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].from(), USER_TWO);
+        assert_eq!(transfers[0].to(), USER_ONE);
+        assert_eq!(transfers[0].amount(), 4200);
+        assert_eq!(transfers[0].splits().len(), 1);
+        assert!(netted_splits.is_empty());
+    }
+
+    /// USER_TWO owes USER_ONE and USER_THREE owes USER_TWO the same amount, so USER_THREE
+    /// can pay USER_ONE directly.
+    #[test]
+    fn test_settle_chain_is_simplified() {
+        let debts = vec![
+            (USER_ONE, Split::new(1, USER_TWO, 1000, false)),
+            (USER_TWO, Split::new(2, USER_THREE, 1000, false)),
+        ];
+
+        let (transfers, netted_splits) = PotService::settle(debts, &[]);
+
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].from(), USER_THREE);
+        assert_eq!(transfers[0].to(), USER_ONE);
+        assert_eq!(transfers[0].amount(), 1000);
+        // neither USER_THREE nor USER_ONE owes the other directly
+        assert!(transfers[0].splits().is_empty());
+        assert_eq!(netted_splits.len(), 2);
+    }
+
+    #[test]
+    fn test_settle_mutual_debts_cancel_out() {
+        let debts = vec![
+            (USER_ONE, Split::new(1, USER_TWO, 1000, false)),
+            (USER_TWO, Split::new(2, USER_ONE, 1000, false)),
+        ];
+
+        let (transfers, netted_splits) = PotService::settle(debts, &[]);
+
+        assert!(transfers.is_empty());
+        assert_eq!(netted_splits.len(), 2);
+    }
+
+    /// USER_TWO owes USER_ONE 73.40 for two expenses and already paid 50.00.
+    #[test]
+    fn test_settle_partial_payment() {
+        let debts = vec![
+            (USER_ONE, Split::new(1, USER_TWO, 3000, false)),
+            (USER_ONE, Split::new(2, USER_TWO, 4340, false)),
+        ];
+        let payments = vec![Settlement::new(1, 1, USER_TWO, USER_ONE, 5000, 1, Utc::now())];
+
+        let (transfers, netted_splits) = PotService::settle(debts, &payments);
+
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].from(), USER_TWO);
+        assert_eq!(transfers[0].to(), USER_ONE);
+        assert_eq!(transfers[0].amount(), 2340);
+        assert_eq!(transfers[0].splits().len(), 2);
+        assert!(netted_splits.is_empty());
+    }
+
+    #[test]
+    fn test_settle_fully_paid() {
+        let debts = vec![(USER_ONE, Split::new(1, USER_TWO, 3000, false))];
+        let payments = vec![Settlement::new(1, 1, USER_TWO, USER_ONE, 3000, 1, Utc::now())];
+
+        let (transfers, netted_splits) = PotService::settle(debts, &payments);
+
+        assert!(transfers.is_empty());
+        assert_eq!(netted_splits.len(), 1);
+    }
+
+    #[test]
+    fn test_settle_multiple_debtors_and_creditors() {
+        let debts = vec![
+            (USER_ONE, Split::new(1, USER_TWO, 3000, false)),
+            (USER_ONE, Split::new(1, USER_THREE, 3000, false)),
+            (USER_FOUR, Split::new(2, USER_TWO, 1500, false)),
+            (USER_THREE, Split::new(3, USER_FOUR, 500, false)),
+        ];
+
+        let (transfers, netted_splits) = PotService::settle(debts, &[]);
+
+        // four users with an open balance need at most three transfers
+        assert!(transfers.len() <= 3);
+
+        let received_by_one: i64 = transfers
+            .iter()
+            .filter(|t| t.to() == USER_ONE)
+            .map(|t| t.amount())
+            .sum();
+        let paid_by_two: i64 = transfers
+            .iter()
+            .filter(|t| t.from() == USER_TWO)
+            .map(|t| t.amount())
+            .sum();
+
+        assert_eq!(received_by_one, 6000);
+        assert_eq!(paid_by_two, 4500);
+
+        // splits are only listed with a transfer of the debtor to the creditor they owe
+        let owed = [
+            (1, USER_TWO, USER_ONE),
+            (1, USER_THREE, USER_ONE),
+            (2, USER_TWO, USER_FOUR),
+            (3, USER_FOUR, USER_THREE),
+        ];
+        for transfer in &transfers {
+            for split in transfer.splits() {
+                assert_eq!(split.user_id(), transfer.from());
+                assert!(owed.contains(&(
+                    split.expense_id(),
+                    transfer.from(),
+                    transfer.to()
+                )));
+            }
+        }
+
+        let assigned_splits: usize = transfers.iter().map(|t| t.splits().len()).sum();
+        assert_eq!(assigned_splits + netted_splits.len(), 4);
+    }
+
+    #[test]
+    fn test_validate_settings() {
+        assert!(pot_service::validate_settings(Some("Holidays"), Some("Two weeks")).is_ok());
+        assert!(pot_service::validate_settings(None, None).is_ok());
+        assert!(pot_service::validate_settings(Some(&"ä".repeat(24)), None).is_ok());
+
+        for name in ["", "  ", &"a".repeat(25)] {
+            let result = pot_service::validate_settings(Some(name), None);
+            assert!(matches!(result, Err(ExpenseError::BadRequest(_))));
+        }
+
+        let result = pot_service::validate_settings(None, Some(&"a".repeat(501)));
+        assert!(matches!(result, Err(ExpenseError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database, see expense_tracker_db::test_support"]
+    async fn test_leave_pot() {
+        let fixture = TestFixture::new(2).await;
+        let service = pot_service::new_service(fixture.pool());
+        let expense_service = expense_service::new_service(fixture.pool());
+        let event_service = pot_event_service::new_service(fixture.pool());
+        let currency = fixture.currency();
+
+        let owner = fixture.user(0);
+        let member = fixture.user(1);
+
+        let mut pot_ids = vec![];
+
+        for name in ["First", "Second"] {
+            pot_ids.push(fixture.create_pot(name, owner, &[member]).await.id());
+        }
+
+        // the member owes the owner half of the expense
+        expense_service
+            .create_expense(
+                NewExpense::new(owner, pot_ids[0], "Dinner".to_string(), currency.id(), None),
+                Some(20.0),
+                SplitMode::Equal(vec![owner, member]),
+            )
+            .await
+            .unwrap();
+
+        let result = service.leave_pot(pot_ids[0], member).await;
+        assert!(matches!(result, Err(ExpenseError::Conflict(_))));
+
+        let result = service.leave_pot(pot_ids[1], owner).await;
+        assert!(matches!(result, Err(ExpenseError::Conflict(_))));
+
+        // only removes the member from the given pot
+        service.leave_pot(pot_ids[1], member).await.unwrap();
+
+        let member_pots: Vec<i32> = service
+            .get_pots(member)
+            .await
+            .unwrap()
+            .iter()
+            .map(|(pot, _)| pot.id())
+            .collect();
+        assert_eq!(member_pots, vec![pot_ids[0]]);
+
+        let result = service.leave_pot(pot_ids[1], member).await;
+        assert!(matches!(result, Err(ExpenseError::NotFound(_))));
+
+        // the event is recorded together with the removal, so failed attempts leave none
+        for (pot_id, left) in [(pot_ids[0], 0), (pot_ids[1], 1)] {
+            let page = event_service
+                .get_events(pot_id, owner, &Pagination::new(1, 50))
+                .await
+                .unwrap();
+            let left_count = page
+                .events()
+                .iter()
+                .filter(|(event, _)| event.kind() == PotEventKind::MemberLeft)
+                .count();
+            assert_eq!(left_count, left);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database, see expense_tracker_db::test_support"]
+    async fn test_roles_and_ownership_transfer() {
+        let fixture = TestFixture::new(3).await;
+        let service = pot_service::new_service(fixture.pool());
+        let expense_service = expense_service::new_service(fixture.pool());
+        let currency = fixture.currency();
+
+        let owner = fixture.user(0);
+        let admin = fixture.user(1);
+        let viewer = fixture.user(2);
+
+        let pot = fixture.create_pot("Roles", owner, &[admin, viewer]).await;
+
+        service.set_member_role(pot.id(), owner, admin, PotRole::Admin).await.unwrap();
+        service.set_member_role(pot.id(), admin, viewer, PotRole::Viewer).await.unwrap();
+
+        // admins can't hand out their own role or change the owner
+        let result = service.set_member_role(pot.id(), admin, viewer, PotRole::Admin).await;
+        assert!(matches!(result, Err(ExpenseError::Forbidden(_))));
+
+        let result = service.set_member_role(pot.id(), admin, owner, PotRole::Member).await;
+        assert!(matches!(result, Err(ExpenseError::Forbidden(_))));
+
+        // viewers can't add expenses
+        let result = expense_service
+            .create_expense(
+                NewExpense::new(viewer, pot.id(), "Snacks".to_string(), currency.id(), None),
+                Some(5.0),
+                SplitMode::Equal(vec![viewer, owner]),
+            )
+            .await;
+        assert!(matches!(result, Err(ExpenseError::Forbidden(_))));
+
+        let result = service.try_delete_pot(pot.id(), admin).await;
+        assert!(matches!(result, Err(ExpenseError::Forbidden(_))));
+
+        let result = service.transfer_ownership(pot.id(), admin, admin).await;
+        assert!(matches!(result, Err(ExpenseError::Forbidden(_))));
+
+        let members = service.transfer_ownership(pot.id(), owner, admin).await.unwrap();
+        let roles: Vec<(Uuid, PotRole)> = members
+            .iter()
+            .map(|(user, role)| (user.id(), *role))
+            .collect();
+        assert_eq!(
+            roles,
+            vec![(admin, PotRole::Owner), (owner, PotRole::Admin), (viewer, PotRole::Viewer)]
+        );
+
+        assert_eq!(service.get_pot_by_id(pot.id(), owner).await.unwrap().owner_id(), admin);
+
+        // the previous owner is an admin now and can't delete the pot anymore
+        let result = service.try_delete_pot(pot.id(), owner).await;
+        assert!(matches!(result, Err(ExpenseError::Forbidden(_))));
+
+        assert!(service.try_delete_pot(pot.id(), admin).await.unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database, see expense_tracker_db::test_support"]
+    async fn test_pot_activity() {
+        let fixture = TestFixture::new(3).await;
+        let service = pot_service::new_service(fixture.pool());
+        let expense_service = expense_service::new_service(fixture.pool());
+        let event_service = pot_event_service::new_service(fixture.pool());
+        let currency = fixture.currency();
+
+        let owner = fixture.user(0);
+        let member = fixture.user(1);
+        let stranger = fixture.user(2);
+
+        let pot = fixture.create_pot("Activity", owner, &[member]).await;
+
+        // only users of the pot can be part of its splits, failed attempts leave no event
+        let result = expense_service
+            .create_expense(
+                NewExpense::new(member, pot.id(), "Pizza".to_string(), currency.id(), None),
+                Some(20.0),
+                SplitMode::Equal(vec![member, stranger]),
+            )
+            .await;
+        assert!(matches!(result, Err(ExpenseError::BadRequest(_))));
+
+        let (expense, _, _) = expense_service
+            .create_expense(
+                NewExpense::new(member, pot.id(), "Pizza".to_string(), currency.id(), None),
+                Some(20.0),
+                SplitMode::Equal(vec![owner, member]),
+            )
+            .await
+            .unwrap();
+
+        expense_service
+            .delete_expense(expense.id(), member)
+            .await
+            .unwrap();
+        service.archive(pot.id(), owner).await.unwrap();
+
+        let page = event_service
+            .get_events(pot.id(), member, &Pagination::new(1, 50))
+            .await
+            .unwrap();
+        assert_eq!(page.total_count(), 5);
+
+        let events: Vec<(PotEventKind, Option<Uuid>)> = page
+            .events()
+            .iter()
+            .map(|(event, actor)| (event.kind(), actor.as_ref().map(User::id)))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (PotEventKind::PotArchived, Some(owner)),
+                (PotEventKind::ExpenseDeleted, Some(member)),
+                (PotEventKind::ExpenseAdded, Some(member)),
+                (PotEventKind::MemberAdded, Some(owner)),
+                (PotEventKind::PotCreated, Some(owner)),
+            ]
+        );
+
+        let (added, _) = &page.events()[2];
+        assert_eq!(added.expense_id(), Some(expense.id()));
+        assert_eq!(added.subject_id(), Some(member));
+
+        let page = event_service
+            .get_events(pot.id(), owner, &Pagination::new(2, 2))
+            .await
+            .unwrap();
+        assert_eq!(page.events().len(), 2);
+        assert_eq!(page.events()[0].0.kind(), PotEventKind::ExpenseAdded);
+
+        let result = event_service
+            .get_events(pot.id(), stranger, &Pagination::new(1, 50))
+            .await;
+        assert!(matches!(result, Err(ExpenseError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database, see expense_tracker_db::test_support"]
+    async fn test_pay_pot_keeps_settlements() {
+        let fixture = TestFixture::new(2).await;
+        let service = pot_service::new_service(fixture.pool());
+        let expense_service = expense_service::new_service(fixture.pool());
+        let settlement_service = settlement_service::new_service(fixture.pool());
+        let currency = fixture.currency();
+
+        let owner = fixture.user(0);
+        let member = fixture.user(1);
+
+        let pot = fixture.create_pot("Paid", owner, &[member]).await;
+
+        // the member owes the owner half of the expense and pays back a part of it
+        expense_service
+            .create_expense(
+                NewExpense::new(owner, pot.id(), "Dinner".to_string(), currency.id(), None),
+                Some(20.0),
+                SplitMode::Equal(vec![owner, member]),
+            )
+            .await
+            .unwrap();
+
+        settlement_service
+            .create_settlement(
+                NewSettlement::new(pot.id(), member, owner, 400, currency.id()),
+                member,
+            )
+            .await
+            .unwrap();
+
+        let balance = expense_service.get_pot_net_balance(pot.id(), owner).await.unwrap();
+        assert_eq!(balance.converted(), Some(600));
+
+        service.pay_pot(pot.id(), owner).await.unwrap();
+
+        // the settlement is part of the payment, so it no longer counts towards the balances
+        for user in [owner, member] {
+            let balance = expense_service.get_pot_net_balance(pot.id(), user).await.unwrap();
+            assert!(balance.balances().is_empty());
+            assert_eq!(balance.converted(), Some(0));
         }
+
+        let plan = service.get_settlement_plan(pot.id(), owner).await.unwrap();
+        assert!(plan.currencies().is_empty());
+
+        // but it is kept as payment history
+        let settlements = settlement_service.get_settlements(pot.id(), owner).await.unwrap();
+        assert_eq!(settlements.len(), 1);
+        assert!(settlements[0].cleared_at().is_some());
     }
 }
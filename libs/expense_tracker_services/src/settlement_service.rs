@@ -0,0 +1,219 @@
+pub mod settlement_service {
+    use crate::currency_service::currency_service;
+    use crate::currency_service::currency_service::CurrencyService;
+    use crate::money::money::format_minor_units;
+    use crate::pot_event_service::pot_event_service::insert_events;
+    use crate::pot_role_service::pot_role_service;
+    use crate::pot_role_service::pot_role_service::PotRoleService;
+    use crate::ExpenseError::{BadRequest, Forbidden, Locked, NotFound};
+    use crate::{internal_error, not_found_error, ExpenseError};
+    use diesel::internal::derives::multiconnection::chrono::Utc;
+    use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, SelectableHelper};
+    use diesel::result::Error;
+    use diesel_async::scoped_futures::ScopedFutureExt;
+    use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+    use expense_tracker_db::currencies::currencies::Currency;
+    use expense_tracker_db::pot_events::pot_events::{NewPotEvent, PotEventKind};
+    use expense_tracker_db::pots::pots::PotRole;
+    use expense_tracker_db::schema::pots::dsl::pots;
+    use expense_tracker_db::schema::pots::{archived, id as pots_id};
+    use expense_tracker_db::schema::settlements::dsl::settlements;
+    use expense_tracker_db::schema::settlements::{
+        amount as settlement_amount, cleared_amount as settlement_cleared_amount,
+        cleared_at as settlement_cleared_at, id as settlement_id, pot_id as settlement_pot_id,
+    };
+    use expense_tracker_db::settlements::settlements::{NewSettlement, Settlement};
+    use expense_tracker_db::setup::DbPool;
+    use uuid::Uuid;
+
+    /// A service offering interfaces related to Settlements, meaning payments between the
+    /// users of a pot.
+    #[derive(Clone)]
+    pub struct SettlementService {
+        db_pool: DbPool,
+        currency_service: CurrencyService,
+        pot_role_service: PotRoleService,
+    }
+
+    impl SettlementService {
+        /// Records the given settlement. The requester must either be the payer or the receiver
+        /// and at least a member of the pot, while the other one only has to be part of it.
+        pub async fn create_settlement(
+            &self,
+            new_settlement: NewSettlement,
+            requester_id: Uuid,
+        ) -> Result<(Settlement, Currency), ExpenseError> {
+            let target_pot_id = new_settlement.pot_id();
+            let payer_id = new_settlement.payer_id();
+            let receiver_id = new_settlement.receiver_id();
+
+            if requester_id != payer_id && requester_id != receiver_id {
+                return Err(Forbidden(
+                    "Only the payer or the receiver can record a settlement".to_string(),
+                ));
+            }
+
+            if payer_id == receiver_id {
+                return Err(BadRequest(
+                    "Payer and receiver of a settlement must differ".to_string(),
+                ));
+            }
+
+            if new_settlement.amount() <= 0 {
+                return Err(BadRequest(
+                    "The amount of a settlement must be positive".to_string(),
+                ));
+            }
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let is_archived = pots
+                .filter(pots_id.eq(target_pot_id))
+                .select(archived)
+                .first::<bool>(&mut conn)
+                .await
+                .map_err(not_found_error)?;
+
+            // the requester is checked first, so outsiders can't probe who is part of the pot
+            self.pot_role_service
+                .require_role(target_pot_id, requester_id, PotRole::Member)
+                .await?;
+
+            // the other side of the settlement, which is also the subject of the event
+            let other_id = if requester_id == payer_id { receiver_id } else { payer_id };
+
+            if self.pot_role_service.get_role(target_pot_id, other_id).await?.is_none() {
+                return Err(NotFound(format!(
+                    "User {} is not part of pot {}",
+                    other_id, target_pot_id
+                )));
+            }
+
+            if is_archived {
+                return Err(Locked(format!("Pot {} is archived", target_pot_id)));
+            }
+
+            let currency = self
+                .currency_service
+                .get_currency_by_id(new_settlement.currency_id())
+                .await?;
+
+            let event = NewPotEvent::new(
+                target_pot_id,
+                Some(requester_id),
+                PotEventKind::SettlementRecorded,
+            )
+            .with_subject(other_id)
+            .with_details(format!(
+                "{} {} from {} to {}",
+                format_minor_units(new_settlement.amount(), currency.minor_units()),
+                currency.symbol(),
+                payer_id,
+                receiver_id
+            ));
+
+            let settlement = conn
+                .transaction::<_, Error, _>(|conn| {
+                    async move {
+                        let settlement = diesel::insert_into(settlements)
+                            .values(new_settlement)
+                            .returning(Settlement::as_returning())
+                            .get_result::<Settlement>(conn)
+                            .await?;
+
+                        insert_events(conn, vec![event]).await?;
+
+                        Ok(settlement)
+                    }
+                    .scope_boxed()
+                })
+                .await
+                .map_err(internal_error)?;
+
+            Ok((settlement, currency))
+        }
+
+        /// Gets all settlements of the given pot, including cleared ones, if the requester is part
+        /// of it.
+        pub async fn get_settlements(
+            &self,
+            target_pot_id: i32,
+            requester_id: Uuid,
+        ) -> Result<Vec<Settlement>, ExpenseError> {
+            self.pot_role_service
+                .require_role(target_pot_id, requester_id, PotRole::Viewer)
+                .await?;
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            settlements
+                .filter(settlement_pot_id.eq(target_pot_id))
+                .order(settlement_id.asc())
+                .select(Settlement::as_select())
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)
+        }
+
+        /// Gets the settlements of the given pot that have not been cleared yet, without checking
+        /// the requester. Only those count towards the balances of the pot.
+        pub(crate) async fn get_open_settlements_by_pot_id(
+            &self,
+            target_pot_id: i32,
+        ) -> Result<Vec<Settlement>, ExpenseError> {
+            self.get_open_settlements_by_pot_ids(&[target_pot_id]).await
+        }
+
+        /// Gets the settlements of the given pots that have not been cleared yet at once, without
+        /// checking the requester.
+        pub(crate) async fn get_open_settlements_by_pot_ids(
+            &self,
+            target_pot_ids: &[i32],
+        ) -> Result<Vec<Settlement>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            settlements
+                .filter(
+                    settlement_pot_id
+                        .eq_any(target_pot_ids)
+                        .and(settlement_cleared_at.is_null()),
+                )
+                .order(settlement_id.asc())
+                .select(Settlement::as_select())
+                .load(&mut conn)
+                .await
+                .map_err(internal_error)
+        }
+    }
+
+    /// Clears all open settlements of the given pot on the given connection. Used once all
+    /// expenses of the pot have been marked as paid, as the settlements are part of that payment.
+    /// Cleared settlements are kept as payment history.
+    pub(crate) async fn clear_settlements(
+        conn: &mut AsyncPgConnection,
+        target_pot_id: i32,
+    ) -> Result<usize, Error> {
+        diesel::update(
+            settlements.filter(
+                settlement_pot_id
+                    .eq(target_pot_id)
+                    .and(settlement_cleared_at.is_null()),
+            ),
+        )
+        .set((
+            settlement_cleared_at.eq(Utc::now()),
+            settlement_cleared_amount.eq(settlement_amount),
+        ))
+        .execute(conn)
+        .await
+    }
+
+    /// Creates a new SettlementService with the given DbConnectionPool.
+    pub fn new_service(pool: DbPool) -> SettlementService {
+        SettlementService {
+            db_pool: pool.clone(),
+            currency_service: currency_service::new_service(pool.clone()),
+            pot_role_service: pot_role_service::new_service(pool),
+        }
+    }
+}
@@ -1,7 +1,8 @@
 pub mod pot_template_service {
+    use std::collections::HashMap;
     use std::sync::Arc;
     use tokio::sync::Mutex;
-    use diesel::{SelectableHelper, ExpressionMethods, QueryDsl, BoolExpressionMethods};
+    use diesel::{SelectableHelper, ExpressionMethods, OptionalExtension, QueryDsl, BoolExpressionMethods};
     use diesel::internal::derives::multiconnection::chrono;
     use diesel::internal::derives::multiconnection::chrono::Datelike;
     use diesel::result::Error;
@@ -23,7 +24,7 @@ pub mod pot_template_service {
     use crate::cron_manager_service::cron_manager_service::CronManagerService;
     use crate::currency_service::currency_service;
     use crate::currency_service::currency_service::CurrencyService;
-    use crate::ExpenseError::Forbidden;
+    use crate::ExpenseError::{Forbidden, NotFound};
     use crate::pot_service::pot_service;
     use crate::pot_service::pot_service::PotService;
     use crate::user_service::user_service;
@@ -74,7 +75,7 @@ pub mod pot_template_service {
                 .transaction::<_, Error, _>(|conn| {
                     async move {
                         // cloning variables for later use and before they are moved
-                        let currency_id_clone = new_template.default_currency_id().clone();
+                        let currency_id_clone = new_template.default_currency_id();
                         let new_template_clone = new_template.clone();
 
                         let template_pot = diesel::insert_into(pot_templates)
@@ -304,6 +305,89 @@ pub mod pot_template_service {
             Ok((template_with_currency.0, template_with_currency.1, loaded_users))
         }
 
+        /// Gets all templates with their currency and users, regardless of who owns them.
+        /// Only meant for admins.
+        pub async fn get_all_templates(&self)
+            -> Result<Vec<(PotTemplate, Currency, Vec<User>)>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let templates_with_currency = pot_templates
+                .inner_join(currencies)
+                .order(id.asc())
+                .select((PotTemplate::as_select(), Currency::as_select()))
+                .load::<(PotTemplate, Currency)>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            // loading the users of all templates at once instead of once per template
+            let loaded_users = pot_template_users
+                .inner_join(users)
+                .select((pot_template_id, User::as_select()))
+                .load::<(i32, User)>(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            let mut users_by_template: HashMap<i32, Vec<User>> = HashMap::new();
+
+            for (template_id, user) in loaded_users {
+                users_by_template.entry(template_id).or_default().push(user);
+            }
+
+            let result = templates_with_currency
+                .into_iter()
+                .map(|(template, currency)| {
+                    let template_users = users_by_template.remove(&template.id()).unwrap_or_default();
+                    (template, currency, template_users)
+                })
+                .collect();
+
+            Ok(result)
+        }
+
+        /// Makes the user with the given id the owner of the given template and adds them to the
+        /// users of the template, if they are not part of it yet. Only meant for admins, so the
+        /// current owner is not checked.
+        pub async fn reassign_owner(&self, target_pot_template_id: i32, new_owner_id: Uuid)
+            -> Result<(PotTemplate, Currency, Vec<User>), ExpenseError> {
+            // fails with NotFound if the new owner does not exist
+            self.user_service.get_user_by_id(new_owner_id).await?;
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let template = diesel::update(pot_templates.filter(id.eq(target_pot_template_id)))
+                .set(owner_id.eq(new_owner_id))
+                .returning(PotTemplate::as_returning())
+                .get_result::<PotTemplate>(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?
+                .ok_or_else(|| NotFound(format!(
+                    "Pot template {} could not be found",
+                    target_pot_template_id
+                )))?;
+
+            // the owner must always be part of the users of the template
+            if !self.is_user_in_template(target_pot_template_id, new_owner_id).await {
+                diesel::insert_into(pot_template_users)
+                    .values(NewPotTemplateUser::new(new_owner_id, target_pot_template_id))
+                    .execute(&mut conn)
+                    .await
+                    .map_err(internal_error)?;
+            }
+
+            // the cron job holds a copy of the template, so it has to be replaced to create
+            // the next pots for the new owner
+            self.cron_manager_service
+                .lock()
+                .await
+                .remove_cron_job_with_id(target_pot_template_id)
+                .await;
+            self.add_template_cron_job(template.clone()).await;
+            self.start_cron_jobs().await;
+
+            self.get_own_template_by_id(new_owner_id, template.id()).await
+        }
+
         async fn get_users_for_pot_template(&self, template : &PotTemplate)
             -> Result<Vec<User>, Error> {
             let mut conn = self.db_pool.get().await.map_err(internal_error)?;
@@ -475,7 +559,9 @@ pub mod pot_template_service {
             // 3. create a new pot automatically using the information from the pot template
             let new_pot = NewPot::from_template(template, &template_name);
 
-            let create_pot_result = pot_service.create_pot(new_pot).await;
+            let create_pot_result = pot_service
+                .create_pot_from_template(new_pot, template_id)
+                .await;
             if let Err(error) = create_pot_result {
                 error!("Failed to create a new pot from template {}: {}", template_id, error);
                 return;
@@ -0,0 +1,89 @@
+pub mod test_support {
+    use crate::currency_service::currency_service;
+    use crate::pot_service::pot_service;
+    use crate::user_service::user_service;
+    use expense_tracker_db::currencies::currencies::Currency;
+    use expense_tracker_db::pots::pots::{NewPot, Pot, PotToUser};
+    use expense_tracker_db::setup::DbPool;
+    use expense_tracker_db::test_support::test_db;
+    use expense_tracker_db::users::users::User;
+    use uuid::Uuid;
+
+    /// The starting point of tests that need a database: new users and a currency on the test
+    /// database, see `expense_tracker_db::test_support`. Pots are created through the services,
+    /// so they come with the same members and events as those of real users.
+    pub struct TestFixture {
+        pool: DbPool,
+        users: Vec<Uuid>,
+        currency: Currency,
+    }
+
+    impl TestFixture {
+        /// Sets up the test database and creates the given number of new users on it.
+        pub async fn new(user_count: usize) -> Self {
+            let pool = test_db().await;
+            let user_service = user_service::new_service(pool.clone());
+
+            let users: Vec<Uuid> = (0..user_count).map(|_| Uuid::new_v4()).collect();
+
+            for user in &users {
+                user_service
+                    .create_user(User::new(*user, user.to_string()))
+                    .await
+                    .expect("the test user could not be created");
+            }
+
+            let currency = currency_service::new_service(pool.clone())
+                .get_currencies()
+                .await
+                .expect("the currencies could not be loaded")
+                .remove(0);
+
+            TestFixture {
+                pool,
+                users,
+                currency,
+            }
+        }
+
+        /// Getter for pool.
+        pub fn pool(&self) -> DbPool {
+            self.pool.clone()
+        }
+
+        /// Gets the user with the given index, in the order they were created in.
+        pub fn user(&self, index: usize) -> Uuid {
+            self.users[index]
+        }
+
+        /// Getter for currency.
+        pub fn currency(&self) -> &Currency {
+            &self.currency
+        }
+
+        /// Creates a pot in the currency of the fixture, owned by the given user and with the
+        /// given users as members.
+        pub async fn create_pot(&self, name: &str, owner: Uuid, members: &[Uuid]) -> Pot {
+            let service = pot_service::new_service(self.pool.clone());
+
+            let (pot, _, _) = service
+                .create_pot(NewPot::new(owner, name.to_string(), self.currency.id()))
+                .await
+                .expect("the test pot could not be created");
+
+            if !members.is_empty() {
+                let pot_members = members
+                    .iter()
+                    .map(|member| PotToUser::new(pot.id(), *member))
+                    .collect();
+
+                service
+                    .add_users_to_pot(pot_members, owner)
+                    .await
+                    .expect("the members could not be added to the test pot");
+            }
+
+            pot
+        }
+    }
+}
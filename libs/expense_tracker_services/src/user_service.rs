@@ -1,13 +1,32 @@
 pub mod user_service {
+    use crate::ExpenseError::{BadRequest, Conflict, NotFound};
     use crate::{internal_error, not_found_error, ExpenseError};
-    use diesel::{ExpressionMethods, QueryDsl};
+    use diesel::dsl::exists;
+    use diesel::{BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl};
     use diesel_async::RunQueryDsl;
     use expense_tracker_db::schema as expense_tracker_db_schema;
+    use expense_tracker_db::schema::{
+        expense_splits, expenses, pot_templates, pots, pots_to_users, settlements,
+    };
     use expense_tracker_db::schema::users::id;
     use expense_tracker_db::setup::DbPool;
-    use expense_tracker_db::users::users::User;
+    use expense_tracker_db::users::users::{User, UserProfile};
+    use std::collections::HashMap;
+    use std::sync::{LazyLock, Mutex};
+    use std::time::{Duration, Instant};
     use uuid::Uuid;
 
+    /// The minimum time between two syncs of the profile of a user with their token.
+    const PROFILE_SYNC_INTERVAL: Duration = Duration::from_secs(300);
+
+    /// The maximum length of a display name.
+    const MAX_DISPLAY_NAME_LENGTH: usize = 24;
+
+    /// When the profile of each user has last been synced. Shared by all instances of the
+    /// service, so each user is synced at most once per interval.
+    static LAST_PROFILE_SYNCS: LazyLock<Mutex<HashMap<Uuid, Instant>>> =
+        LazyLock::new(|| Mutex::new(HashMap::new()));
+
     /// A service to interact with user context.
     #[derive(Clone)]
     pub struct UserService {
@@ -53,6 +72,147 @@ pub mod user_service {
             Ok(users)
         }
 
+        /// Updates the stored profile of the user with the given one, as given by the claims of
+        /// their token. The profile is only written if it changed, and at most once per
+        /// `PROFILE_SYNC_INTERVAL`. Unknown users are ignored, since they are created on their
+        /// first call of `current_user`.
+        pub async fn sync_profile(&self, user_id: Uuid, profile: UserProfile) -> Result<(), ExpenseError> {
+            if !Self::is_profile_sync_due(user_id, Instant::now()) {
+                return Ok(());
+            }
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let user = expense_tracker_db_schema::users::table
+                .filter(id.eq(user_id))
+                .first::<User>(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?;
+
+            if let Some(user) = user && user.differs_from(&profile) {
+                diesel::update(expense_tracker_db_schema::users::table.filter(id.eq(user_id)))
+                    .set(profile)
+                    .execute(&mut conn)
+                    .await
+                    .map_err(internal_error)?;
+            }
+
+            Ok(())
+        }
+
+        /// Checks if the profile of the user should be synced at the given time and, if so,
+        /// remembers the time as the last sync.
+        pub(crate) fn is_profile_sync_due(user_id: Uuid, now: Instant) -> bool {
+            let mut last_syncs = LAST_PROFILE_SYNCS.lock().unwrap_or_else(|e| e.into_inner());
+
+            let is_due = last_syncs
+                .get(&user_id)
+                .is_none_or(|last_sync| now.duration_since(*last_sync) >= PROFILE_SYNC_INTERVAL);
+
+            if is_due {
+                // forgetting users whose sync is due anyway, so the map doesn't keep growing
+                last_syncs.retain(|_, last_sync| now.duration_since(*last_sync) < PROFILE_SYNC_INTERVAL);
+                last_syncs.insert(user_id, now);
+            }
+
+            is_due
+        }
+
+        /// Sets the display name of the user, which overrides the name given by the OIDC
+        /// provider. Removes the display name, if `None` is given.
+        pub async fn set_display_name(
+            &self,
+            user_id: Uuid,
+            display_name: Option<String>,
+        ) -> Result<User, ExpenseError> {
+            let display_name = display_name.map(|name| name.trim().to_string());
+
+            if let Some(name) = &display_name
+                && (name.is_empty() || name.chars().count() > MAX_DISPLAY_NAME_LENGTH)
+            {
+                return Err(BadRequest(format!(
+                    "The display name must have between 1 and {} characters",
+                    MAX_DISPLAY_NAME_LENGTH
+                )));
+            }
+
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            diesel::update(expense_tracker_db_schema::users::table.filter(id.eq(user_id)))
+                .set(expense_tracker_db_schema::users::display_name.eq(display_name))
+                .get_result::<User>(&mut conn)
+                .await
+                .optional()
+                .map_err(internal_error)?
+                .ok_or_else(|| NotFound(format!("User {} could not be found", user_id)))
+        }
+
+        /// Gets the users that share at least one pot with the requester, including the requester.
+        pub async fn get_users_sharing_pots(&self, requester_id: Uuid) -> Result<Vec<User>, ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let requester_pots: Vec<i32> = pots_to_users::table
+                .filter(pots_to_users::user_id.eq(requester_id))
+                .select(pots_to_users::pot_id)
+                .get_results(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            let shared_users = pots_to_users::table
+                .filter(pots_to_users::pot_id.eq_any(requester_pots))
+                .select(pots_to_users::user_id);
+
+            expense_tracker_db_schema::users::table
+                .filter(id.eq(requester_id).or(id.eq_any(shared_users)))
+                .get_results(&mut conn)
+                .await
+                .map_err(internal_error)
+        }
+
+        /// Deletes the user with the given id and removes them from all pots and templates.
+        /// Users that own pots, templates or expenses, or that are part of expenses or
+        /// settlements, can't be deleted, so the balances of other users don't change.
+        pub async fn delete_user(&self, user_id: Uuid) -> Result<(), ExpenseError> {
+            let mut conn = self.db_pool.get().await.map_err(internal_error)?;
+
+            let is_referenced = diesel::select(
+                exists(pots::table.filter(pots::owner_id.eq(user_id)))
+                    .or(exists(pot_templates::table.filter(pot_templates::owner_id.eq(user_id))))
+                    .or(exists(expenses::table.filter(expenses::owner_id.eq(user_id))))
+                    .or(exists(expense_splits::table.filter(expense_splits::user_id.eq(user_id))))
+                    .or(exists(
+                        settlements::table.filter(
+                            settlements::payer_id
+                                .eq(user_id)
+                                .or(settlements::receiver_id.eq(user_id)),
+                        ),
+                    )),
+            )
+            .get_result::<bool>(&mut conn)
+            .await
+            .map_err(internal_error)?;
+
+            if is_referenced {
+                return Err(Conflict(format!(
+                    "User {} owns pots, templates or expenses, or is part of expenses or settlements",
+                    user_id
+                )));
+            }
+
+            // memberships of pots and templates are deleted by the database
+            let deleted = diesel::delete(expense_tracker_db_schema::users::table.filter(id.eq(user_id)))
+                .execute(&mut conn)
+                .await
+                .map_err(internal_error)?;
+
+            if deleted == 0 {
+                return Err(NotFound(format!("User {} could not be found", user_id)));
+            }
+
+            Ok(())
+        }
+
         /// Gets the user by the given Uuid. Returns a NotFoundError if no user with the given Id
         /// exists.
         pub async fn get_user_by_id(&self, user_id: Uuid) -> Result<User, ExpenseError> {
@@ -73,3 +233,37 @@ pub mod user_service {
         UserService { db_pool: pool }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::user_service::user_service::UserService;
+    use expense_tracker_db::users::users::{User, UserProfile};
+    use std::time::{Duration, Instant};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_is_profile_sync_due() {
+        let user_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        assert!(UserService::is_profile_sync_due(user_id, now));
+        assert!(!UserService::is_profile_sync_due(user_id, now + Duration::from_secs(299)));
+        assert!(UserService::is_profile_sync_due(user_id, now + Duration::from_secs(300)));
+
+        // other users are synced independently
+        assert!(UserService::is_profile_sync_due(Uuid::new_v4(), now));
+    }
+
+    #[test]
+    fn test_profile_differs() {
+        let user = User::new(Uuid::new_v4(), "alice".to_string());
+        let profile = |name: Option<&str>, email: Option<&str>| {
+            UserProfile::new(name.map(str::to_string), email.map(str::to_string), None)
+        };
+
+        assert!(!user.differs_from(&profile(Some("alice"), None)));
+        assert!(!user.differs_from(&profile(None, None)));
+        assert!(user.differs_from(&profile(Some("alicia"), None)));
+        assert!(user.differs_from(&profile(Some("alice"), Some("alice@example.com"))));
+    }
+}